@@ -0,0 +1,109 @@
+//! Generates the bindings and documentation of the `hapi` abi from `abi/hapi.json`.
+//! ```text
+//! cargo run -p hapi-abi            Regenerate the files
+//! cargo run -p hapi-abi -- --check Fail if a generated file is out of date
+//! ```
+
+mod markdown;
+mod rust;
+mod schema;
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+};
+
+use schema::Schema;
+
+/// The schema, relative to the workspace root
+const SCHEMA: &str = "abi/hapi.json";
+
+fn main() -> ExitCode {
+    let check = match std::env::args().nth(1).as_deref() {
+        None => false,
+        Some("--check") => true,
+        Some(_) => {
+            eprintln!("usage: hapi-abi [--check]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(check) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(check: bool) -> Result<(), String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("hapi-abi lives in the workspace")
+        .to_path_buf();
+
+    let source = std::fs::read_to_string(root.join(SCHEMA))
+        .map_err(|e| format!("failed to read {}: {}", SCHEMA, e))?;
+    let schema = Schema::parse(&source).map_err(|e| format!("{}: {}", SCHEMA, e))?;
+
+    let outputs = [
+        ("src/ffi.rs", rustfmt(&rust::ffi(&schema))?),
+        ("src/abi.rs", rustfmt(&rust::bindings(&schema))?),
+        ("docs/abi.md", markdown::reference(&schema)),
+    ];
+
+    let mut stale = Vec::new();
+    for (path, contents) in outputs {
+        let path: PathBuf = root.join(path);
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        if current == contents {
+            continue;
+        }
+        if check {
+            stale.push(path.display().to_string());
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        println!("Wrote {}", path.display());
+    }
+
+    if !stale.is_empty() {
+        return Err(format!(
+            "out of date, run `cargo run -p hapi-abi`:\n  {}",
+            stale.join("\n  ")
+        ));
+    }
+    Ok(())
+}
+
+/// Format generated code with rustfmt
+fn rustfmt(source: &str) -> Result<String, String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run rustfmt: {}", e))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "rustfmt failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
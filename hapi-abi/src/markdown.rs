@@ -0,0 +1,99 @@
+//! Generates the reference of the abi in `docs/abi.md`.
+
+use std::fmt::Write;
+
+use crate::{
+    rust::{ffi_doc, ffi_signature, host_signature},
+    schema::Schema,
+};
+
+/// Generate `docs/abi.md`
+pub fn reference(schema: &Schema) -> String {
+    let mut out = String::new();
+    writeln!(out, "# The `{}` abi\n", schema.module).unwrap();
+    writeln!(
+        out,
+        "<!-- Generated from `abi/hapi.json` by `cargo run -p hapi-abi`, do not edit by hand. -->\n"
+    )
+    .unwrap();
+    out.push_str(
+        "Every import lives in the `hapi` wasm import module.
+
+- Strings are null-terminated and utf-8.
+- Ids are uuids, written to 37 byte buffers including the null terminator.
+- Memory returned by the host is allocated with `hapi_mem_alloc`, and is owned by the caller.
+- Failures are reported as negative codes.
+
+Hosts implement the `hapi::abi::Imports` trait to serve the imports of a process.
+The memory and thread imports are implemented by the wasm runtime instead.
+
+## Linking
+
+wasm imports are resolved when a module is instantiated, before any of it's code runs.
+A module built against this abi imports every call below, so a host that leaves one out can't load it at all,
+and the module never gets to check `hapi_abi_version` or `hapi_abi_capabilities`.
+Hosts must therefore define every import of the abi, including the ones added after the version they report
+and the ones of capabilities they don't support. Those are stubs that return `-1` from calls that report failures
+as negative codes, a null pointer from calls that return memory, and zero or nothing from the rest.
+Kernels that predate version 2 have no `hapi_abi_version` and can't load modules built against this abi.
+",
+    );
+
+    writeln!(
+        out,
+        "\nThis is version {} of the abi, as returned by `hapi_abi_version`.",
+        schema.version
+    )
+    .unwrap();
+    out.push_str(
+        "Imports are grouped into capabilities, which a host reports as a bit set from `hapi_abi_capabilities`.
+
+| Capability | Bit | |
+| --- | --- | --- |
+",
+    );
+    for capability in &schema.capabilities {
+        writeln!(
+            out,
+            "| `{}` | {} | {} |",
+            capability.name, capability.bit, capability.doc
+        )
+        .unwrap();
+    }
+
+    let mut group = "";
+    for call in &schema.calls {
+        let call_group = call.short_name().split('_').next().unwrap_or_default();
+        if call_group != group {
+            group = call_group;
+            writeln!(out, "\n## {}", group).unwrap();
+        }
+
+        writeln!(out, "\n### `{}`\n", call.name).unwrap();
+        writeln!(out, "```rust\nfn {}\n```\n", ffi_signature(call)).unwrap();
+        for line in ffi_doc(schema, call) {
+            match line.strip_prefix("### ") {
+                Some(heading) => writeln!(out, "\n#### {}\n", heading).unwrap(),
+                None => writeln!(out, "{}", line).unwrap(),
+            }
+        }
+
+        if let Some(capability) = &call.capability {
+            writeln!(out, "\nCapability: `{}`", capability).unwrap();
+        }
+        if call.since > 1 {
+            writeln!(out, "\nSince version {}", call.since).unwrap();
+        }
+        if call.runtime {
+            out.push_str("\nImplemented by the wasm runtime.\n");
+        } else {
+            let host = call
+                .variant_of
+                .as_deref()
+                .and_then(|variant_of| schema.call(variant_of))
+                .unwrap_or(call);
+            writeln!(out, "\nHost: `fn {}`", host_signature(host)).unwrap();
+        }
+    }
+    out
+}
@@ -0,0 +1,397 @@
+//! Generates the rust bindings: the extern block in `src/ffi.rs`,
+//! and the code enums and host trait in `src/abi.rs`.
+
+use std::fmt::Write;
+
+use crate::schema::{Call, Param, ParamType, ReturnType, Schema};
+
+/// The header of every generated file
+const GENERATED: &str =
+    "Generated from `abi/hapi.json` by `cargo run -p hapi-abi`, do not edit by hand.";
+
+/// The request methods, documented on the network calls
+const METHODS: [&str; 9] = [
+    "Get", "Head", "Post", "Put", "Delete", "Connect", "Options", "Trace", "Patch",
+];
+
+/// Generate `src/ffi.rs`
+pub fn ffi(schema: &Schema) -> String {
+    let mut out = String::new();
+    writeln!(out, "//! The `{}` wasm imports.", schema.module).unwrap();
+    writeln!(out, "//! {}", GENERATED).unwrap();
+    out.push_str("#[cfg(not(feature = \"sim\"))]\nuse core::ffi::c_void;\n\n");
+    out.push_str("#[cfg(feature = \"sim\")]\npub use crate::sim::ffi::*;\n\n");
+    out.push_str("#[cfg(not(feature = \"sim\"))]\n");
+    writeln!(out, "#[link(wasm_import_module = \"{}\")]", schema.module).unwrap();
+    out.push_str("extern \"C\" {\n");
+    for call in &schema.calls {
+        for line in ffi_doc(schema, call) {
+            writeln!(
+                out,
+                "    ///{}{}",
+                if line.is_empty() { "" } else { " " },
+                line
+            )
+            .unwrap();
+        }
+        writeln!(out, "    pub fn {};", ffi_signature(call)).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// The signature of the import, without `pub fn`
+pub fn ffi_signature(call: &Call) -> String {
+    let params: Vec<String> = call
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, ffi_param(param)))
+        .collect();
+    let mut signature = format!("{}({})", call.name, params.join(", "));
+    if let Some(ret) = ffi_return(call) {
+        write!(signature, " -> {}", ret).unwrap();
+    }
+    signature
+}
+
+fn ffi_param(param: &Param) -> &'static str {
+    match param.ty {
+        ParamType::U8 => "u8",
+        ParamType::U32 => "u32",
+        ParamType::I32 => "i32",
+        ParamType::F64 => "f64",
+        ParamType::Cstr | ParamType::Str | ParamType::Bytes => "*const u8",
+        ParamType::BytesMut | ParamType::IdOut | ParamType::StrOut => "*mut u8",
+        ParamType::Ptr => "*mut c_void",
+        ParamType::FnPtr => "*const c_void",
+    }
+}
+
+fn ffi_return(call: &Call) -> Option<&'static str> {
+    match call.returns.ty {
+        ReturnType::Void => None,
+        ReturnType::Code => Some("i32"),
+        ReturnType::Value => call.returns.abi.map(|abi| abi.rust()),
+        ReturnType::AllocStr | ReturnType::AllocBytes => Some("*const u8"),
+        ReturnType::Ptr => Some("*mut c_void"),
+    }
+}
+
+/// The documentation of an import, line by line
+pub fn ffi_doc(schema: &Schema, call: &Call) -> Vec<String> {
+    let mut lines = call.doc.clone();
+    if let Some(note) = &call.note {
+        lines.push("### Note".to_owned());
+        lines.push(note.clone());
+    }
+
+    let returns = returns_doc(call);
+    if !returns.is_empty() {
+        lines.push("### Returns".to_owned());
+        lines.extend(returns);
+    }
+
+    if call.methods {
+        lines.push("### Methods".to_owned());
+        for (value, method) in METHODS.iter().enumerate() {
+            lines.push(format!("- {} = {}", method, value));
+        }
+    }
+
+    if let Some(panics) = &call.panics {
+        lines.push("### Panics".to_owned());
+        lines.push(panics.clone());
+    }
+
+    let safety = safety_doc(schema, call);
+    if !safety.is_empty() {
+        lines.push("### Safety".to_owned());
+        lines.extend(safety);
+    }
+    lines
+}
+
+fn returns_doc(call: &Call) -> Vec<String> {
+    let mut lines = Vec::new();
+    if call.returns.ty != ReturnType::Code {
+        if let Some(doc) = &call.returns.doc {
+            lines.push(format!("- {}", doc));
+        }
+    }
+    for code in &call.returns.codes {
+        lines.push(format!("- `{}` {}", code.value, code.doc));
+    }
+    lines
+}
+
+fn safety_doc(schema: &Schema, call: &Call) -> Vec<String> {
+    let mut lines = Vec::new();
+    for param in &call.params {
+        let name = &param.name;
+        let line = match param.ty {
+            ParamType::Cstr => format!(
+                "- `{}` must be a valid null-terminated string or unallocated memory will be read from.",
+                name
+            ),
+            ParamType::Str | ParamType::Bytes => format!(
+                "- `{}` must be at least `{}` bytes long or unallocated memory will be read from.",
+                name,
+                param.len.as_deref().unwrap_or_default()
+            ),
+            ParamType::BytesMut => format!(
+                "- `{}` must be at least `{}` bytes long or unallocated memory will be written to.",
+                name,
+                param.len.as_deref().unwrap_or_default()
+            ),
+            ParamType::IdOut if param.nullable => format!(
+                "- `{}` must be null or at least 37-bytes long or unallocated memory will be written to.",
+                name
+            ),
+            ParamType::IdOut => format!(
+                "- `{}` must be at least 37-bytes long or unallocated memory will be written to.",
+                name
+            ),
+            ParamType::StrOut => format!(
+                "- `{}` must be at least the size of `{}` or unallocated memory will be written to.",
+                name,
+                param.length.as_deref().unwrap_or_default()
+            ),
+            ParamType::Ptr if call.name == "hapi_mem_realloc" || call.name == "hapi_mem_free" => {
+                format!("- `{}` must be a block allocated with `hapi_mem_alloc`.", name)
+            }
+            _ => continue,
+        };
+        lines.push(line);
+    }
+
+    match call.returns.ty {
+        ReturnType::AllocStr => lines.push(
+            "- The returned string must be freed with `hapi_mem_free` or memory will be leaked."
+                .to_owned(),
+        ),
+        ReturnType::AllocBytes => {
+            let length = call
+                .returns
+                .length
+                .as_deref()
+                .and_then(|length| schema.call(length))
+                .map(|length| format!(", it's length is returned by `{}`", length.name))
+                .unwrap_or_default();
+            lines.push(format!(
+                "- The returned bytes must be freed with `hapi_mem_free` or memory will be leaked{}.",
+                length
+            ));
+        }
+        _ => {}
+    }
+    lines
+}
+
+/// Generate `src/abi.rs`
+pub fn bindings(schema: &Schema) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "//! Typed codes of the `{}` imports, and the trait a host implements to serve them.",
+        schema.module
+    )
+    .unwrap();
+    writeln!(out, "//! {}", GENERATED).unwrap();
+    out.push('\n');
+    out.push_str("use crate::prelude::*;\n\n");
+    writeln!(
+        out,
+        "/// The version of the abi the bindings were generated from\npub const ABI_VERSION: u32 = {};\n",
+        schema.version
+    )
+    .unwrap();
+    capability_enum(&mut out, schema);
+    out.push('\n');
+    out.push_str(
+        "/// A code returned by a `hapi_*` import
+pub trait AbiCode: Copy {
+    /// The raw value of the code
+    fn code(self) -> i32;
+
+    /// Get the code of a raw value
+    fn from_code(code: i32) -> Self;
+}
+",
+    );
+
+    for call in schema.calls.iter().filter(|call| call.variant_of.is_none()) {
+        if let Some(name) = call.code_enum() {
+            out.push('\n');
+            code_enum(&mut out, call, &name);
+        }
+    }
+
+    out.push('\n');
+    out.push_str(
+        "/// The `hapi` imports, implemented by a honeyos kernel or simulator for a single process.
+/// Every method serves the import with the same name, with pointers replaced by rust types.
+/// Output buffers become return values, and codes become enums.
+///
+/// The memory and thread imports work on the guest's memory and function table,
+/// so they are implemented by the wasm runtime and are not part of the trait.
+/// Variants of an import, such as the `_v2` imports, are served by the same method.
+pub trait Imports {
+",
+    );
+    let mut first = true;
+    for call in schema
+        .calls
+        .iter()
+        .filter(|call| !call.runtime && call.variant_of.is_none())
+    {
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+        for line in &call.doc {
+            writeln!(out, "    /// {}", line).unwrap();
+        }
+        writeln!(out, "    fn {};", host_signature(call)).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn capability_enum(out: &mut String, schema: &Schema) {
+    out.push_str(
+        "/// A group of imports a host may or may not support, reported by `hapi_abi_capabilities`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+",
+    );
+    for capability in &schema.capabilities {
+        writeln!(out, "    /// {}", capability.doc).unwrap();
+        writeln!(out, "    {},", capability.name).unwrap();
+    }
+    out.push_str("}\n\nimpl Capability {\n");
+
+    writeln!(
+        out,
+        "    /// Every capability\n    pub const ALL: [Capability; {}] = [{}];\n",
+        schema.capabilities.len(),
+        schema
+            .capabilities
+            .iter()
+            .map(|capability| format!("Self::{}", capability.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+
+    out.push_str(
+        "    /// The bit of the capability in `hapi_abi_capabilities`\n    pub fn bit(self) -> u32 {\n        match self {\n",
+    );
+    for capability in &schema.capabilities {
+        writeln!(
+            out,
+            "            Self::{} => 1 << {},",
+            capability.name, capability.bit
+        )
+        .unwrap();
+    }
+    out.push_str("        }\n    }\n\n");
+
+    out.push_str(
+        "    /// The imports that belong to the capability\n    pub fn calls(self) -> &'static [&'static str] {\n        match self {\n",
+    );
+    for capability in &schema.capabilities {
+        let calls: Vec<String> = schema
+            .capability_calls(capability)
+            .map(|call| format!("\"{}\"", call.name))
+            .collect();
+        writeln!(
+            out,
+            "            Self::{} => &[{}],",
+            capability.name,
+            calls.join(", ")
+        )
+        .unwrap();
+    }
+    out.push_str("        }\n    }\n}\n");
+}
+
+fn code_enum(out: &mut String, call: &Call, name: &str) {
+    writeln!(out, "/// The codes of `{}`", call.name).unwrap();
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n");
+    writeln!(out, "pub enum {} {{", name).unwrap();
+    for code in call.enum_codes() {
+        writeln!(out, "    /// `{}` {}", code.value, code.doc).unwrap();
+        writeln!(out, "    {},", code.name).unwrap();
+    }
+    out.push_str("    /// A code that is not part of the abi\n    Unknown(i32),\n}\n\n");
+
+    writeln!(out, "impl AbiCode for {} {{", name).unwrap();
+    out.push_str("    fn code(self) -> i32 {\n        match self {\n");
+    for code in call.enum_codes() {
+        writeln!(out, "            Self::{} => {},", code.name, code.value).unwrap();
+    }
+    out.push_str("            Self::Unknown(code) => code,\n        }\n    }\n\n");
+    out.push_str("    fn from_code(code: i32) -> Self {\n        match code {\n");
+    for code in call.enum_codes() {
+        writeln!(out, "            {} => Self::{},", code.value, code.name).unwrap();
+    }
+    out.push_str("            code => Self::Unknown(code),\n        }\n    }\n}\n\n");
+
+    writeln!(
+        out,
+        "impl From<i32> for {} {{\n    fn from(code: i32) -> Self {{\n        Self::from_code(code)\n    }}\n}}\n",
+        name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "impl From<{}> for i32 {{\n    fn from(code: {}) -> Self {{\n        code.code()\n    }}\n}}",
+        name, name
+    )
+    .unwrap();
+}
+
+/// The signature of the host method serving the import, without `fn`
+pub fn host_signature(call: &Call) -> String {
+    let mut params = vec!["&mut self".to_owned()];
+    for param in &call.params {
+        if param.is_out() || call.is_len(param) {
+            continue;
+        }
+        let ty = match param.ty {
+            ParamType::U8 => "u8",
+            ParamType::U32 => "u32",
+            ParamType::I32 => "i32",
+            ParamType::F64 => "f64",
+            ParamType::Cstr | ParamType::Str => "&str",
+            ParamType::Bytes => "&[u8]",
+            ParamType::BytesMut => "&mut [u8]",
+            ParamType::Ptr | ParamType::FnPtr => "usize",
+            ParamType::IdOut | ParamType::StrOut => unreachable!(),
+        };
+        params.push(format!("{}: {}", param.name, ty));
+    }
+
+    let mut signature = format!("{}({})", call.short_name(), params.join(", "));
+    if let Some(ret) = host_return(call) {
+        write!(signature, " -> {}", ret).unwrap();
+    }
+    signature
+}
+
+/// The type returned by the host method serving the import
+pub fn host_return(call: &Call) -> Option<String> {
+    let value = call.returns.value.map(|value| value.rust().to_owned());
+    match (call.returns.ty, call.code_enum(), call.out_param()) {
+        (ReturnType::Void, _, Some(_)) => Some("String".to_owned()),
+        (ReturnType::Void, _, None) => None,
+        (ReturnType::Code, Some(codes), Some(_)) => Some(format!("Result<String, {}>", codes)),
+        (ReturnType::Code, codes, None) => codes,
+        (ReturnType::Value, Some(codes), _) => Some(format!("Result<{}, {}>", value?, codes)),
+        (ReturnType::Value, None, _) => value,
+        (ReturnType::AllocStr, _, _) => Some("Option<String>".to_owned()),
+        (ReturnType::AllocBytes, _, _) => Some("Option<Vec<u8>>".to_owned()),
+        (ReturnType::Ptr, _, _) => Some("usize".to_owned()),
+        (ReturnType::Code, None, Some(_)) => unreachable!(),
+    }
+}
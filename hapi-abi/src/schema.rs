@@ -0,0 +1,385 @@
+//! The description of the `hapi` abi, as read from `abi/hapi.json`.
+
+use std::{collections::HashSet, fmt::Display};
+
+use serde::Deserialize;
+
+/// The whole abi
+#[derive(Debug, Deserialize)]
+pub struct Schema {
+    /// The wasm import module
+    pub module: String,
+    /// The version of the abi, bumped whenever a call is added or changed
+    pub version: u32,
+    pub capabilities: Vec<Capability>,
+    pub calls: Vec<Call>,
+}
+
+/// A group of calls a host may or may not support
+#[derive(Debug, Deserialize)]
+pub struct Capability {
+    pub name: String,
+    /// The bit of the capability in `hapi_abi_capabilities`
+    pub bit: u32,
+    pub doc: String,
+}
+
+/// A single import
+#[derive(Debug, Deserialize)]
+pub struct Call {
+    pub name: String,
+    /// The capability the call belongs to, if it can be missing from a host
+    #[serde(default)]
+    pub capability: Option<String>,
+    /// The version of the abi that added the call
+    #[serde(default = "first_version")]
+    pub since: u32,
+    /// The call this is a variant of, which shares it's codes and host method
+    #[serde(default)]
+    pub variant_of: Option<String>,
+    pub doc: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub panics: Option<String>,
+    /// Whether the call works on the guest's memory or function table,
+    /// and is implemented by the wasm runtime rather than the kernel
+    #[serde(default)]
+    pub runtime: bool,
+    /// Whether to document the request methods
+    #[serde(default)]
+    pub methods: bool,
+    pub params: Vec<Param>,
+    pub returns: Returns,
+}
+
+/// A parameter of an import
+#[derive(Debug, Deserialize)]
+pub struct Param {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: ParamType,
+    /// The parameter holding the length of a buffer
+    #[serde(default)]
+    pub len: Option<String>,
+    /// The call that returns the length of an out string
+    #[serde(default)]
+    pub length: Option<String>,
+    /// The code returned when the string can't be read
+    #[serde(default)]
+    pub invalid: Option<String>,
+    /// Whether an out buffer can be null
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamType {
+    U8,
+    U32,
+    I32,
+    F64,
+    /// A null-terminated string
+    Cstr,
+    /// A string that is not null-terminated, with it's length in `len`
+    Str,
+    /// A buffer read by the host, with it's length in `len`
+    Bytes,
+    /// A buffer written by the host, with it's length in `len`
+    BytesMut,
+    /// A 37 byte buffer the host writes an id to
+    IdOut,
+    /// A buffer the host writes a null-terminated string to, sized by the `length` call
+    StrOut,
+    /// A block of memory allocated with `hapi_mem_alloc`
+    Ptr,
+    /// A function pointer
+    FnPtr,
+}
+
+/// The return value of an import
+#[derive(Debug, Deserialize)]
+pub struct Returns {
+    #[serde(rename = "type")]
+    pub ty: ReturnType,
+    /// The abi type of a value
+    #[serde(default)]
+    pub abi: Option<Scalar>,
+    /// The type of a value once it is decoded
+    #[serde(default)]
+    pub value: Option<Scalar>,
+    #[serde(default)]
+    pub doc: Option<String>,
+    /// The call that returns the length of allocated bytes
+    #[serde(default)]
+    pub length: Option<String>,
+    #[serde(default)]
+    pub codes: Vec<Code>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnType {
+    Void,
+    /// A status code, `0` on success
+    Code,
+    /// A value, or a negative code on failure
+    Value,
+    /// A null-terminated string allocated with `hapi_mem_alloc`, or `NULL`
+    AllocStr,
+    /// Bytes allocated with `hapi_mem_alloc`, or `NULL`
+    AllocBytes,
+    /// A block of memory, or `NULL`
+    Ptr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scalar {
+    I32,
+    U32,
+    F64,
+    Bool,
+}
+
+/// A code returned by an import
+#[derive(Debug, Deserialize)]
+pub struct Code {
+    pub value: i32,
+    pub name: String,
+    pub doc: String,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+impl Schema {
+    /// Parse and validate a schema
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let schema: Schema = serde_json::from_str(source).map_err(|e| e.to_string())?;
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Get a call by it's name
+    pub fn call(&self, name: &str) -> Option<&Call> {
+        self.calls.iter().find(|call| call.name == name)
+    }
+
+    /// Get a capability by it's name
+    pub fn capability(&self, name: &str) -> Option<&Capability> {
+        self.capabilities
+            .iter()
+            .find(|capability| capability.name == name)
+    }
+
+    /// The calls that belong to the capability
+    pub fn capability_calls<'a>(
+        &'a self,
+        capability: &'a Capability,
+    ) -> impl Iterator<Item = &'a Call> {
+        self.calls
+            .iter()
+            .filter(|call| call.capability.as_deref() == Some(&capability.name))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut bits = HashSet::new();
+        for capability in &self.capabilities {
+            if capability.bit >= 32 {
+                return Err(format!("{}: the bit must be below 32", capability.name));
+            }
+            if !bits.insert(capability.bit) {
+                return Err(format!("{}: the bit is already used", capability.name));
+            }
+        }
+
+        let mut names = HashSet::new();
+        for call in &self.calls {
+            let error = |message: &dyn Display| format!("{}: {}", call.name, message);
+            if !call.name.starts_with("hapi_") {
+                return Err(error(&"calls must start with `hapi_`"));
+            }
+            if !names.insert(&call.name) {
+                return Err(error(&"defined twice"));
+            }
+            if let Some(capability) = &call.capability {
+                if self.capability(capability).is_none() {
+                    return Err(error(&format!("`{}` is not a capability", capability)));
+                }
+            }
+            if call.since == 0 || call.since > self.version {
+                return Err(error(&format!(
+                    "`since` must be between 1 and {}",
+                    self.version
+                )));
+            }
+
+            for param in &call.params {
+                if let Some(len) = &param.len {
+                    match call.param(len) {
+                        Some(len) if len.ty == ParamType::U32 => {}
+                        _ => return Err(error(&format!("`{}` is not a u32 parameter", len))),
+                    }
+                } else if matches!(
+                    param.ty,
+                    ParamType::Str | ParamType::Bytes | ParamType::BytesMut
+                ) {
+                    return Err(error(&format!("`{}` has no length", param.name)));
+                }
+                if let Some(length) = &param.length {
+                    if self.call(length).is_none() {
+                        return Err(error(&format!("`{}` does not exist", length)));
+                    }
+                } else if param.ty == ParamType::StrOut {
+                    return Err(error(&format!("`{}` has no length call", param.name)));
+                }
+                if let Some(invalid) = &param.invalid {
+                    if !call.returns.codes.iter().any(|code| &code.name == invalid) {
+                        return Err(error(&format!("`{}` is not a code", invalid)));
+                    }
+                }
+            }
+            if call.params.iter().filter(|param| param.is_out()).count() > 1 {
+                return Err(error(&"calls can only have a single out parameter"));
+            }
+
+            if let Some(variant_of) = &call.variant_of {
+                let Some(original) = self.call(variant_of) else {
+                    return Err(error(&format!("`{}` does not exist", variant_of)));
+                };
+                if original.variant_of.is_some() {
+                    return Err(error(&format!("`{}` is a variant itself", variant_of)));
+                }
+                let codes = |call: &Call| -> Vec<(i32, String)> {
+                    call.returns
+                        .codes
+                        .iter()
+                        .map(|code| (code.value, code.name.clone()))
+                        .collect()
+                };
+                if original.returns.ty != call.returns.ty || codes(original) != codes(call) {
+                    return Err(error(&format!(
+                        "variants must return the same as `{}`",
+                        variant_of
+                    )));
+                }
+            }
+
+            let returns = &call.returns;
+            if let Some(length) = &returns.length {
+                if self.call(length).is_none() {
+                    return Err(error(&format!("`{}` does not exist", length)));
+                }
+            }
+            match returns.ty {
+                ReturnType::Code => {
+                    if !returns.codes.iter().any(|code| code.value == 0) {
+                        return Err(error(&"status codes need a success code"));
+                    }
+                }
+                ReturnType::Value => {
+                    if returns.abi.is_none() || returns.value.is_none() {
+                        return Err(error(&"values need an abi and a value type"));
+                    }
+                    if returns.codes.iter().any(|code| code.value >= 0) {
+                        return Err(error(&"value error codes must be negative"));
+                    }
+                }
+                _ if !returns.codes.is_empty() => {
+                    return Err(error(&"only status codes and values can have codes"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Call {
+    /// The name without the `hapi_` prefix
+    pub fn short_name(&self) -> &str {
+        &self.name["hapi_".len()..]
+    }
+
+    /// The name of the call in pascal case, without the `hapi_` prefix
+    pub fn pascal_name(&self) -> String {
+        pascal_case(self.short_name())
+    }
+
+    /// Get a parameter by it's name
+    pub fn param(&self, name: &str) -> Option<&Param> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    /// The parameter the host writes it's output to
+    pub fn out_param(&self) -> Option<&Param> {
+        self.params.iter().find(|param| param.is_out())
+    }
+
+    /// Whether the parameter only holds the length of another parameter
+    pub fn is_len(&self, param: &Param) -> bool {
+        self.params
+            .iter()
+            .any(|other| other.len.as_deref() == Some(&param.name))
+    }
+
+    /// The name of the enum of the codes, if the call returns any.
+    /// Calls that only return codes on failure get an error enum, the rest get a result enum.
+    pub fn code_enum(&self) -> Option<String> {
+        // Variants share the enum of the call they are a variant of
+        let name = match &self.variant_of {
+            Some(variant_of) => pascal_case(variant_of.strip_prefix("hapi_").unwrap_or(variant_of)),
+            None => self.pascal_name(),
+        };
+        match self.returns.ty {
+            ReturnType::Code if self.out_param().is_some() => Some(format!("{}Error", name)),
+            ReturnType::Code => Some(format!("{}Result", name)),
+            ReturnType::Value if !self.returns.codes.is_empty() => Some(format!("{}Error", name)),
+            _ => None,
+        }
+    }
+
+    /// The codes that end up in the code enum
+    pub fn enum_codes(&self) -> impl Iterator<Item = &Code> {
+        let skip_success = self.code_enum().is_some_and(|name| name.ends_with("Error"));
+        self.returns
+            .codes
+            .iter()
+            .filter(move |code| !(skip_success && code.value == 0))
+    }
+}
+
+/// Convert a snake case name to pascal case
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+impl Param {
+    /// Whether the host writes it's output to the parameter
+    pub fn is_out(&self) -> bool {
+        matches!(self.ty, ParamType::IdOut | ParamType::StrOut)
+    }
+}
+
+impl Scalar {
+    /// The rust type
+    pub fn rust(self) -> &'static str {
+        match self {
+            Scalar::I32 => "i32",
+            Scalar::U32 => "u32",
+            Scalar::F64 => "f64",
+            Scalar::Bool => "bool",
+        }
+    }
+}
@@ -4,32 +4,40 @@ use syn::{parse_macro_input, ItemFn, ReturnType};
 
 /// Set the main function as an entrypoint.
 /// The entrypoint can be generic or async.
-/// It can also have an error return type
+/// It can also have an error return type.
+/// It also works in `no_std` apps, which provide their own `#[panic_handler]` and allocator.
+/// The entrypoint is only exported as `_start` on wasm, so natively it can be called to run the app under the sim.
 /// ```
-/// /// Generic
+/// // Generic
 /// #[hapi::main]
 /// fn main() {
 ///     hapi::println!("Hello, world!");
 /// }
-///
-/// /// Async
+/// # fn main() { _start(); assert_eq!(hapi::sim::stdout(), "Hello, world!\n"); }
+/// ```
+/// ```
+/// // Async
 /// #[hapi::main]
 /// async fn main() {
 ///     // async code
 /// }
-///
-/// /// With error
+/// # fn main() { _start(); }
+/// ```
+/// ```
+/// // With error
 /// #[hapi::main]
-/// fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     hapi::println!("Hello, world!");
+/// fn main() -> hapi::Result<()> {
+///     hapi::fs::RamFileSystem::init(hapi::fs::fslabel::FsLabel::A)?;
+///     hapi::display::Display::assume_control()?;
 ///     Ok(())
 /// }
+/// # fn main() { _start(); }
 /// ```
 #[proc_macro_attribute]
 pub fn main(_: TokenStream, item: TokenStream) -> TokenStream {
     let entrypoint = parse_macro_input!(item as ItemFn);
 
-    if entrypoint.sig.ident.to_string() != "main" {
+    if entrypoint.sig.ident != "main" {
         panic!("The entrypoint should be named main")
     }
 
@@ -41,12 +49,12 @@ pub fn main(_: TokenStream, item: TokenStream) -> TokenStream {
     let entrypoint_call = match entrypoint.sig.output {
         ReturnType::Default => entrypoint_call,
         _ => quote! {
-            if cfg!(feature = "logger") {
-                #entrypoint_call.map_err(|e| log::error!("{}", e)).unwrap();
-            }
-            else {
-                #entrypoint_call.unwrap();
+            let result = #entrypoint_call;
+            #[cfg(feature = "logger")]
+            if let Err(error) = &result {
+                log::error!("{}", error);
             }
+            result.unwrap()
         },
     };
     let entrypoint_call = match entrypoint.sig.asyncness {
@@ -59,26 +67,20 @@ pub fn main(_: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     quote! {
-        #[no_mangle]
+        #[cfg_attr(target_arch = "wasm32", no_mangle)]
+        #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
         pub extern "C" fn _start() {
             #entrypoint
 
-            std::panic::set_hook(Box::new(|info| {
-                let message = info.to_string();
-
-                #[cfg(feature = "logger")]
-                log::error("{}", message);
-
-                hapi::println!("\x1b[91m{}\x1b[97m", message);
-            }));
+            hapi::rt::set_panic_hook();
 
             #entrypoint_call;
         }
 
-        #[no_mangle]
+        #[cfg_attr(target_arch = "wasm32", no_mangle)]
+        #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
         pub extern "C" fn _thread_entrypoint(f_ptr: u32) {
-            let func = unsafe { Box::from_raw(f_ptr as *mut Box<dyn FnOnce()>) };
-            (*func)();
+            unsafe { hapi::rt::thread_entrypoint(f_ptr) }
         }
     }
     .into()
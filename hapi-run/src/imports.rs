@@ -0,0 +1,1189 @@
+//! The `hapi` import module, implemented on top of the simulated kernel.
+//! Pointers are checked against the linear memory, an out of bounds access traps instead of corrupting memory.
+
+use hapi::{abi::*, sim::js::LogLevel};
+use wasmi::{
+    core::{Trap, F64},
+    Caller, Linker,
+};
+
+use crate::{
+    memory::{
+        alloc, alloc_bytes, alloc_str, read_bytes, read_str, read_str_len, write_bytes, write_id,
+        write_str,
+    },
+    runner::{ProcessState, Task},
+};
+
+/// The name of the import module
+const MODULE: &str = "hapi";
+
+type Ctx<'a> = Caller<'a, ProcessState>;
+
+/// Define every import of the `hapi` module
+pub fn define(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    define_abi(linker)?;
+    define_js(linker)?;
+    define_stdout(linker)?;
+    define_process(linker)?;
+    define_mem(linker)?;
+    define_display(linker)?;
+    define_time(linker)?;
+    define_browser(linker)?;
+    define_network(linker)?;
+    define_fs(linker)?;
+    define_thread(linker)?;
+    Ok(())
+}
+
+/// Encode a value, or the code it failed with
+fn value<T: Into<i64>, E: AbiCode>(result: Result<T, E>) -> i32 {
+    match result {
+        Ok(value) => value.into() as i32,
+        Err(code) => code.code(),
+    }
+}
+
+/// Write the id to the buffer and return `0`, or return the code
+fn id_result<E: AbiCode>(
+    caller: &mut Ctx,
+    call: &str,
+    buffer: u32,
+    result: Result<String, E>,
+) -> Result<i32, Trap> {
+    match result {
+        Ok(id) => write_id(caller, call, buffer, &id).map(|_| 0),
+        Err(code) => Ok(code.code()),
+    }
+}
+
+/// A string argument, null-terminated or passed with its length by a `_v2` import
+#[derive(Clone, Copy)]
+enum Str {
+    Cstr(u32),
+    Len(u32, u32),
+}
+
+impl Str {
+    fn read(self, caller: &Ctx, call: &str) -> Result<String, Trap> {
+        match self {
+            Self::Cstr(ptr) => read_str(caller, call, ptr),
+            Self::Len(ptr, len) => read_str_len(caller, call, ptr, len),
+        }
+    }
+}
+
+fn log(caller: Ctx, call: &str, level: LogLevel, string: Str) -> Result<(), Trap> {
+    let string = string.read(&caller, call)?;
+    let state = caller.data();
+    state.imports(|imports| match level {
+        LogLevel::Info => imports.js_console_log_info(&string),
+        LogLevel::Warn => imports.js_console_log_warn(&string),
+        LogLevel::Error => imports.js_console_log_error(&string),
+    });
+    state.terminal().log(level, &string);
+    Ok(())
+}
+
+fn define_abi(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(MODULE, "hapi_abi_version", |caller: Ctx| -> u32 {
+        caller.data().imports(|imports| imports.abi_version())
+    })?;
+    linker.func_wrap(MODULE, "hapi_abi_capabilities", |caller: Ctx| -> u32 {
+        caller.data().imports(|imports| imports.abi_capabilities())
+    })?;
+    Ok(())
+}
+
+fn define_js(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_log_info",
+        |caller: Ctx, string: u32| {
+            log(
+                caller,
+                "hapi_js_console_log_info",
+                LogLevel::Info,
+                Str::Cstr(string),
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_log_info_v2",
+        |caller: Ctx, string: u32, string_len: u32| {
+            let string = Str::Len(string, string_len);
+            log(
+                caller,
+                "hapi_js_console_log_info_v2",
+                LogLevel::Info,
+                string,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_log_warn",
+        |caller: Ctx, string: u32| {
+            log(
+                caller,
+                "hapi_js_console_log_warn",
+                LogLevel::Warn,
+                Str::Cstr(string),
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_log_warn_v2",
+        |caller: Ctx, string: u32, string_len: u32| {
+            let string = Str::Len(string, string_len);
+            log(
+                caller,
+                "hapi_js_console_log_warn_v2",
+                LogLevel::Warn,
+                string,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_log_error",
+        |caller: Ctx, string: u32| {
+            log(
+                caller,
+                "hapi_js_console_log_error",
+                LogLevel::Error,
+                Str::Cstr(string),
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_log_error_v2",
+        |caller: Ctx, string: u32, string_len: u32| {
+            let string = Str::Len(string, string_len);
+            log(
+                caller,
+                "hapi_js_console_log_error_v2",
+                LogLevel::Error,
+                string,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_eval",
+        |mut caller: Ctx, source: u32| -> Result<u32, Trap> {
+            const CALL: &str = "hapi_js_console_eval";
+            let source = read_str(&caller, CALL, source)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.js_console_eval(&source));
+            match result {
+                Some(result) => alloc_str(&mut caller, CALL, &result),
+                None => Ok(0),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_js_console_eval_v2",
+        |mut caller: Ctx, source: u32, source_len: u32| -> Result<u32, Trap> {
+            const CALL: &str = "hapi_js_console_eval_v2";
+            let source = read_str_len(&caller, CALL, source, source_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.js_console_eval(&source));
+            match result {
+                Some(result) => alloc_str(&mut caller, CALL, &result),
+                None => Ok(0),
+            }
+        },
+    )?;
+    Ok(())
+}
+
+fn define_stdout(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(MODULE, "hapi_stdout_clear", |caller: Ctx| {
+        let state = caller.data();
+        state.imports(|imports| imports.stdout_clear());
+        if state.root {
+            state.terminal().clear();
+        }
+    })?;
+    linker.func_wrap(MODULE, "hapi_stdout_clear_line", |caller: Ctx| {
+        let state = caller.data();
+        state.imports(|imports| imports.stdout_clear_line());
+        if state.root {
+            state.terminal().clear_lines(1);
+        }
+    })?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_stdout_clear_lines",
+        |caller: Ctx, num: u32| {
+            let state = caller.data();
+            state.imports(|imports| imports.stdout_clear_lines(num));
+            if state.root {
+                state.terminal().clear_lines(num);
+            }
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_stdout_write",
+        |caller: Ctx, string: u32| -> Result<(), Trap> {
+            let string = read_str(&caller, "hapi_stdout_write", string)?;
+            let state = caller.data();
+            state.imports(|imports| imports.stdout_write(&string));
+            if state.root {
+                state.terminal().write(&string);
+            }
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_stdout_write_v2",
+        |caller: Ctx, string: u32, string_len: u32| -> Result<(), Trap> {
+            let string = read_str_len(&caller, "hapi_stdout_write_v2", string, string_len)?;
+            let state = caller.data();
+            state.imports(|imports| imports.stdout_write(&string));
+            if state.root {
+                state.terminal().write(&string);
+            }
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+/// Spawn a subprocess and queue it to start, writing the pid to the buffer unless null
+fn spawn_subprocess(
+    caller: &mut Ctx,
+    call: &str,
+    bin: u32,
+    bin_len: u32,
+    pid_out: u32,
+) -> Result<i32, Trap> {
+    let binary = read_bytes(caller, call, bin, bin_len)?;
+    let state = caller.data();
+    let pid = match state.imports(|imports| imports.process_spawn_subprocess_v2(&binary)) {
+        Ok(pid) => pid,
+        Err(code) => return Ok(code.code()),
+    };
+    state.tasks.borrow_mut().push_back(Task::Start {
+        pid: pid.clone(),
+        binary,
+    });
+    if pid_out != 0 {
+        write_id(caller, call, pid_out, &pid)?;
+    }
+    Ok(0)
+}
+
+fn define_process(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_get_pid",
+        |mut caller: Ctx, buffer: u32| -> Result<(), Trap> {
+            let pid = caller.data().imports(|imports| imports.process_get_pid());
+            write_id(&mut caller, "hapi_process_get_pid", buffer, &pid)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_get_cwd",
+        |mut caller: Ctx, buffer: u32| -> Result<(), Trap> {
+            let cwd = caller.data().imports(|imports| imports.process_get_cwd());
+            write_str(&mut caller, "hapi_process_get_cwd", buffer, &cwd)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_get_cwd_length",
+        |caller: Ctx| -> u32 {
+            caller
+                .data()
+                .imports(|imports| imports.process_get_cwd_length())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_set_cwd",
+        |caller: Ctx, path: u32| -> Result<i32, Trap> {
+            let path = read_str(&caller, "hapi_process_set_cwd", path)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.process_set_cwd(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_set_cwd_v2",
+        |caller: Ctx, path: u32, path_len: u32| -> Result<i32, Trap> {
+            let path = read_str_len(&caller, "hapi_process_set_cwd_v2", path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.process_set_cwd(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_spawn_subprocess",
+        |mut caller: Ctx, bin: u32, bin_len: u32, pid_out: u32| -> Result<(), Trap> {
+            const CALL: &str = "hapi_process_spawn_subprocess";
+            let code = spawn_subprocess(&mut caller, CALL, bin, bin_len, pid_out)?;
+            if code != 0 && pid_out != 0 {
+                write_str(&mut caller, CALL, pid_out, "")?;
+            }
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_spawn_subprocess_v2",
+        |mut caller: Ctx, bin: u32, bin_len: u32, pid_out: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_process_spawn_subprocess_v2";
+            spawn_subprocess(&mut caller, CALL, bin, bin_len, pid_out)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_alive",
+        |caller: Ctx, id: u32| -> Result<i32, Trap> {
+            let id = read_str(&caller, "hapi_process_alive", id)?;
+            let alive = caller.data().imports(|imports| imports.process_alive(&id));
+            Ok(alive as i32)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_stdout",
+        |mut caller: Ctx, id: u32, out_buffer: u32| -> Result<(), Trap> {
+            const CALL: &str = "hapi_process_stdout";
+            let id = read_str(&caller, CALL, id)?;
+            let stdout = caller.data().imports(|imports| {
+                imports
+                    .process_stdout_length(&id)
+                    .map(|_| imports.process_stdout(&id))
+            });
+            match stdout {
+                Ok(stdout) => write_str(&mut caller, CALL, out_buffer, &stdout),
+                Err(_) => Ok(()),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_process_stdout_length",
+        |caller: Ctx, id: u32| -> Result<i32, Trap> {
+            let id = read_str(&caller, "hapi_process_stdout_length", id)?;
+            Ok(value(
+                caller
+                    .data()
+                    .imports(|imports| imports.process_stdout_length(&id)),
+            ))
+        },
+    )?;
+    Ok(())
+}
+
+fn define_mem(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_mem_alloc",
+        |mut caller: Ctx, size: u32| -> Result<u32, Trap> { alloc(&mut caller, size) },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_mem_realloc",
+        |mut caller: Ctx, ptr: u32, size: u32| -> Result<u32, Trap> {
+            const CALL: &str = "hapi_mem_realloc";
+            if ptr == 0 {
+                return alloc(&mut caller, size);
+            }
+            let Some(old_size) = caller.data().allocator.size(ptr) else {
+                return Err(Trap::new(format!(
+                    "{}: {:#x} was not allocated by the host",
+                    CALL, ptr
+                )));
+            };
+
+            let new_ptr = alloc(&mut caller, size)?;
+            if new_ptr == 0 {
+                return Ok(0);
+            }
+            let data = read_bytes(&caller, CALL, ptr, old_size.min(size))?;
+            write_bytes(&mut caller, CALL, new_ptr, &data)?;
+            caller.data_mut().allocator.free(ptr);
+            Ok(new_ptr)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_mem_free",
+        |mut caller: Ctx, ptr: u32| -> Result<(), Trap> {
+            if ptr == 0 {
+                return Ok(());
+            }
+            caller.data_mut().allocator.free(ptr).ok_or_else(|| {
+                Trap::new(format!(
+                    "hapi_mem_free: {:#x} was not allocated by the host or was already freed",
+                    ptr
+                ))
+            })
+        },
+    )?;
+    Ok(())
+}
+
+fn define_display(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_display_assume_control",
+        |caller: Ctx| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.display_assume_control())
+                .code()
+        },
+    )?;
+    linker.func_wrap(MODULE, "hapi_display_override_control", |caller: Ctx| {
+        caller
+            .data()
+            .imports(|imports| imports.display_override_control());
+    })?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_display_release_control",
+        |caller: Ctx| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.display_release_control())
+                .code()
+        },
+    )?;
+    linker.func_wrap(MODULE, "hapi_display_displace_control", |caller: Ctx| {
+        caller
+            .data()
+            .imports(|imports| imports.display_displace_control());
+    })?;
+    linker.func_wrap(MODULE, "hapi_display_push_stdout", |caller: Ctx| -> i32 {
+        let state = caller.data();
+        let result = state.imports(|imports| imports.display_push_stdout());
+        state.terminal().display(&state.kernel().display.text);
+        result.code()
+    })?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_display_set_text",
+        |caller: Ctx, text: u32| -> Result<i32, Trap> {
+            let text = read_str(&caller, "hapi_display_set_text", text)?;
+            let state = caller.data();
+            let result = state.imports(|imports| imports.display_set_text(&text));
+            state.terminal().display(&state.kernel().display.text);
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_display_set_text_v2",
+        |caller: Ctx, text: u32, text_len: u32| -> Result<i32, Trap> {
+            let text = read_str_len(&caller, "hapi_display_set_text_v2", text, text_len)?;
+            let state = caller.data();
+            let result = state.imports(|imports| imports.display_set_text(&text));
+            state.terminal().display(&state.kernel().display.text);
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_display_get_key_buffer",
+        |caller: Ctx| -> i32 {
+            value(
+                caller
+                    .data()
+                    .imports(|imports| imports.display_get_key_buffer()),
+            )
+        },
+    )?;
+    linker.func_wrap(MODULE, "hapi_display_get_key_shift", |caller: Ctx| -> i32 {
+        value(
+            caller
+                .data()
+                .imports(|imports| imports.display_get_key_shift()),
+        )
+    })?;
+    linker.func_wrap(MODULE, "hapi_display_get_key_ctrl", |caller: Ctx| -> i32 {
+        value(
+            caller
+                .data()
+                .imports(|imports| imports.display_get_key_ctrl()),
+        )
+    })?;
+    linker.func_wrap(MODULE, "hapi_display_clear_key", |caller: Ctx| {
+        caller.data().imports(|imports| imports.display_clear_key());
+    })?;
+    Ok(())
+}
+
+fn define_time(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(MODULE, "hapi_time_since_unix_epoch", |caller: Ctx| -> F64 {
+        F64::from(
+            caller
+                .data()
+                .imports(|imports| imports.time_since_unix_epoch()),
+        )
+    })?;
+    linker.func_wrap(MODULE, "hapi_time_since_startup", |caller: Ctx| -> F64 {
+        F64::from(
+            caller
+                .data()
+                .imports(|imports| imports.time_since_startup()),
+        )
+    })?;
+    Ok(())
+}
+
+fn define_browser(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_browser_user_agent",
+        |mut caller: Ctx| -> Result<u32, Trap> {
+            let user_agent = caller
+                .data()
+                .imports(|imports| imports.browser_user_agent());
+            match user_agent {
+                Some(user_agent) => alloc_str(&mut caller, "hapi_browser_user_agent", &user_agent),
+                None => Ok(0),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_browser_user_agent_length",
+        |caller: Ctx| -> u32 {
+            caller
+                .data()
+                .imports(|imports| imports.browser_user_agent_length())
+        },
+    )?;
+    linker.func_wrap(MODULE, "hapi_browser_is_online", |caller: Ctx| -> u32 {
+        caller.data().imports(|imports| imports.browser_is_online()) as u32
+    })?;
+    Ok(())
+}
+
+fn network_request(
+    mut caller: Ctx,
+    call: &str,
+    url: Str,
+    method: u32,
+    headers: Str,
+    local: bool,
+) -> Result<u32, Trap> {
+    let url = url.read(&caller, call)?;
+    let headers = headers.read(&caller, call)?;
+    let id = caller.data().imports(|imports| match local {
+        false => imports.network_request(&url, method, &headers),
+        true => imports.network_request_local(&url, method, &headers),
+    });
+    match id {
+        Some(id) => alloc_str(&mut caller, call, &id),
+        None => Ok(0),
+    }
+}
+
+fn define_network(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request",
+        |caller: Ctx, url: u32, method: u32, headers: u32| {
+            let (url, headers) = (Str::Cstr(url), Str::Cstr(headers));
+            network_request(caller, "hapi_network_request", url, method, headers, false)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_v2",
+        |caller: Ctx, url: u32, url_len: u32, method: u32, headers: u32, headers_len: u32| {
+            let (url, headers) = (Str::Len(url, url_len), Str::Len(headers, headers_len));
+            network_request(
+                caller,
+                "hapi_network_request_v2",
+                url,
+                method,
+                headers,
+                false,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_local",
+        |caller: Ctx, url: u32, method: u32, headers: u32| {
+            let (url, headers) = (Str::Cstr(url), Str::Cstr(headers));
+            network_request(
+                caller,
+                "hapi_network_request_local",
+                url,
+                method,
+                headers,
+                true,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_local_v2",
+        |caller: Ctx, url: u32, url_len: u32, method: u32, headers: u32, headers_len: u32| {
+            let (url, headers) = (Str::Len(url, url_len), Str::Len(headers, headers_len));
+            network_request(
+                caller,
+                "hapi_network_request_local_v2",
+                url,
+                method,
+                headers,
+                true,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_status",
+        |caller: Ctx, id: u32| -> Result<i32, Trap> {
+            let id = read_str(&caller, "hapi_network_request_status", id)?;
+            Ok(value(
+                caller
+                    .data()
+                    .imports(|imports| imports.network_request_status(&id)),
+            ))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_data_length",
+        |caller: Ctx, id: u32| -> Result<i32, Trap> {
+            let id = read_str(&caller, "hapi_network_request_data_length", id)?;
+            Ok(value(caller.data().imports(|imports| {
+                imports.network_request_data_length(&id)
+            })))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_data",
+        |mut caller: Ctx, id: u32| -> Result<u32, Trap> {
+            const CALL: &str = "hapi_network_request_data";
+            let id = read_str(&caller, CALL, id)?;
+            let data = caller
+                .data()
+                .imports(|imports| imports.network_request_data(&id));
+            match data {
+                Some(data) => alloc_bytes(&mut caller, CALL, &data),
+                None => Ok(0),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_network_request_drop",
+        |caller: Ctx, id: u32| -> Result<(), Trap> {
+            let id = read_str(&caller, "hapi_network_request_drop", id)?;
+            caller
+                .data()
+                .imports(|imports| imports.network_request_drop(&id));
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn define_fs(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_init_ramfs",
+        |caller: Ctx, label: u32| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.fs_init_ramfs(label as u8))
+                .code()
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_create",
+        |caller: Ctx, path: u32| -> Result<i32, Trap> {
+            let path = read_str(&caller, "hapi_fs_file_create", path)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_create(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_create_v2",
+        |caller: Ctx, path: u32, path_len: u32| -> Result<i32, Trap> {
+            let path = read_str_len(&caller, "hapi_fs_file_create_v2", path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_create(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_get",
+        |mut caller: Ctx, path: u32, id_buf: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_get";
+            let path = read_str(&caller, CALL, path)?;
+            let result = caller.data().imports(|imports| imports.fs_file_get(&path));
+            id_result(&mut caller, CALL, id_buf, result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_get_v2",
+        |mut caller: Ctx, path: u32, path_len: u32, id_buf: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_get_v2";
+            let path = read_str_len(&caller, CALL, path, path_len)?;
+            let result = caller.data().imports(|imports| imports.fs_file_get(&path));
+            id_result(&mut caller, CALL, id_buf, result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_write",
+        |caller: Ctx,
+         fs_label: u32,
+         file_id: u32,
+         offset: u32,
+         size: u32,
+         buffer: u32|
+         -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_write";
+            let file_id = read_str(&caller, CALL, file_id)?;
+            let data = read_bytes(&caller, CALL, buffer, size)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_write(fs_label as u8, &file_id, offset, &data));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_read",
+        |mut caller: Ctx,
+         fs_label: u32,
+         file_id: u32,
+         offset: u32,
+         size: u32,
+         buffer: u32|
+         -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_read";
+            let file_id = read_str(&caller, CALL, file_id)?;
+            let mut data = read_bytes(&caller, CALL, buffer, size)?;
+            let result = caller.data().imports(|imports| {
+                imports.fs_file_read(fs_label as u8, &file_id, offset, &mut data)
+            });
+            if result == FsFileReadResult::Ok {
+                write_bytes(&mut caller, CALL, buffer, &data)?;
+            }
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_size",
+        |caller: Ctx, fs_label: u32, file_id: u32| -> Result<i32, Trap> {
+            let file_id = read_str(&caller, "hapi_fs_file_size", file_id)?;
+            Ok(value(caller.data().imports(|imports| {
+                imports.fs_file_size(fs_label as u8, &file_id)
+            })))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_directory_create",
+        |caller: Ctx, path: u32| -> Result<i32, Trap> {
+            let path = read_str(&caller, "hapi_fs_directory_create", path)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_directory_create(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_directory_create_v2",
+        |caller: Ctx, path: u32, path_len: u32| -> Result<i32, Trap> {
+            let path = read_str_len(&caller, "hapi_fs_directory_create_v2", path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_directory_create(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_directory_get",
+        |mut caller: Ctx, path: u32, id_buf: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_directory_get";
+            let path = read_str(&caller, CALL, path)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_directory_get(&path));
+            id_result(&mut caller, CALL, id_buf, result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_directory_get_v2",
+        |mut caller: Ctx, path: u32, path_len: u32, id_buf: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_directory_get_v2";
+            let path = read_str_len(&caller, CALL, path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_directory_get(&path));
+            id_result(&mut caller, CALL, id_buf, result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_directory_list",
+        |mut caller: Ctx,
+         fs_label: u32,
+         dir_id: u32,
+         dir_id_len: u32,
+         size: u32,
+         buffer: u32|
+         -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_directory_list";
+            let dir_id = read_str_len(&caller, CALL, dir_id, dir_id_len)?;
+            let mut data = read_bytes(&caller, CALL, buffer, size)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_directory_list(fs_label as u8, &dir_id, &mut data));
+            if matches!(result, Ok(len) if len <= size) {
+                write_bytes(&mut caller, CALL, buffer, &data)?;
+            }
+            Ok(value(result))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_remove",
+        |caller: Ctx, path: u32, path_len: u32| -> Result<i32, Trap> {
+            let path = read_str_len(&caller, "hapi_fs_file_remove", path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_remove(&path));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_directory_remove",
+        |caller: Ctx, path: u32, path_len: u32, recursive: u32| -> Result<i32, Trap> {
+            let path = read_str_len(&caller, "hapi_fs_directory_remove", path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_directory_remove(&path, recursive as u8));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_rename",
+        |caller: Ctx, from: u32, from_len: u32, to: u32, to_len: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_rename";
+            let from = read_str_len(&caller, CALL, from, from_len)?;
+            let to = read_str_len(&caller, CALL, to, to_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_rename(&from, &to));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_copy",
+        |caller: Ctx, from: u32, from_len: u32, to: u32, to_len: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_copy";
+            let from = read_str_len(&caller, CALL, from, from_len)?;
+            let to = read_str_len(&caller, CALL, to, to_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_copy(&from, &to));
+            Ok(value(result))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_metadata",
+        |mut caller: Ctx, path: u32, path_len: u32, size: u32, buffer: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_metadata";
+            let path = read_str_len(&caller, CALL, path, path_len)?;
+            let mut data = read_bytes(&caller, CALL, buffer, size)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_metadata(&path, &mut data));
+            if matches!(result, Ok(len) if len <= size) {
+                write_bytes(&mut caller, CALL, buffer, &data)?;
+            }
+            Ok(value(result))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_set_modified",
+        |caller: Ctx, path: u32, path_len: u32, modified: F64| -> Result<i32, Trap> {
+            let path = read_str_len(&caller, "hapi_fs_set_modified", path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_set_modified(&path, modified.into()));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_open",
+        |mut caller: Ctx, path: u32, path_len: u32, flags: u32, id_buf: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_open";
+            let path = read_str_len(&caller, CALL, path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_open(&path, flags));
+            id_result(&mut caller, CALL, id_buf, result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_set_len",
+        |caller: Ctx,
+         fs_label: u32,
+         file_id: u32,
+         file_id_len: u32,
+         len: u32|
+         -> Result<i32, Trap> {
+            let file_id = read_str_len(&caller, "hapi_fs_file_set_len", file_id, file_id_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_set_len(fs_label as u8, &file_id, len));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_append",
+        |caller: Ctx,
+         fs_label: u32,
+         file_id: u32,
+         file_id_len: u32,
+         size: u32,
+         buffer: u32|
+         -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_file_append";
+            let file_id = read_str_len(&caller, CALL, file_id, file_id_len)?;
+            let data = read_bytes(&caller, CALL, buffer, size)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_append(fs_label as u8, &file_id, &data));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_init_ramfs_with_capacity",
+        |caller: Ctx, label: u32, capacity: u32| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.fs_init_ramfs_with_capacity(label as u8, capacity))
+                .code()
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_unmount",
+        |caller: Ctx, label: u32| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.fs_unmount(label as u8))
+                .code()
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_mounts",
+        |mut caller: Ctx, size: u32, buffer: u32| -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_mounts";
+            let mut data = read_bytes(&caller, CALL, buffer, size)?;
+            let len = caller
+                .data()
+                .imports(|imports| imports.fs_mounts(&mut data));
+            if len <= size {
+                write_bytes(&mut caller, CALL, buffer, &data)?;
+            }
+            Ok(len as i32)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_init_persistent",
+        |caller: Ctx, label: u32| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.fs_init_persistent(label as u8))
+                .code()
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_init_overlay",
+        |caller: Ctx, label: u32, lower: u32| -> i32 {
+            caller
+                .data()
+                .imports(|imports| imports.fs_init_overlay(label as u8, lower as u8))
+                .code()
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_watch",
+        |mut caller: Ctx,
+         path: u32,
+         path_len: u32,
+         recursive: u32,
+         id_buf: u32|
+         -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_watch";
+            let path = read_str_len(&caller, CALL, path, path_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_watch(&path, recursive as u8));
+            id_result(&mut caller, CALL, id_buf, result)
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_watch_poll",
+        |mut caller: Ctx,
+         watcher_id: u32,
+         watcher_id_len: u32,
+         size: u32,
+         buffer: u32|
+         -> Result<i32, Trap> {
+            const CALL: &str = "hapi_fs_watch_poll";
+            let watcher_id = read_str_len(&caller, CALL, watcher_id, watcher_id_len)?;
+            let mut data = read_bytes(&caller, CALL, buffer, size)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_watch_poll(&watcher_id, &mut data));
+            if matches!(result, Ok(len) if len <= size) {
+                write_bytes(&mut caller, CALL, buffer, &data)?;
+            }
+            Ok(value(result))
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_watch_wait",
+        |caller: Ctx, watcher_id: u32, watcher_id_len: u32, timeout_ms: u32| -> Result<i32, Trap> {
+            let watcher_id =
+                read_str_len(&caller, "hapi_fs_watch_wait", watcher_id, watcher_id_len)?;
+            let result = hapi::sim::wait_for(
+                timeout_ms,
+                || {
+                    caller
+                        .data()
+                        .imports(|imports| imports.fs_watch_wait(&watcher_id, timeout_ms))
+                },
+                |result| *result != FsWatchWaitResult::Timeout,
+            );
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_unwatch",
+        |caller: Ctx, watcher_id: u32, watcher_id_len: u32| -> Result<i32, Trap> {
+            let watcher_id = read_str_len(&caller, "hapi_fs_unwatch", watcher_id, watcher_id_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_unwatch(&watcher_id));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_lock",
+        |caller: Ctx,
+         fs_label: u32,
+         file_id: u32,
+         file_id_len: u32,
+         exclusive: u32|
+         -> Result<i32, Trap> {
+            let file_id = read_str_len(&caller, "hapi_fs_file_lock", file_id, file_id_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_lock(fs_label as u8, &file_id, exclusive as u8));
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_lock_wait",
+        |caller: Ctx,
+         fs_label: u32,
+         file_id: u32,
+         file_id_len: u32,
+         exclusive: u32,
+         timeout_ms: u32|
+         -> Result<i32, Trap> {
+            let file_id = read_str_len(&caller, "hapi_fs_file_lock_wait", file_id, file_id_len)?;
+            let result = hapi::sim::wait_for(
+                timeout_ms,
+                || {
+                    caller.data().imports(|imports| {
+                        imports.fs_file_lock_wait(
+                            fs_label as u8,
+                            &file_id,
+                            exclusive as u8,
+                            timeout_ms,
+                        )
+                    })
+                },
+                |result| *result != FsFileLockWaitResult::Locked,
+            );
+            Ok(result.code())
+        },
+    )?;
+    linker.func_wrap(
+        MODULE,
+        "hapi_fs_file_unlock",
+        |caller: Ctx, fs_label: u32, file_id: u32, file_id_len: u32| -> Result<i32, Trap> {
+            let file_id = read_str_len(&caller, "hapi_fs_file_unlock", file_id, file_id_len)?;
+            let result = caller
+                .data()
+                .imports(|imports| imports.fs_file_unlock(fs_label as u8, &file_id));
+            Ok(result.code())
+        },
+    )?;
+    Ok(())
+}
+
+fn define_thread(linker: &mut Linker<ProcessState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(MODULE, "hapi_thread_spawn", |caller: Ctx, f_ptr: u32| {
+        let state = caller.data();
+        state.tasks.borrow_mut().push_back(Task::Thread {
+            pid: state.pid.clone(),
+            f_ptr,
+        });
+    })?;
+    Ok(())
+}
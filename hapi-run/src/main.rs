@@ -0,0 +1,160 @@
+//! Runs honeyos wasm apps locally.
+//! Provides the `hapi` import module on top of the simulated kernel from `hapi::sim`,
+//! and prints the app's stdout and display to the terminal.
+
+mod imports;
+mod memory;
+mod runner;
+mod terminal;
+
+use std::{path::Path, process::ExitCode};
+
+use hapi::{
+    abi::{FsInitPersistentResult, Imports},
+    fs::fslabel::FsLabel,
+};
+use runner::Runner;
+
+const USAGE: &str = "\
+Usage: hapi-run [OPTIONS] <APP.wasm>
+
+Options:
+  -m, --mount <LABEL>    Mount a ram file system at the label before starting. Can be repeated
+  -p, --persist <LABEL>  Mount a persistent file system at the label before starting. Can be repeated
+  -s, --storage <DIR>    The directory persistent file systems are kept in [default: .hapi-storage]
+  -w, --serve <URL=DIR>  Serve the files in the directory under the url on the simulated network. Can be repeated
+  -c, --cwd <PATH>       The working directory of the app [default: a:/]
+  -k, --keys <TEXT>      Type the text into the display's key buffer before starting
+  -h, --help             Print this message";
+
+/// The directory persistent file systems are kept in when `--storage` is not passed
+const DEFAULT_STORAGE: &str = ".hapi-storage";
+
+/// The command line options
+#[derive(Debug, Default)]
+struct Options {
+    app: String,
+    mounts: Vec<FsLabel>,
+    persistent: Vec<FsLabel>,
+    storage: Option<String>,
+    serve: Vec<(String, String)>,
+    cwd: Option<String>,
+    keys: Option<String>,
+}
+
+impl Options {
+    /// Parse the options from the command line arguments
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut app = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or(format!("{} expects a value", name));
+            match arg.as_str() {
+                "-m" | "--mount" => options.mounts.push(parse_label(&value(&arg)?)?),
+                "-p" | "--persist" => options.persistent.push(parse_label(&value(&arg)?)?),
+                "-s" | "--storage" => options.storage = Some(value(&arg)?),
+                "-w" | "--serve" => {
+                    let value = value(&arg)?;
+                    let (url, dir) = value
+                        .split_once('=')
+                        .ok_or(format!("{} expects a value like URL=DIR", arg))?;
+                    options.serve.push((url.to_owned(), dir.to_owned()));
+                }
+                "-c" | "--cwd" => options.cwd = Some(value(&arg)?),
+                "-k" | "--keys" => options.keys = Some(value(&arg)?),
+                "-h" | "--help" => return Err(USAGE.to_owned()),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ if app.is_none() => app = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+
+        options.app = app.ok_or(USAGE.to_owned())?;
+        Ok(options)
+    }
+}
+
+/// Parse a fs label option
+fn parse_label(label: &str) -> Result<FsLabel, String> {
+    label
+        .parse()
+        .map_err(|e| format!("{}", e).trim().to_owned())
+}
+
+/// Serve every file under a directory at its path under the url
+fn serve(kernel: &mut hapi::sim::Kernel, url: &str, dir: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let url = format!(
+            "{}/{}",
+            url.trim_end_matches('/'),
+            entry.file_name().to_string_lossy()
+        );
+        match entry.file_type()?.is_dir() {
+            true => serve(kernel, &url, &entry.path())?,
+            false => kernel.network.respond(url, std::fs::read(entry.path())?),
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let binary = match std::fs::read(&options.app) {
+        Ok(binary) => binary,
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", options.app, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut runner = match Runner::new() {
+        Ok(runner) => runner,
+        Err(error) => {
+            eprintln!("Failed to create the runner: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    {
+        let mut kernel = runner.kernel.borrow_mut();
+        let storage = options.storage.as_deref().unwrap_or(DEFAULT_STORAGE);
+        kernel.storage_dir = Some(storage.into());
+        for label in &options.mounts {
+            // Mounting does not depend on the calling process
+            kernel.imports("").fs_init_ramfs((*label).into());
+        }
+        for label in &options.persistent {
+            if kernel.imports("").fs_init_persistent((*label).into()) != FsInitPersistentResult::Ok
+            {
+                eprintln!(
+                    "Failed to mount a persistent file system at {} in {}",
+                    label, storage
+                );
+                return ExitCode::FAILURE;
+            }
+        }
+        for (url, dir) in &options.serve {
+            if let Err(error) = serve(&mut kernel, url, Path::new(dir)) {
+                eprintln!("Failed to serve {}: {}", dir, error);
+                return ExitCode::FAILURE;
+            }
+        }
+        if let Some(keys) = &options.keys {
+            kernel.display.type_str(keys);
+        }
+    }
+
+    match runner.run(binary, options.cwd.as_deref()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use wasmi::{
+    core::{Pages, Trap},
+    AsContext, AsContextMut, Caller, Memory,
+};
+
+use crate::runner::ProcessState;
+
+/// The size of a wasm page
+const PAGE_SIZE: u32 = 65536;
+/// The alignment of blocks allocated by the host
+const ALIGN: u32 = 8;
+
+/// Get the linear memory of the process
+pub fn memory(caller: &Caller<'_, ProcessState>) -> Result<Memory, Trap> {
+    caller
+        .data()
+        .memory
+        .ok_or_else(|| Trap::new("the module does not export it's memory"))
+}
+
+/// Check that a range lies within the linear memory
+fn range(data: &[u8], call: &str, ptr: u32, len: usize) -> Result<std::ops::Range<usize>, Trap> {
+    let start = ptr as usize;
+    match start.checked_add(len) {
+        Some(end) if ptr != 0 && end <= data.len() => Ok(start..end),
+        _ => Err(Trap::new(format!(
+            "{}: {} bytes at {:#x} are out of bounds",
+            call, len, ptr
+        ))),
+    }
+}
+
+/// Read a null-terminated utf-8 string
+pub fn read_str(caller: &Caller<'_, ProcessState>, call: &str, ptr: u32) -> Result<String, Trap> {
+    let data = memory(caller)?.data(caller.as_context());
+    range(data, call, ptr, 1)?;
+
+    let bytes = &data[ptr as usize..];
+    let Some(len) = bytes.iter().position(|b| *b == 0) else {
+        return Err(Trap::new(format!(
+            "{}: string at {:#x} is not null-terminated",
+            call, ptr
+        )));
+    };
+    String::from_utf8(bytes[..len].to_vec())
+        .map_err(|_| Trap::new(format!("{}: string at {:#x} is not utf-8", call, ptr)))
+}
+
+/// Read a utf-8 string from a pointer and a length
+pub fn read_str_len(
+    caller: &Caller<'_, ProcessState>,
+    call: &str,
+    ptr: u32,
+    len: u32,
+) -> Result<String, Trap> {
+    String::from_utf8(read_bytes(caller, call, ptr, len)?)
+        .map_err(|_| Trap::new(format!("{}: string at {:#x} is not utf-8", call, ptr)))
+}
+
+/// Read a range of bytes
+pub fn read_bytes(
+    caller: &Caller<'_, ProcessState>,
+    call: &str,
+    ptr: u32,
+    len: u32,
+) -> Result<Vec<u8>, Trap> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let data = memory(caller)?.data(caller.as_context());
+    Ok(data[range(data, call, ptr, len as usize)?].to_vec())
+}
+
+/// Write bytes to the memory
+pub fn write_bytes(
+    caller: &mut Caller<'_, ProcessState>,
+    call: &str,
+    ptr: u32,
+    bytes: &[u8],
+) -> Result<(), Trap> {
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    let data = memory(caller)?.data_mut(caller.as_context_mut());
+    let range = range(data, call, ptr, bytes.len())?;
+    data[range].copy_from_slice(bytes);
+    Ok(())
+}
+
+/// Write a string and it's null terminator to the memory
+pub fn write_str(
+    caller: &mut Caller<'_, ProcessState>,
+    call: &str,
+    ptr: u32,
+    string: &str,
+) -> Result<(), Trap> {
+    let mut bytes = string.as_bytes().to_vec();
+    bytes.push(0);
+    write_bytes(caller, call, ptr, &bytes)
+}
+
+/// Write an id to a buffer, which the abi requires to be 37 bytes
+pub fn write_id(
+    caller: &mut Caller<'_, ProcessState>,
+    call: &str,
+    ptr: u32,
+    id: &str,
+) -> Result<(), Trap> {
+    if id.len() != 36 {
+        return Err(Trap::new(format!(
+            "{}: the kernel produced an id of {} bytes",
+            call,
+            id.len()
+        )));
+    }
+    write_str(caller, call, ptr, id)
+}
+
+/// Allocate a block in the memory and copy the bytes into it.
+/// Returns `NULL` if the memory could not grow.
+pub fn alloc_bytes(
+    caller: &mut Caller<'_, ProcessState>,
+    call: &str,
+    bytes: &[u8],
+) -> Result<u32, Trap> {
+    let ptr = alloc(caller, bytes.len() as u32)?;
+    if ptr != 0 {
+        write_bytes(caller, call, ptr, bytes)?;
+    }
+    Ok(ptr)
+}
+
+/// Allocate a null-terminated string in the memory
+pub fn alloc_str(
+    caller: &mut Caller<'_, ProcessState>,
+    call: &str,
+    string: &str,
+) -> Result<u32, Trap> {
+    let mut bytes = string.as_bytes().to_vec();
+    bytes.push(0);
+    alloc_bytes(caller, call, &bytes)
+}
+
+/// Allocate a block in the memory.
+/// Returns `NULL` if the memory could not grow.
+pub fn alloc(caller: &mut Caller<'_, ProcessState>, size: u32) -> Result<u32, Trap> {
+    if let Some(ptr) = caller.data_mut().allocator.alloc(size) {
+        return Ok(ptr);
+    }
+
+    // Grow the memory by enough pages to hold the block.
+    // The allocator of the guest never reuses pages it did not grow itself.
+    let memory = memory(caller)?;
+    let pages = size.max(1).div_ceil(PAGE_SIZE);
+    let Some(additional) = Pages::new(pages) else {
+        return Ok(0);
+    };
+    let Ok(old_pages) = memory.grow(caller.as_context_mut(), additional) else {
+        return Ok(0);
+    };
+    let base = u32::from(old_pages) * PAGE_SIZE;
+
+    let allocator = &mut caller.data_mut().allocator;
+    allocator.add_region(base, pages * PAGE_SIZE);
+    Ok(allocator.alloc(size).unwrap_or(0))
+}
+
+/// Allocates blocks in pages of the linear memory owned by the host.
+/// Keeps track of every live block, so invalid frees and leaks can be reported.
+#[derive(Debug, Default)]
+pub struct GuestAllocator {
+    live: BTreeMap<u32, u32>,
+    free: Vec<(u32, u32)>,
+}
+
+impl GuestAllocator {
+    /// Add a region of memory to allocate blocks in
+    pub fn add_region(&mut self, base: u32, len: u32) {
+        self.free.push((base, len));
+    }
+
+    /// Allocate a block in one of the regions
+    pub fn alloc(&mut self, size: u32) -> Option<u32> {
+        let size = size.max(1).div_ceil(ALIGN) * ALIGN;
+        let index = self.free.iter().position(|(_, len)| *len >= size)?;
+        let (ptr, len) = self.free[index];
+        if len == size {
+            self.free.remove(index);
+        } else {
+            self.free[index] = (ptr + size, len - size);
+        }
+        self.live.insert(ptr, size);
+        Some(ptr)
+    }
+
+    /// Free a block. Returns None if the block was not allocated by the host.
+    pub fn free(&mut self, ptr: u32) -> Option<()> {
+        let size = self.live.remove(&ptr)?;
+        self.free.push((ptr, size));
+        Some(())
+    }
+
+    /// Get the size of a live block
+    pub fn size(&self, ptr: u32) -> Option<u32> {
+        self.live.get(&ptr).copied()
+    }
+
+    /// The amount of live blocks and the bytes they hold
+    pub fn live(&self) -> (usize, u32) {
+        (self.live.len(), self.live.values().sum())
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    cell::{RefCell, RefMut},
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
+
+use hapi::sim::{kernel::DEFAULT_CWD, Kernel, SimImports};
+use wasmi::{Engine, Extern, Instance, Linker, Memory, Module, Store};
+
+use crate::{memory::GuestAllocator, terminal::Terminal};
+
+/// Work waiting to be run
+#[derive(Debug)]
+pub enum Task {
+    /// Instantiate a process and call it's `_start`
+    Start { pid: String, binary: Vec<u8> },
+    /// Call `_thread_entrypoint` of a process
+    Thread { pid: String, f_ptr: u32 },
+}
+
+/// The state of a process, owned by it's store
+pub struct ProcessState {
+    pub pid: String,
+    pub root: bool,
+    pub memory: Option<Memory>,
+    pub allocator: GuestAllocator,
+    pub kernel: Rc<RefCell<Kernel>>,
+    pub tasks: Rc<RefCell<VecDeque<Task>>>,
+    pub terminal: Rc<RefCell<Terminal>>,
+}
+
+impl ProcessState {
+    /// Borrow the kernel
+    pub fn kernel(&self) -> RefMut<'_, Kernel> {
+        self.kernel.borrow_mut()
+    }
+
+    /// Run a closure with the imports of the process
+    pub fn imports<R>(&self, f: impl FnOnce(&mut SimImports) -> R) -> R {
+        f(&mut self.kernel().imports(&self.pid))
+    }
+
+    /// Borrow the terminal
+    pub fn terminal(&self) -> RefMut<'_, Terminal> {
+        self.terminal.borrow_mut()
+    }
+}
+
+/// Runs processes and their threads one task at a time.
+/// Threads do not run in parallel, they run after the task that spawned them returns.
+pub struct Runner {
+    engine: Engine,
+    linker: Linker<ProcessState>,
+    processes: HashMap<String, (Store<ProcessState>, Instance)>,
+    pub kernel: Rc<RefCell<Kernel>>,
+    tasks: Rc<RefCell<VecDeque<Task>>>,
+    terminal: Rc<RefCell<Terminal>>,
+}
+
+impl Runner {
+    /// Create a runner with a fresh kernel
+    pub fn new() -> Result<Self, wasmi::Error> {
+        let engine = Engine::default();
+        let mut linker = Linker::new(&engine);
+        crate::imports::define(&mut linker)?;
+
+        Ok(Self {
+            engine,
+            linker,
+            processes: HashMap::new(),
+            kernel: Rc::new(RefCell::new(Kernel::new())),
+            tasks: Rc::new(RefCell::new(VecDeque::new())),
+            terminal: Rc::new(RefCell::new(Terminal::default())),
+        })
+    }
+
+    /// Run a binary as the root process until it and all of it's threads and subprocesses finish.
+    /// Returns an error if the root process trapped.
+    pub fn run(&mut self, binary: Vec<u8>, cwd: Option<&str>) -> Result<(), wasmi::Error> {
+        let cwd = cwd.unwrap_or(DEFAULT_CWD);
+        let root = self.kernel.borrow_mut().spawn_process(cwd, binary.clone());
+        self.tasks.borrow_mut().push_back(Task::Start {
+            pid: root.clone(),
+            binary,
+        });
+
+        let mut result = Ok(());
+        loop {
+            let Some(task) = self.tasks.borrow_mut().pop_front() else {
+                break;
+            };
+            let pid = match &task {
+                Task::Start { pid, .. } | Task::Thread { pid, .. } => pid.clone(),
+            };
+
+            if let Err(error) = self.run_task(task, pid == root) {
+                eprintln!("\x1b[91mprocess {} trapped: {}\x1b[0m", pid, error);
+                self.kernel.borrow_mut().kill(&pid);
+                self.tasks.borrow_mut().retain(|task| {
+                    !matches!(task, Task::Thread { pid: thread_pid, .. } if *thread_pid == pid)
+                });
+                if pid == root {
+                    result = Err(error);
+                }
+            }
+
+            if !self.has_threads(&pid) {
+                self.kernel.borrow_mut().kill(&pid);
+            }
+        }
+
+        for (label, fs) in &mut self.kernel.borrow_mut().filesystems {
+            if let Err(error) = fs.save() {
+                eprintln!("\x1b[91mfailed to save {}: {}\x1b[0m", label, error);
+            }
+        }
+        self.report_leaks();
+        result
+    }
+
+    fn run_task(&mut self, task: Task, root: bool) -> Result<(), wasmi::Error> {
+        match task {
+            Task::Start { pid, binary } => {
+                let module = Module::new(&self.engine, &binary[..])?;
+                let state = ProcessState {
+                    pid: pid.clone(),
+                    root,
+                    memory: None,
+                    allocator: GuestAllocator::default(),
+                    kernel: self.kernel.clone(),
+                    tasks: self.tasks.clone(),
+                    terminal: self.terminal.clone(),
+                };
+                let mut store = Store::new(&self.engine, state);
+                let instance = self
+                    .linker
+                    .instantiate(&mut store, &module)?
+                    .start(&mut store)?;
+                store.data_mut().memory = instance
+                    .get_export(&store, "memory")
+                    .and_then(Extern::into_memory);
+
+                let start = instance.get_typed_func::<(), ()>(&store, "_start")?;
+                let result = start.call(&mut store, ());
+                self.processes.insert(pid, (store, instance));
+                result?;
+            }
+            Task::Thread { pid, f_ptr } => {
+                let Some((store, instance)) = self.processes.get_mut(&pid) else {
+                    return Ok(());
+                };
+                let entrypoint =
+                    instance.get_typed_func::<u32, ()>(&*store, "_thread_entrypoint")?;
+                entrypoint.call(store, f_ptr)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn has_threads(&self, pid: &str) -> bool {
+        self.tasks
+            .borrow()
+            .iter()
+            .any(|task| matches!(task, Task::Thread { pid: thread_pid, .. } if thread_pid == pid))
+    }
+
+    /// Report the blocks allocated by the host that were never freed with `hapi_mem_free`
+    fn report_leaks(&self) {
+        for (pid, (store, _)) in &self.processes {
+            let (blocks, bytes) = store.data().allocator.live();
+            if blocks > 0 {
+                eprintln!(
+                    "\x1b[93mprocess {} leaked {} bytes in {} blocks allocated by the host\x1b[0m",
+                    pid, bytes, blocks
+                );
+            }
+        }
+    }
+}
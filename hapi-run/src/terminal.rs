@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use hapi::sim::js::LogLevel;
+
+/// Renders the output of the root process and the display to the terminal
+#[derive(Debug, Default)]
+pub struct Terminal {
+    display: String,
+}
+
+impl Terminal {
+    /// Print stdout
+    pub fn write(&mut self, string: &str) {
+        print!("{}", string);
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Clear the screen
+    pub fn clear(&mut self) {
+        print!("\x1b[2J\x1b[H");
+    }
+
+    /// Clear N lines, the current line included
+    pub fn clear_lines(&mut self, num: u32) {
+        for _ in 0..num {
+            print!("\r\x1b[2K\x1b[1A");
+        }
+        print!("\r\x1b[2K");
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Print the display's text-mode buffer if it changed
+    pub fn display(&mut self, text: &str) {
+        if self.display == text {
+            return;
+        }
+        text.clone_into(&mut self.display);
+        println!("\x1b[2m--- display ---\x1b[0m");
+        println!("{}", text);
+        println!("\x1b[2m---------------\x1b[0m");
+    }
+
+    /// Print a message logged to the js console
+    pub fn log(&mut self, level: LogLevel, message: &str) {
+        match level {
+            LogLevel::Info => eprintln!("[console] {}", message),
+            LogLevel::Warn => eprintln!("\x1b[93m[console] {}\x1b[0m", message),
+            LogLevel::Error => eprintln!("\x1b[91m[console] {}\x1b[0m", message),
+        }
+    }
+}
@@ -0,0 +1,2334 @@
+//! Typed codes of the `hapi` imports, and the trait a host implements to serve them.
+//! Generated from `abi/hapi.json` by `cargo run -p hapi-abi`, do not edit by hand.
+
+use crate::prelude::*;
+
+/// The version of the abi the bindings were generated from
+pub const ABI_VERSION: u32 = 13;
+
+/// A group of imports a host may or may not support, reported by `hapi_abi_capabilities`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Logging to and evaluating js in the browser console
+    Console,
+    /// Writing to the process's stdout
+    Stdout,
+    /// The working directory, subprocesses and their stdout
+    Process,
+    /// Taking control of the display and reading keys
+    Display,
+    /// The clocks
+    Time,
+    /// The user agent and online state of the browser
+    Browser,
+    /// Network requests
+    Network,
+    /// Network requests to the local server
+    NetworkLocal,
+    /// Mounting ram file systems
+    Ramfs,
+    /// Files and directories
+    Fs,
+    /// Spawning threads
+    Threads,
+    /// Mounting file systems that survive restarts
+    Persistent,
+    /// Mounting writable overlays of file systems
+    Overlay,
+    /// Watching files and directories for changes
+    Watch,
+    /// Advisory locks on files
+    Locks,
+}
+
+impl Capability {
+    /// Every capability
+    pub const ALL: [Capability; 15] = [
+        Self::Console,
+        Self::Stdout,
+        Self::Process,
+        Self::Display,
+        Self::Time,
+        Self::Browser,
+        Self::Network,
+        Self::NetworkLocal,
+        Self::Ramfs,
+        Self::Fs,
+        Self::Threads,
+        Self::Persistent,
+        Self::Overlay,
+        Self::Watch,
+        Self::Locks,
+    ];
+
+    /// The bit of the capability in `hapi_abi_capabilities`
+    pub fn bit(self) -> u32 {
+        match self {
+            Self::Console => 1 << 0,
+            Self::Stdout => 1 << 1,
+            Self::Process => 1 << 2,
+            Self::Display => 1 << 3,
+            Self::Time => 1 << 4,
+            Self::Browser => 1 << 5,
+            Self::Network => 1 << 6,
+            Self::NetworkLocal => 1 << 7,
+            Self::Ramfs => 1 << 8,
+            Self::Fs => 1 << 9,
+            Self::Threads => 1 << 10,
+            Self::Persistent => 1 << 11,
+            Self::Overlay => 1 << 12,
+            Self::Watch => 1 << 14,
+            Self::Locks => 1 << 15,
+        }
+    }
+
+    /// The imports that belong to the capability
+    pub fn calls(self) -> &'static [&'static str] {
+        match self {
+            Self::Console => &[
+                "hapi_js_console_log_info",
+                "hapi_js_console_log_info_v2",
+                "hapi_js_console_log_warn",
+                "hapi_js_console_log_warn_v2",
+                "hapi_js_console_log_error",
+                "hapi_js_console_log_error_v2",
+                "hapi_js_console_eval",
+                "hapi_js_console_eval_v2",
+            ],
+            Self::Stdout => &[
+                "hapi_stdout_clear",
+                "hapi_stdout_clear_line",
+                "hapi_stdout_clear_lines",
+                "hapi_stdout_write",
+                "hapi_stdout_write_v2",
+            ],
+            Self::Process => &[
+                "hapi_process_get_pid",
+                "hapi_process_get_cwd",
+                "hapi_process_get_cwd_length",
+                "hapi_process_set_cwd",
+                "hapi_process_set_cwd_v2",
+                "hapi_process_spawn_subprocess",
+                "hapi_process_spawn_subprocess_v2",
+                "hapi_process_alive",
+                "hapi_process_stdout",
+                "hapi_process_stdout_length",
+            ],
+            Self::Display => &[
+                "hapi_display_assume_control",
+                "hapi_display_override_control",
+                "hapi_display_release_control",
+                "hapi_display_displace_control",
+                "hapi_display_push_stdout",
+                "hapi_display_set_text",
+                "hapi_display_set_text_v2",
+                "hapi_display_get_key_buffer",
+                "hapi_display_get_key_shift",
+                "hapi_display_get_key_ctrl",
+                "hapi_display_clear_key",
+            ],
+            Self::Time => &["hapi_time_since_unix_epoch", "hapi_time_since_startup"],
+            Self::Browser => &[
+                "hapi_browser_user_agent",
+                "hapi_browser_user_agent_length",
+                "hapi_browser_is_online",
+            ],
+            Self::Network => &[
+                "hapi_network_request",
+                "hapi_network_request_v2",
+                "hapi_network_request_status",
+                "hapi_network_request_data_length",
+                "hapi_network_request_data",
+                "hapi_network_request_drop",
+            ],
+            Self::NetworkLocal => &[
+                "hapi_network_request_local",
+                "hapi_network_request_local_v2",
+            ],
+            Self::Ramfs => &["hapi_fs_init_ramfs", "hapi_fs_init_ramfs_with_capacity"],
+            Self::Fs => &[
+                "hapi_fs_file_create",
+                "hapi_fs_file_create_v2",
+                "hapi_fs_file_get",
+                "hapi_fs_file_get_v2",
+                "hapi_fs_file_write",
+                "hapi_fs_file_read",
+                "hapi_fs_file_size",
+                "hapi_fs_directory_create",
+                "hapi_fs_directory_create_v2",
+                "hapi_fs_directory_get",
+                "hapi_fs_directory_get_v2",
+                "hapi_fs_directory_list",
+                "hapi_fs_file_remove",
+                "hapi_fs_directory_remove",
+                "hapi_fs_rename",
+                "hapi_fs_file_copy",
+                "hapi_fs_metadata",
+                "hapi_fs_set_modified",
+                "hapi_fs_file_open",
+                "hapi_fs_file_set_len",
+                "hapi_fs_file_append",
+                "hapi_fs_unmount",
+                "hapi_fs_mounts",
+            ],
+            Self::Threads => &["hapi_thread_spawn"],
+            Self::Persistent => &["hapi_fs_init_persistent"],
+            Self::Overlay => &["hapi_fs_init_overlay"],
+            Self::Watch => &[
+                "hapi_fs_watch",
+                "hapi_fs_watch_poll",
+                "hapi_fs_watch_wait",
+                "hapi_fs_unwatch",
+            ],
+            Self::Locks => &[
+                "hapi_fs_file_lock",
+                "hapi_fs_file_lock_wait",
+                "hapi_fs_file_unlock",
+            ],
+        }
+    }
+}
+
+/// A code returned by a `hapi_*` import
+pub trait AbiCode: Copy {
+    /// The raw value of the code
+    fn code(self) -> i32;
+
+    /// Get the code of a raw value
+    fn from_code(code: i32) -> Self;
+}
+
+/// The codes of `hapi_process_set_cwd`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSetCwdResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the path is invalid
+    InvalidPath,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for ProcessSetCwdResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::InvalidPath => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::InvalidPath,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for ProcessSetCwdResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<ProcessSetCwdResult> for i32 {
+    fn from(code: ProcessSetCwdResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_process_spawn_subprocess_v2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSpawnSubprocessV2Error {
+    /// `-1` On failure
+    Failed,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for ProcessSpawnSubprocessV2Error {
+    fn code(self) -> i32 {
+        match self {
+            Self::Failed => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::Failed,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for ProcessSpawnSubprocessV2Error {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<ProcessSpawnSubprocessV2Error> for i32 {
+    fn from(code: ProcessSpawnSubprocessV2Error) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_process_stdout_length`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStdoutLengthError {
+    /// `-1` If the process does not exist or the id cannot be read from memory
+    NoSuchProcess,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for ProcessStdoutLengthError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NoSuchProcess => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NoSuchProcess,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for ProcessStdoutLengthError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<ProcessStdoutLengthError> for i32 {
+    fn from(code: ProcessStdoutLengthError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_assume_control`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayAssumeControlResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the display is occupied
+    Occupied,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplayAssumeControlResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::Occupied => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::Occupied,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplayAssumeControlResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplayAssumeControlResult> for i32 {
+    fn from(code: DisplayAssumeControlResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_release_control`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayReleaseControlResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the process doesn't have control over the display
+    NotInControl,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplayReleaseControlResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotInControl => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotInControl,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplayReleaseControlResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplayReleaseControlResult> for i32 {
+    fn from(code: DisplayReleaseControlResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_push_stdout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayPushStdoutResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the process doesn't have control over the display
+    NotInControl,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplayPushStdoutResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotInControl => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotInControl,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplayPushStdoutResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplayPushStdoutResult> for i32 {
+    fn from(code: DisplayPushStdoutResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_set_text`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplaySetTextResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the process doesn't have control over the display
+    NotInControl,
+    /// `-2` If the string is invalid
+    InvalidString,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplaySetTextResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotInControl => -1,
+            Self::InvalidString => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotInControl,
+            -2 => Self::InvalidString,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplaySetTextResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplaySetTextResult> for i32 {
+    fn from(code: DisplaySetTextResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_get_key_buffer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayGetKeyBufferError {
+    /// `-1` If the process doesn't have control over the display
+    NotInControl,
+    /// `-2` If the key buffer is empty
+    Empty,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplayGetKeyBufferError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotInControl => -1,
+            Self::Empty => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotInControl,
+            -2 => Self::Empty,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplayGetKeyBufferError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplayGetKeyBufferError> for i32 {
+    fn from(code: DisplayGetKeyBufferError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_get_key_shift`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayGetKeyShiftError {
+    /// `-1` If the process doesn't have control over the display
+    NotInControl,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplayGetKeyShiftError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotInControl => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotInControl,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplayGetKeyShiftError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplayGetKeyShiftError> for i32 {
+    fn from(code: DisplayGetKeyShiftError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_display_get_key_ctrl`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayGetKeyCtrlError {
+    /// `-1` If the process doesn't have control over the display
+    NotInControl,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for DisplayGetKeyCtrlError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotInControl => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotInControl,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for DisplayGetKeyCtrlError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<DisplayGetKeyCtrlError> for i32 {
+    fn from(code: DisplayGetKeyCtrlError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_network_request_status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkRequestStatusError {
+    /// `-1` If the request does not exist
+    NoSuchRequest,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for NetworkRequestStatusError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NoSuchRequest => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NoSuchRequest,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for NetworkRequestStatusError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<NetworkRequestStatusError> for i32 {
+    fn from(code: NetworkRequestStatusError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_network_request_data_length`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkRequestDataLengthError {
+    /// `-1` If the request does not exist
+    NoSuchRequest,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for NetworkRequestDataLengthError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NoSuchRequest => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NoSuchRequest,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for NetworkRequestDataLengthError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<NetworkRequestDataLengthError> for i32 {
+    fn from(code: NetworkRequestDataLengthError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_init_ramfs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsInitRamfsResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the label char is invalid
+    InvalidLabel,
+    /// `-2` If the label is already occupied
+    LabelOccupied,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsInitRamfsResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::InvalidLabel => -1,
+            Self::LabelOccupied => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::InvalidLabel,
+            -2 => Self::LabelOccupied,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsInitRamfsResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsInitRamfsResult> for i32 {
+    fn from(code: FsInitRamfsResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_create`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileCreateResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the directory doesn't exist
+    NoSuchDirectory,
+    /// `-2` If a file with the name already exists
+    Exists,
+    /// `-3` If the path string is invalid
+    InvalidPath,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileCreateResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchDirectory => -1,
+            Self::Exists => -2,
+            Self::InvalidPath => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchDirectory,
+            -2 => Self::Exists,
+            -3 => Self::InvalidPath,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileCreateResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileCreateResult> for i32 {
+    fn from(code: FsFileCreateResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_get`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileGetError {
+    /// `-1` If the file does not exist or if the path is incorrect
+    NotFound,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileGetError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotFound => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotFound,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileGetError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileGetError> for i32 {
+    fn from(code: FsFileGetError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_write`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileWriteResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the file label does not correspond to an active fs
+    NoSuchFs,
+    /// `-3` If there is not enough space
+    NotEnoughSpace,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileWriteResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::NotEnoughSpace => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            -3 => Self::NotEnoughSpace,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileWriteResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileWriteResult> for i32 {
+    fn from(code: FsFileWriteResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_read`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileReadResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the file label does not correspond to an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileReadResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileReadResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileReadResult> for i32 {
+    fn from(code: FsFileReadResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_size`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileSizeError {
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileSizeError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileSizeError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileSizeError> for i32 {
+    fn from(code: FsFileSizeError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_directory_create`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsDirectoryCreateResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the parent directory doesn't exist
+    NoSuchDirectory,
+    /// `-2` If a directory with the name already exists
+    Exists,
+    /// `-3` If the path string is invalid
+    InvalidPath,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsDirectoryCreateResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchDirectory => -1,
+            Self::Exists => -2,
+            Self::InvalidPath => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchDirectory,
+            -2 => Self::Exists,
+            -3 => Self::InvalidPath,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsDirectoryCreateResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsDirectoryCreateResult> for i32 {
+    fn from(code: FsDirectoryCreateResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_directory_get`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsDirectoryGetError {
+    /// `-1` If the directory does not exist or if the path is incorrect
+    NotFound,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsDirectoryGetError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotFound => -1,
+            Self::NoSuchFs => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotFound,
+            -2 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsDirectoryGetError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsDirectoryGetError> for i32 {
+    fn from(code: FsDirectoryGetError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_directory_list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsDirectoryListError {
+    /// `-1` If the directory does not exist
+    NoSuchDirectory,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsDirectoryListError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NoSuchDirectory => -1,
+            Self::NoSuchFs => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NoSuchDirectory,
+            -2 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsDirectoryListError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsDirectoryListError> for i32 {
+    fn from(code: FsDirectoryListError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_remove`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileRemoveResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If there is no file at the path
+    NotFound,
+    /// `-2` If the path string is invalid
+    InvalidPath,
+    /// `-3` If the path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileRemoveResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotFound => -1,
+            Self::InvalidPath => -2,
+            Self::NoSuchFs => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotFound,
+            -2 => Self::InvalidPath,
+            -3 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileRemoveResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileRemoveResult> for i32 {
+    fn from(code: FsFileRemoveResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_directory_remove`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsDirectoryRemoveResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If there is no directory at the path
+    NotFound,
+    /// `-2` If the directory is not empty and `recursive` is not `1`
+    NotEmpty,
+    /// `-3` If the path string is invalid or is the root of a fs
+    InvalidPath,
+    /// `-4` If the path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsDirectoryRemoveResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotFound => -1,
+            Self::NotEmpty => -2,
+            Self::InvalidPath => -3,
+            Self::NoSuchFs => -4,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotFound,
+            -2 => Self::NotEmpty,
+            -3 => Self::InvalidPath,
+            -4 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsDirectoryRemoveResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsDirectoryRemoveResult> for i32 {
+    fn from(code: FsDirectoryRemoveResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_rename`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsRenameResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If there is nothing at the old path
+    NotFound,
+    /// `-2` If the parent directory of the new path doesn't exist
+    NoSuchDirectory,
+    /// `-3` If a directory exists at the new path, or a file exists when moving a directory
+    Exists,
+    /// `-4` If the paths are on different file systems
+    CrossDevice,
+    /// `-5` If a path string is invalid, is the root of a fs, or a directory would be moved into itself
+    InvalidPath,
+    /// `-6` If a path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsRenameResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotFound => -1,
+            Self::NoSuchDirectory => -2,
+            Self::Exists => -3,
+            Self::CrossDevice => -4,
+            Self::InvalidPath => -5,
+            Self::NoSuchFs => -6,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotFound,
+            -2 => Self::NoSuchDirectory,
+            -3 => Self::Exists,
+            -4 => Self::CrossDevice,
+            -5 => Self::InvalidPath,
+            -6 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsRenameResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsRenameResult> for i32 {
+    fn from(code: FsRenameResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_copy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileCopyError {
+    /// `-1` If there is no file at the old path
+    NotFound,
+    /// `-2` If the parent directory of the new path doesn't exist
+    NoSuchDirectory,
+    /// `-3` If a directory exists at the new path
+    Exists,
+    /// `-4` If a path string is invalid
+    InvalidPath,
+    /// `-5` If a path is not on an active fs
+    NoSuchFs,
+    /// `-6` If there is not enough space on the fs of the new path
+    NotEnoughSpace,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileCopyError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotFound => -1,
+            Self::NoSuchDirectory => -2,
+            Self::Exists => -3,
+            Self::InvalidPath => -4,
+            Self::NoSuchFs => -5,
+            Self::NotEnoughSpace => -6,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotFound,
+            -2 => Self::NoSuchDirectory,
+            -3 => Self::Exists,
+            -4 => Self::InvalidPath,
+            -5 => Self::NoSuchFs,
+            -6 => Self::NotEnoughSpace,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileCopyError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileCopyError> for i32 {
+    fn from(code: FsFileCopyError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_metadata`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsMetadataError {
+    /// `-1` If there is nothing at the path
+    NotFound,
+    /// `-2` If the path string is invalid
+    InvalidPath,
+    /// `-3` If the path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsMetadataError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotFound => -1,
+            Self::InvalidPath => -2,
+            Self::NoSuchFs => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotFound,
+            -2 => Self::InvalidPath,
+            -3 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsMetadataError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsMetadataError> for i32 {
+    fn from(code: FsMetadataError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_set_modified`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsSetModifiedResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If there is nothing at the path
+    NotFound,
+    /// `-2` If the path string is invalid
+    InvalidPath,
+    /// `-3` If the path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsSetModifiedResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NotFound => -1,
+            Self::InvalidPath => -2,
+            Self::NoSuchFs => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NotFound,
+            -2 => Self::InvalidPath,
+            -3 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsSetModifiedResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsSetModifiedResult> for i32 {
+    fn from(code: FsSetModifiedResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_open`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileOpenError {
+    /// `-1` If the file does not exist and is not created
+    NotFound,
+    /// `-2` If the file exists and `2` is set, or a directory exists at the path
+    Exists,
+    /// `-3` If the parent directory doesn't exist
+    NoSuchDirectory,
+    /// `-4` If the path string is invalid
+    InvalidPath,
+    /// `-5` If the path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileOpenError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotFound => -1,
+            Self::Exists => -2,
+            Self::NoSuchDirectory => -3,
+            Self::InvalidPath => -4,
+            Self::NoSuchFs => -5,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotFound,
+            -2 => Self::Exists,
+            -3 => Self::NoSuchDirectory,
+            -4 => Self::InvalidPath,
+            -5 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileOpenError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileOpenError> for i32 {
+    fn from(code: FsFileOpenError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_set_len`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileSetLenResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// `-3` If there is not enough space to extend the file
+    NotEnoughSpace,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileSetLenResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::NotEnoughSpace => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            -3 => Self::NotEnoughSpace,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileSetLenResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileSetLenResult> for i32 {
+    fn from(code: FsFileSetLenResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_append`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileAppendResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// `-3` If there is not enough space
+    NotEnoughSpace,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileAppendResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::NotEnoughSpace => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            -3 => Self::NotEnoughSpace,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileAppendResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileAppendResult> for i32 {
+    fn from(code: FsFileAppendResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_init_ramfs_with_capacity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsInitRamfsWithCapacityResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the label char is invalid
+    InvalidLabel,
+    /// `-2` If the label is already occupied
+    LabelOccupied,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsInitRamfsWithCapacityResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::InvalidLabel => -1,
+            Self::LabelOccupied => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::InvalidLabel,
+            -2 => Self::LabelOccupied,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsInitRamfsWithCapacityResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsInitRamfsWithCapacityResult> for i32 {
+    fn from(code: FsInitRamfsWithCapacityResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_unmount`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsUnmountResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If no filesystem is mounted at the label
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsUnmountResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFs => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsUnmountResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsUnmountResult> for i32 {
+    fn from(code: FsUnmountResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_init_persistent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsInitPersistentResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the label char is invalid
+    InvalidLabel,
+    /// `-2` If the label is already occupied
+    LabelOccupied,
+    /// `-3` If the host has no storage for persistent filesystems
+    Unavailable,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsInitPersistentResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::InvalidLabel => -1,
+            Self::LabelOccupied => -2,
+            Self::Unavailable => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::InvalidLabel,
+            -2 => Self::LabelOccupied,
+            -3 => Self::Unavailable,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsInitPersistentResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsInitPersistentResult> for i32 {
+    fn from(code: FsInitPersistentResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_init_overlay`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsInitOverlayResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If either label char is invalid
+    InvalidLabel,
+    /// `-2` If the label is already occupied
+    LabelOccupied,
+    /// `-3` If there is no filesystem mounted at the lower label
+    NoSuchFs,
+    /// `-4` If the lower filesystem is itself an overlay
+    InvalidLower,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsInitOverlayResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::InvalidLabel => -1,
+            Self::LabelOccupied => -2,
+            Self::NoSuchFs => -3,
+            Self::InvalidLower => -4,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::InvalidLabel,
+            -2 => Self::LabelOccupied,
+            -3 => Self::NoSuchFs,
+            -4 => Self::InvalidLower,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsInitOverlayResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsInitOverlayResult> for i32 {
+    fn from(code: FsInitOverlayResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_watch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsWatchError {
+    /// `-1` If nothing exists at the path
+    NotFound,
+    /// `-2` If the path string is invalid
+    InvalidPath,
+    /// `-3` If the path is not on an active fs
+    NoSuchFs,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsWatchError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NotFound => -1,
+            Self::InvalidPath => -2,
+            Self::NoSuchFs => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NotFound,
+            -2 => Self::InvalidPath,
+            -3 => Self::NoSuchFs,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsWatchError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsWatchError> for i32 {
+    fn from(code: FsWatchError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_watch_poll`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsWatchPollError {
+    /// `-1` If the watcher does not exist
+    NoSuchWatcher,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsWatchPollError {
+    fn code(self) -> i32 {
+        match self {
+            Self::NoSuchWatcher => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            -1 => Self::NoSuchWatcher,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsWatchPollError {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsWatchPollError> for i32 {
+    fn from(code: FsWatchPollError) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_watch_wait`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsWatchWaitResult {
+    /// `0` If changes are queued
+    Ok,
+    /// `-1` If the watcher does not exist
+    NoSuchWatcher,
+    /// `-2` If nothing was queued before the timeout
+    Timeout,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsWatchWaitResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchWatcher => -1,
+            Self::Timeout => -2,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchWatcher,
+            -2 => Self::Timeout,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsWatchWaitResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsWatchWaitResult> for i32 {
+    fn from(code: FsWatchWaitResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_unwatch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsUnwatchResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the watcher does not exist
+    NoSuchWatcher,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsUnwatchResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchWatcher => -1,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchWatcher,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsUnwatchResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsUnwatchResult> for i32 {
+    fn from(code: FsUnwatchResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_lock`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileLockResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// `-3` If another process holds a lock on the file that conflicts with it
+    Locked,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileLockResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::Locked => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            -3 => Self::Locked,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileLockResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileLockResult> for i32 {
+    fn from(code: FsFileLockResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_lock_wait`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileLockWaitResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// `-3` If a conflicting lock was still held when the timeout passed
+    Locked,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileLockWaitResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::Locked => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            -3 => Self::Locked,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileLockWaitResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileLockWaitResult> for i32 {
+    fn from(code: FsFileLockWaitResult) -> Self {
+        code.code()
+    }
+}
+
+/// The codes of `hapi_fs_file_unlock`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsFileUnlockResult {
+    /// `0` On success
+    Ok,
+    /// `-1` If the file does not exist
+    NoSuchFile,
+    /// `-2` If the fs label does not correspond to an active fs
+    NoSuchFs,
+    /// `-3` If the process holds no lock on the file
+    NotLocked,
+    /// A code that is not part of the abi
+    Unknown(i32),
+}
+
+impl AbiCode for FsFileUnlockResult {
+    fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::NoSuchFile => -1,
+            Self::NoSuchFs => -2,
+            Self::NotLocked => -3,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            -1 => Self::NoSuchFile,
+            -2 => Self::NoSuchFs,
+            -3 => Self::NotLocked,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<i32> for FsFileUnlockResult {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<FsFileUnlockResult> for i32 {
+    fn from(code: FsFileUnlockResult) -> Self {
+        code.code()
+    }
+}
+
+/// The `hapi` imports, implemented by a honeyos kernel or simulator for a single process.
+/// Every method serves the import with the same name, with pointers replaced by rust types.
+/// Output buffers become return values, and codes become enums.
+///
+/// The memory and thread imports work on the guest's memory and function table,
+/// so they are implemented by the wasm runtime and are not part of the trait.
+/// Variants of an import, such as the `_v2` imports, are served by the same method.
+pub trait Imports {
+    /// Get the version of the abi the host implements
+    fn abi_version(&mut self) -> u32;
+
+    /// Get the capabilities the host supports, as a bit set.
+    /// The imports of an unsupported capability are stubs that fail or do nothing, see Linking.
+    fn abi_capabilities(&mut self) -> u32;
+
+    /// Logs a string to the js console as info
+    fn js_console_log_info(&mut self, string: &str);
+
+    /// Logs a string to the js console as a warning
+    fn js_console_log_warn(&mut self, string: &str);
+
+    /// Logs a string to the js console as an error
+    fn js_console_log_error(&mut self, string: &str);
+
+    /// Evaluate a string as js code. Returns the result as a string.
+    fn js_console_eval(&mut self, source: &str) -> Option<String>;
+
+    /// Clear the process's stdout
+    fn stdout_clear(&mut self);
+
+    /// Clear last line in the process's stdout
+    fn stdout_clear_line(&mut self);
+
+    /// Clear N number of lines in the processes's stdout.
+    /// Will only clear up to the amount of lines.
+    fn stdout_clear_lines(&mut self, num: u32);
+
+    /// Print a string to process's stdout
+    fn stdout_write(&mut self, string: &str);
+
+    /// Write the proccess id to the buffer
+    fn process_get_pid(&mut self) -> String;
+
+    /// Write the current working directory to the buffer
+    fn process_get_cwd(&mut self) -> String;
+
+    /// Get the string length of current working directory, including the null terminator
+    fn process_get_cwd_length(&mut self) -> u32;
+
+    /// Sets the current working directory for the process.
+    fn process_set_cwd(&mut self, path: &str) -> ProcessSetCwdResult;
+
+    /// Spawn a wasm binary as a subprocess.
+    /// Writes the pid of the process to the provided buffer, unless null, or an empty string on failure.
+    fn process_spawn_subprocess(&mut self, bin: &[u8]) -> String;
+
+    /// Same as `hapi_process_spawn_subprocess`, but returns whether the process was spawned.
+    /// Writes the pid of the process to the provided buffer, unless null.
+    fn process_spawn_subprocess_v2(
+        &mut self,
+        bin: &[u8],
+    ) -> Result<String, ProcessSpawnSubprocessV2Error>;
+
+    /// Returns true if the process is alive
+    fn process_alive(&mut self, id: &str) -> bool;
+
+    /// Write the stdout of a process to a buffer.
+    /// Writes nothing if the process does not exist.
+    fn process_stdout(&mut self, id: &str) -> String;
+
+    /// Returns the current length of the stdout buffer, including the null terminator
+    fn process_stdout_length(&mut self, id: &str) -> Result<u32, ProcessStdoutLengthError>;
+
+    /// Attempt to take control of the display
+    fn display_assume_control(&mut self) -> DisplayAssumeControlResult;
+
+    /// Override the control over the display
+    fn display_override_control(&mut self);
+
+    /// Release the control over the display.
+    fn display_release_control(&mut self) -> DisplayReleaseControlResult;
+
+    /// Take away the control over the display from the currently controling process,
+    /// regardless of whether the process has control.
+    fn display_displace_control(&mut self);
+
+    /// Push stdout to the display's text-mode buffer.
+    /// Do nothing if the process does not have control of the display.
+    fn display_push_stdout(&mut self) -> DisplayPushStdoutResult;
+
+    /// Set the text in the displays text-mode buffer.
+    /// Do nothing if the process does not have control of the display.
+    fn display_set_text(&mut self, text: &str) -> DisplaySetTextResult;
+
+    /// Get the key in the displays key buffer.
+    /// Do nothing if the process does not have control of the display.
+    fn display_get_key_buffer(&mut self) -> Result<i32, DisplayGetKeyBufferError>;
+
+    /// Whether or not the shift key is in the key buffer
+    /// Do nothing if the process does not have control of the display.
+    fn display_get_key_shift(&mut self) -> Result<bool, DisplayGetKeyShiftError>;
+
+    /// Whether or not the control key is in the key buffer
+    /// Do nothing if the process does not have control of the display.
+    fn display_get_key_ctrl(&mut self) -> Result<bool, DisplayGetKeyCtrlError>;
+
+    /// Clears the key buffer of the display
+    /// Do nothing if the process does not have control of the display.
+    fn display_clear_key(&mut self);
+
+    /// Get the time in seconds since the start of the unix epoch
+    fn time_since_unix_epoch(&mut self) -> f64;
+
+    /// Get the time in seconds since the start of the process
+    fn time_since_startup(&mut self) -> f64;
+
+    /// Returns a pointer to the user agent.
+    fn browser_user_agent(&mut self) -> Option<String>;
+
+    /// Returns the length of the user agent string
+    fn browser_user_agent_length(&mut self) -> u32;
+
+    /// Returns whether the browser is online
+    fn browser_is_online(&mut self) -> bool;
+
+    /// Create a network request and return it's id.
+    fn network_request(&mut self, url: &str, method: u32, headers: &str) -> Option<String>;
+
+    /// Create a network request to the local server and return it's id.
+    fn network_request_local(&mut self, url: &str, method: u32, headers: &str) -> Option<String>;
+
+    /// Check the status of the request
+    fn network_request_status(&mut self, id: &str) -> Result<u32, NetworkRequestStatusError>;
+
+    /// Check the length of the data in bytes.
+    fn network_request_data_length(
+        &mut self,
+        id: &str,
+    ) -> Result<u32, NetworkRequestDataLengthError>;
+
+    /// Check the data in a request
+    fn network_request_data(&mut self, id: &str) -> Option<Vec<u8>>;
+
+    /// Drop the request from memory.
+    /// Does nothing if the request does not exist.
+    fn network_request_drop(&mut self, id: &str);
+
+    /// Register a ram filesystem with the provided label.
+    fn fs_init_ramfs(&mut self, label: u8) -> FsInitRamfsResult;
+
+    /// Create a file at the path.
+    fn fs_file_create(&mut self, path: &str) -> FsFileCreateResult;
+
+    /// Find a file at disk and return it's id
+    fn fs_file_get(&mut self, path: &str) -> Result<String, FsFileGetError>;
+
+    /// Write a set amount of bytes to a file
+    fn fs_file_write(
+        &mut self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &[u8],
+    ) -> FsFileWriteResult;
+
+    /// Read a set amount of bytes from the file and write it to a buffer
+    fn fs_file_read(
+        &mut self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &mut [u8],
+    ) -> FsFileReadResult;
+
+    /// Return a file's length
+    fn fs_file_size(&mut self, fs_label: u8, file_id: &str) -> Result<u32, FsFileSizeError>;
+
+    /// Create a directory at the path.
+    fn fs_directory_create(&mut self, path: &str) -> FsDirectoryCreateResult;
+
+    /// Find a directory at disk and return it's id
+    fn fs_directory_get(&mut self, path: &str) -> Result<String, FsDirectoryGetError>;
+
+    /// List the entries of a directory into a buffer, if the listing fits in it.
+    /// Each entry is encoded as its kind as a u8 (`0` for a file, `1` for a directory), its size as a little endian u32 (`0` for directories),
+    /// its 36 byte id, the length of its name as a little endian u32, and its name as utf-8.
+    /// Entries are sorted by name.
+    fn fs_directory_list(
+        &mut self,
+        fs_label: u8,
+        dir_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsDirectoryListError>;
+
+    /// Remove the file at a path. Open handles to it stop working.
+    fn fs_file_remove(&mut self, path: &str) -> FsFileRemoveResult;
+
+    /// Remove the directory at a path.
+    /// Unless `recursive` is `1` the directory must be empty.
+    fn fs_directory_remove(&mut self, path: &str, recursive: u8) -> FsDirectoryRemoveResult;
+
+    /// Move a file or directory to a new path on the same fs, keeping its id.
+    /// A file at the new path is replaced.
+    fn fs_rename(&mut self, from: &str, to: &str) -> FsRenameResult;
+
+    /// Copy the contents of a file to a new path, which may be on another fs.
+    /// A file at the new path is overwritten, keeping its id.
+    fn fs_file_copy(&mut self, from: &str, to: &str) -> Result<u32, FsFileCopyError>;
+
+    /// Get the metadata of the file or directory at a path, if it fits in the buffer.
+    /// The metadata is encoded as the kind as a u8 (`0` for a file, `1` for a directory), `1` if it is read-only as a u8,
+    /// the length as a little endian u32 (`0` for directories),
+    /// and the created, modified and accessed times as little endian f64 seconds since the start of the unix epoch.
+    /// Later versions may add fields to the end.
+    fn fs_metadata(&mut self, path: &str, buffer: &mut [u8]) -> Result<u32, FsMetadataError>;
+
+    /// Set the modified time of the file or directory at a path, in seconds since the start of the unix epoch.
+    fn fs_set_modified(&mut self, path: &str, modified: f64) -> FsSetModifiedResult;
+
+    /// Open the file at a path and write it's id to the buffer, in a single step.
+    /// `flags` is a bit set of `1` to create the file if it doesn't exist, `2` to fail if it exists, and `4` to truncate it to 0 bytes.
+    fn fs_file_open(&mut self, path: &str, flags: u32) -> Result<String, FsFileOpenError>;
+
+    /// Truncate or extend a file to a length. New bytes are zero.
+    fn fs_file_set_len(&mut self, fs_label: u8, file_id: &str, len: u32) -> FsFileSetLenResult;
+
+    /// Write bytes to the end of a file. The length is read and the bytes written in one step,
+    /// so appends from several processes never overwrite each other.
+    fn fs_file_append(&mut self, fs_label: u8, file_id: &str, buffer: &[u8]) -> FsFileAppendResult;
+
+    /// Register a ram filesystem with the provided label, that can hold at most `capacity` bytes of file contents.
+    fn fs_init_ramfs_with_capacity(
+        &mut self,
+        label: u8,
+        capacity: u32,
+    ) -> FsInitRamfsWithCapacityResult;
+
+    /// Unmount the filesystem with the provided label. Open files and directories on it stop working.
+    fn fs_unmount(&mut self, label: u8) -> FsUnmountResult;
+
+    /// List the mounted filesystems into a buffer, if the list fits in it.
+    /// Each filesystem is encoded as its label as a u8, its type as a u8 (`0` for a ram filesystem, `1` for a persistent filesystem, `2` for an overlay filesystem),
+    /// the bytes used by file contents as a little endian u32, and its capacity as a little endian u32 (`0xffffffff` if it has no limit).
+    /// Filesystems are sorted by label.
+    fn fs_mounts(&mut self, buffer: &mut [u8]) -> u32;
+
+    /// Register a persistent filesystem with the provided label.
+    /// Its contents are kept in storage by the host, and survive reloads and restarts.
+    fn fs_init_persistent(&mut self, label: u8) -> FsInitPersistentResult;
+
+    /// Register an overlay filesystem with the provided label, showing the `lower` filesystem under a writable ram layer.
+    /// Changed files are copied up to the ram layer and deletions are hidden there, so the lower filesystem is never written to.
+    fn fs_init_overlay(&mut self, label: u8, lower: u8) -> FsInitOverlayResult;
+
+    /// Start watching a file or directory for changes made by any process, and write the watcher's id to the buffer.
+    /// A directory watcher sees changes to the directory and its entries, or everything under it if `recursive` is `1`.
+    /// Changes are queued until they are read with `hapi_fs_watch_poll`.
+    /// The watcher is stopped when the process that started it dies, or the file system is unmounted.
+    fn fs_watch(&mut self, path: &str, recursive: u8) -> Result<String, FsWatchError>;
+
+    /// Move the changes queued for a watcher into a buffer, if they all fit in it.
+    /// Each change is encoded as its kind as a u8 (`0` for a create, `1` for a modify, `2` for a remove and `3` for a rename),
+    /// the length of its path as a little endian u32, its path as utf-8, the length of the new path as a little endian u32 (`0` unless it is a rename),
+    /// and the new path as utf-8. Paths are absolute, and changes are in the order they were made.
+    /// At most 1024 changes are queued for a watcher. Once it is full later changes are dropped,
+    /// and a single overflow change (`4`) with the watched path is queued until it is polled, so the app knows to look at the path again.
+    fn fs_watch_poll(
+        &mut self,
+        watcher_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsWatchPollError>;
+
+    /// Block until changes are queued for a watcher, or `timeout_ms` milliseconds have passed.
+    /// Returns at once if changes are already queued, and doesn't dequeue them.
+    fn fs_watch_wait(&mut self, watcher_id: &str, timeout_ms: u32) -> FsWatchWaitResult;
+
+    /// Stop a watcher and drop the changes queued for it.
+    fn fs_unwatch(&mut self, watcher_id: &str) -> FsUnwatchResult;
+
+    /// Take an advisory lock on a file for the process, without waiting. `exclusive` is `1` for an exclusive lock and `0` for a shared one.
+    /// Any number of processes can hold a shared lock on a file, but only one an exclusive lock, and not while others hold a shared one.
+    /// A lock the process already holds on the file is replaced. Locks are released when the process dies or the file is removed, and don't stop reads or writes.
+    fn fs_file_lock(&mut self, fs_label: u8, file_id: &str, exclusive: u8) -> FsFileLockResult;
+
+    /// Take an advisory lock on a file like `hapi_fs_file_lock`, blocking while another process holds a conflicting lock
+    /// until it is released or `timeout_ms` milliseconds have passed.
+    fn fs_file_lock_wait(
+        &mut self,
+        fs_label: u8,
+        file_id: &str,
+        exclusive: u8,
+        timeout_ms: u32,
+    ) -> FsFileLockWaitResult;
+
+    /// Release the lock the process holds on a file.
+    fn fs_file_unlock(&mut self, fs_label: u8, file_id: &str) -> FsFileUnlockResult;
+}
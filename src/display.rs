@@ -1,11 +1,16 @@
-use std::{ffi::CString, marker::PhantomData};
+use core::marker::PhantomData;
 
-use crate::util::keys::KeyCode;
+use crate::{
+    abi::{DisplayAssumeControlResult, DisplayReleaseControlResult},
+    prelude::*,
+    util::keys::KeyCode,
+};
 
 /// The errors for the display
 #[derive(Debug)]
 pub enum DisplayError {
     Occupied,
+    NotInControl,
 }
 
 /// The os's display
@@ -25,62 +30,56 @@ impl Display {
     /// ### Errors
     /// - `DisplayError::Occupied` When the display is in control of another process
     pub fn assume_control() -> Result<(), DisplayError> {
-        let result = unsafe { crate::ffi::hapi_display_assume_control() };
-        if result < 0 {
-            return Err(DisplayError::Occupied);
+        match crate::host::current().display_assume_control() {
+            DisplayAssumeControlResult::Ok => Ok(()),
+            _ => Err(DisplayError::Occupied),
         }
-        Ok(())
     }
 
     /// Override the control of the current process
     pub fn override_control() {
-        unsafe {
-            crate::ffi::hapi_display_override_control();
-        }
+        crate::host::current().display_override_control();
     }
 
     /// Release control of the display
+    /// ### Errors
+    /// - `DisplayError::NotInControl` When the process is not in control of the display
     pub fn release_control() -> Result<(), DisplayError> {
-        let result = unsafe { crate::ffi::hapi_display_release_control() };
-        if result < 0 {
-            return Err(DisplayError::Occupied);
+        match crate::host::current().display_release_control() {
+            DisplayReleaseControlResult::Ok => Ok(()),
+            _ => Err(DisplayError::NotInControl),
         }
-        Ok(())
     }
 
     /// Take away the control over the display from the currently controling process,
     /// regardless of whether the process has control.
     pub fn displace_control() {
-        unsafe { crate::ffi::hapi_display_displace_control() };
+        crate::host::current().display_displace_control();
     }
 
     /// Push the process's stdout to the display's text buffer.
     /// Will do nothing if this process does not have control of the display.
     pub fn push_stdout() {
-        unsafe { crate::ffi::hapi_display_push_stdout() };
+        crate::host::current().display_push_stdout();
     }
 
     /// Set the text on the display's text buffer
     /// Will do nothing if this process does not have control of the display.
     pub fn set_text(text: impl Into<String>) {
         let text: String = text.into();
-        let text_cstr = CString::new(text.clone()).unwrap();
-
-        unsafe { crate::ffi::hapi_display_set_text(text_cstr.as_ptr() as *const u8) };
+        crate::host::current().display_set_text(&text);
     }
 
     /// Get the key from the the key buffer and clear it
     /// Will return nothing if the key buffer is empty, or if this process does not have control of the display.
     pub fn key_buffer() -> Option<KeyPress> {
-        let key = unsafe { crate::ffi::hapi_display_get_key_buffer() };
-        if key <= -1 {
-            return None;
-        }
+        let host = crate::host::current();
+        let key = host.display_get_key_buffer().ok()?;
 
-        let shift = unsafe { crate::ffi::hapi_display_get_key_shift() } != 0;
-        let ctrl = unsafe { crate::ffi::hapi_display_get_key_ctrl() } != 0;
+        let shift = host.display_get_key_shift().unwrap_or_default();
+        let ctrl = host.display_get_key_ctrl().unwrap_or_default();
 
-        unsafe { crate::ffi::hapi_display_clear_key() };
+        host.display_clear_key();
         Some(KeyPress {
             key,
             shift,
@@ -99,13 +98,17 @@ impl KeyPress {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for DisplayError {}
 
-impl std::fmt::Display for DisplayError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             DisplayError::Occupied => {
-                writeln!(f, "The display is currently occupied by another process")
+                write!(f, "The display is currently occupied by another process")
+            }
+            DisplayError::NotInControl => {
+                write!(f, "The process is not in control of the display")
             }
         }
     }
@@ -0,0 +1,362 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "display")]
+use crate::display::DisplayError;
+#[cfg(feature = "fs")]
+use crate::fs;
+#[cfg(feature = "network")]
+use crate::network::NetworkError;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+/// A result with the crate-wide [`Error`]
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// The kinds of [`Error`].
+/// Every kind has a stable code, which is never reused even if the kind is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A file, directory, process or request does not exist
+    NotFound,
+    /// A file, directory or file system already exists
+    AlreadyExists,
+    /// A path, label, string or header is not valid
+    InvalidInput,
+    /// The host returned a value that could not be understood
+    InvalidData,
+    /// No file system is mounted at the label
+    NotMounted,
+    /// The file system is out of space
+    StorageFull,
+    /// The display is in control of another process, or the file is locked by one
+    Occupied,
+    /// The process is not in control of the display
+    NotInControl,
+    /// The request has not completed yet
+    NotReady,
+    /// The request failed to complete
+    RequestFailed,
+    /// The host failed to allocate memory
+    OutOfMemory,
+    /// The subprocess failed to spawn
+    SpawnFailed,
+    /// The host does not support the call
+    Unsupported,
+    /// The directory has contents
+    DirectoryNotEmpty,
+    /// The paths are on different file systems
+    CrossesDevices,
+    /// The file was not opened for the operation, or the file system is read-only
+    PermissionDenied,
+    /// Any other error
+    Other,
+}
+
+/// The error of every subsystem.
+/// Carries a stable [`ErrorKind`], the `hapi_*` import that failed if it is known,
+/// and the error of the subsystem it came from.
+/// Without `std` only the message of that error is kept.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    call: Option<&'static str>,
+    #[cfg(feature = "std")]
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    #[cfg(not(feature = "std"))]
+    source: Option<String>,
+}
+
+impl ErrorKind {
+    /// The stable code of the kind
+    pub fn code(self) -> u32 {
+        match self {
+            Self::NotFound => 1,
+            Self::AlreadyExists => 2,
+            Self::InvalidInput => 3,
+            Self::InvalidData => 4,
+            Self::NotMounted => 5,
+            Self::StorageFull => 6,
+            Self::Occupied => 7,
+            Self::NotInControl => 8,
+            Self::NotReady => 9,
+            Self::RequestFailed => 10,
+            Self::OutOfMemory => 11,
+            Self::SpawnFailed => 12,
+            Self::Unsupported => 13,
+            Self::Other => 14,
+            Self::DirectoryNotEmpty => 15,
+            Self::CrossesDevices => 16,
+            Self::PermissionDenied => 17,
+        }
+    }
+
+    /// The closest [`io::ErrorKind`]
+    #[cfg(feature = "std")]
+    pub fn io_kind(self) -> io::ErrorKind {
+        match self {
+            Self::NotFound | Self::NotMounted => io::ErrorKind::NotFound,
+            Self::AlreadyExists => io::ErrorKind::AlreadyExists,
+            Self::InvalidInput => io::ErrorKind::InvalidInput,
+            Self::InvalidData => io::ErrorKind::InvalidData,
+            Self::NotInControl | Self::PermissionDenied => io::ErrorKind::PermissionDenied,
+            Self::NotReady => io::ErrorKind::WouldBlock,
+            Self::OutOfMemory => io::ErrorKind::OutOfMemory,
+            Self::Unsupported => io::ErrorKind::Unsupported,
+            Self::StorageFull
+            | Self::Occupied
+            | Self::RequestFailed
+            | Self::SpawnFailed
+            | Self::DirectoryNotEmpty
+            | Self::CrossesDevices
+            | Self::Other => io::ErrorKind::Other,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn from_io_kind(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::NotFound => Self::NotFound,
+            io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+            io::ErrorKind::InvalidInput => Self::InvalidInput,
+            io::ErrorKind::InvalidData => Self::InvalidData,
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            io::ErrorKind::WouldBlock => Self::NotReady,
+            io::ErrorKind::OutOfMemory => Self::OutOfMemory,
+            io::ErrorKind::Unsupported => Self::Unsupported,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::NotFound => "not found",
+            Self::AlreadyExists => "already exists",
+            Self::InvalidInput => "invalid input",
+            Self::InvalidData => "invalid data from the host",
+            Self::NotMounted => "no file system mounted",
+            Self::StorageFull => "no space left on the file system",
+            Self::Occupied => "held by another process",
+            Self::NotInControl => "not in control of the display",
+            Self::NotReady => "the request is not complete",
+            Self::RequestFailed => "the request failed",
+            Self::OutOfMemory => "out of memory",
+            Self::SpawnFailed => "failed to spawn the process",
+            Self::Unsupported => "not supported by the host",
+            Self::DirectoryNotEmpty => "the directory is not empty",
+            Self::CrossesDevices => "the paths are on different file systems",
+            Self::PermissionDenied => "permission denied",
+            Self::Other => "other error",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error {
+    /// Create an error of the kind, raised by the import
+    pub fn new(kind: ErrorKind, call: &'static str) -> Self {
+        Self {
+            kind,
+            call: Some(call),
+            source: None,
+        }
+    }
+
+    /// Create an error of the kind, caused by another error
+    #[cfg(feature = "std")]
+    pub fn other(
+        kind: ErrorKind,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self {
+            kind,
+            call: None,
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create an error of the kind, caused by another error
+    #[cfg(not(feature = "std"))]
+    pub fn other(kind: ErrorKind, source: impl fmt::Display) -> Self {
+        Self {
+            kind,
+            call: None,
+            source: Some(source.to_string()),
+        }
+    }
+
+    /// Set the import that failed
+    pub fn with_call(mut self, call: &'static str) -> Self {
+        self.call = Some(call);
+        self
+    }
+
+    /// Get the kind of the error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Get the stable code of the error
+    pub fn code(&self) -> u32 {
+        self.kind.code()
+    }
+
+    /// Get the `hapi_*` import that failed, if it is known
+    pub fn call(&self) -> Option<&'static str> {
+        self.call
+    }
+
+    /// Get the closest [`io::ErrorKind`]
+    #[cfg(feature = "std")]
+    pub fn io_kind(&self) -> io::ErrorKind {
+        self.kind.io_kind()
+    }
+
+    /// Get the error of the subsystem
+    #[cfg(feature = "std")]
+    pub fn get_ref(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        self.source.as_deref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    /// The source of the error of the subsystem, as `Display` already prints that error like `io::Error` does.
+    /// The error of the subsystem itself is returned by [`Error::get_ref`].
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().and_then(|source| source.source())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(call) = self.call {
+            write!(f, "{}: ", call)?;
+        }
+        match &self.source {
+            Some(source) => write!(f, "{}", source),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            call: None,
+            source: None,
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl From<fs::error::Error> for Error {
+    fn from(error: fs::error::Error) -> Self {
+        use fs::error::Error as FsError;
+
+        // Errors raised by several imports, or by hapi itself, have no call
+        let (kind, call) = match &error {
+            FsError::NoFsLabel(_) | FsError::NotAFsLabel(_) | FsError::InvalidPath(_) => {
+                (ErrorKind::InvalidInput, None)
+            }
+            FsError::NoSuchFile(_)
+            | FsError::NoSuchFileWithId(_)
+            | FsError::NoSuchDirectory(_)
+            | FsError::NoSuchDirectoryWithId(_) => (ErrorKind::NotFound, None),
+            FsError::FileExists(_) | FsError::DirExists(_) | FsError::FsAlreadyExists(_) => {
+                (ErrorKind::AlreadyExists, None)
+            }
+            FsError::NoSuchFs(_) => (ErrorKind::NotMounted, None),
+            FsError::NotEnoughSpace(_) => (ErrorKind::StorageFull, None),
+            FsError::StorageUnavailable(_) => {
+                (ErrorKind::Unsupported, Some("hapi_fs_init_persistent"))
+            }
+            FsError::NestedOverlay(_) => (ErrorKind::InvalidInput, Some("hapi_fs_init_overlay")),
+            FsError::ReadOnly(_) => (ErrorKind::PermissionDenied, None),
+            FsError::FetchFailed(_) => (ErrorKind::RequestFailed, None),
+            FsError::InvalidManifest(_) => (ErrorKind::InvalidData, None),
+            FsError::InvalidId(_) => (ErrorKind::InvalidData, None),
+            FsError::DestinationExists(_) => (ErrorKind::AlreadyExists, None),
+            FsError::DirNotEmpty(_) => (
+                ErrorKind::DirectoryNotEmpty,
+                Some("hapi_fs_directory_remove"),
+            ),
+            FsError::CrossDevice(_, _) => (ErrorKind::CrossesDevices, Some("hapi_fs_rename")),
+            FsError::InvalidListing => (ErrorKind::InvalidData, Some("hapi_fs_directory_list")),
+            FsError::InvalidOpenOptions(_) => (ErrorKind::InvalidInput, None),
+            FsError::NotReadable(_) | FsError::NotWritable(_) => {
+                (ErrorKind::PermissionDenied, None)
+            }
+            FsError::InvalidMetadata => (ErrorKind::InvalidData, Some("hapi_fs_metadata")),
+            FsError::InvalidMountList => (ErrorKind::InvalidData, Some("hapi_fs_mounts")),
+            FsError::InvalidArchive(_) => (ErrorKind::InvalidData, None),
+            FsError::InvalidChangeList => (ErrorKind::InvalidData, Some("hapi_fs_watch_poll")),
+            FsError::NoSuchWatcher(_) => (ErrorKind::NotFound, Some("hapi_fs_watch_poll")),
+            FsError::Locked(_) => (ErrorKind::Occupied, Some("hapi_fs_file_lock")),
+            FsError::NotLocked(_) => (ErrorKind::InvalidInput, Some("hapi_fs_file_unlock")),
+        };
+        Self {
+            kind,
+            call,
+            source: Some(Box::new(error)),
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+impl From<NetworkError> for Error {
+    fn from(error: NetworkError) -> Self {
+        let (kind, call) = match &error {
+            NetworkError::InvalidRequestId(_) => {
+                (ErrorKind::NotFound, "hapi_network_request_status")
+            }
+            NetworkError::InvalidHeaders => (ErrorKind::InvalidInput, "hapi_network_request"),
+            NetworkError::InvalidId(_) => (ErrorKind::InvalidData, "hapi_network_request"),
+            NetworkError::InvalidRequestStatus(_) => {
+                (ErrorKind::InvalidData, "hapi_network_request_status")
+            }
+            NetworkError::RequestFailure(_) => {
+                (ErrorKind::RequestFailed, "hapi_network_request_status")
+            }
+            NetworkError::StillProcessing(_) | NetworkError::StillPending(_) => {
+                (ErrorKind::NotReady, "hapi_network_request_status")
+            }
+            NetworkError::AllocFailure(_) => (ErrorKind::OutOfMemory, "hapi_network_request_data"),
+        };
+        Self::other(kind, error).with_call(call)
+    }
+}
+
+#[cfg(feature = "display")]
+impl From<DisplayError> for Error {
+    fn from(error: DisplayError) -> Self {
+        let (kind, call) = match &error {
+            DisplayError::Occupied => (ErrorKind::Occupied, "hapi_display_assume_control"),
+            DisplayError::NotInControl => (ErrorKind::NotInControl, "hapi_display_release_control"),
+        };
+        Self::other(kind, error).with_call(call)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        // Unwrap errors that were converted into io errors
+        if error.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            return *error.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+        Self::other(ErrorKind::from_io_kind(error.kind()), error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::new(error.io_kind(), error)
+    }
+}
@@ -1,18 +1,59 @@
-use std::os::raw::c_void;
+//! The `hapi` wasm imports.
+//! Generated from `abi/hapi.json` by `cargo run -p hapi-abi`, do not edit by hand.
+#[cfg(not(feature = "sim"))]
+use core::ffi::c_void;
 
+#[cfg(feature = "sim")]
+pub use crate::sim::ffi::*;
+
+#[cfg(not(feature = "sim"))]
 #[link(wasm_import_module = "hapi")]
 extern "C" {
+    /// Get the version of the abi the host implements
+    /// ### Note
+    /// Every host must provide this import. Kernels older than version 2 do not, and can't load modules built against this abi.
+    pub fn hapi_abi_version() -> u32;
+    /// Get the capabilities the host supports, as a bit set.
+    /// The imports of an unsupported capability are stubs that fail or do nothing, see Linking.
+    pub fn hapi_abi_capabilities() -> u32;
     /// Logs a string to the js console as info
+    /// ### Safety
+    /// - `string` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_js_console_log_info(string: *const u8);
+    /// Same as `hapi_js_console_log_info`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Safety
+    /// - `string` must be at least `string_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_js_console_log_info_v2(string: *const u8, string_len: u32);
     /// Logs a string to the js console as a warning
+    /// ### Safety
+    /// - `string` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_js_console_log_warn(string: *const u8);
+    /// Same as `hapi_js_console_log_warn`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Safety
+    /// - `string` must be at least `string_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_js_console_log_warn_v2(string: *const u8, string_len: u32);
     /// Logs a string to the js console as an error
+    /// ### Safety
+    /// - `string` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_js_console_log_error(string: *const u8);
+    /// Same as `hapi_js_console_log_error`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Safety
+    /// - `string` must be at least `string_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_js_console_log_error_v2(string: *const u8, string_len: u32);
     /// Evaluate a string as js code. Returns the result as a string.
+    /// ### Returns
+    /// - The result of the evaluation, or `NULL` if it failed
     /// ### Safety
-    /// - The string must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `source` must be a valid null-terminated string or unallocated memory will be read from.
     /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
     pub fn hapi_js_console_eval(source: *const u8) -> *const u8;
+    /// Same as `hapi_js_console_eval`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - The result of the evaluation, or `NULL` if it failed
+    /// ### Safety
+    /// - `source` must be at least `source_len` bytes long or unallocated memory will be read from.
+    /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
+    pub fn hapi_js_console_eval_v2(source: *const u8, source_len: u32) -> *const u8;
     /// Clear the process's stdout
     pub fn hapi_stdout_clear();
     /// Clear last line in the process's stdout
@@ -21,16 +62,22 @@ extern "C" {
     /// Will only clear up to the amount of lines.
     pub fn hapi_stdout_clear_lines(num: u32);
     /// Print a string to process's stdout
+    /// ### Safety
+    /// - `string` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_stdout_write(string: *const u8);
+    /// Same as `hapi_stdout_write`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Safety
+    /// - `string` must be at least `string_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_stdout_write_v2(string: *const u8, string_len: u32);
     /// Write the proccess id to the buffer
     /// ### Safety
-    /// - The buffer size must be at least 37-bytes or unallocated memory will be written to.
+    /// - `buffer` must be at least 37-bytes long or unallocated memory will be written to.
     pub fn hapi_process_get_pid(buffer: *mut u8);
     /// Write the current working directory to the buffer
     /// ### Safety
-    /// - The buffer size must be at least the size of `hapi_process_get_cwd_length` or unallocated memory will be written to.
+    /// - `buffer` must be at least the size of `hapi_process_get_cwd_length` or unallocated memory will be written to.
     pub fn hapi_process_get_cwd(buffer: *mut u8);
-    // Get the string length of current working directory
+    /// Get the string length of current working directory, including the null terminator
     pub fn hapi_process_get_cwd_length() -> u32;
     /// Sets the current working directory for the process.
     /// ### Note
@@ -38,44 +85,61 @@ extern "C" {
     /// ### Returns
     /// - `0` On success
     /// - `-1` If the path is invalid
+    /// ### Safety
+    /// - `path` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_process_set_cwd(path: *const u8) -> i32;
+    /// Same as `hapi_process_set_cwd`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the path is invalid
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_process_set_cwd_v2(path: *const u8, path_len: u32) -> i32;
     /// Spawn a wasm binary as a subprocess.
-    /// Writes the pid of the process to the provided buffer, unless null.
+    /// Writes the pid of the process to the provided buffer, unless null, or an empty string on failure.
     /// ### Safety
-    /// - The provided buffer must be at least 37-bytes of length or unallocated memory will be written to
+    /// - `bin` must be at least `bin_len` bytes long or unallocated memory will be read from.
+    /// - `pid_out` must be null or at least 37-bytes long or unallocated memory will be written to.
+    pub fn hapi_process_spawn_subprocess(bin: *const u8, bin_len: u32, pid_out: *mut u8);
+    /// Same as `hapi_process_spawn_subprocess`, but returns whether the process was spawned.
+    /// Writes the pid of the process to the provided buffer, unless null.
     /// ### Returns
     /// - `0` On success
     /// - `-1` On failure
-    pub fn hapi_process_spawn_subprocess(bin: *const u8, bin_len: u32, pid_out: *mut u8);
+    /// ### Safety
+    /// - `bin` must be at least `bin_len` bytes long or unallocated memory will be read from.
+    /// - `pid_out` must be null or at least 37-bytes long or unallocated memory will be written to.
+    pub fn hapi_process_spawn_subprocess_v2(bin: *const u8, bin_len: u32, pid_out: *mut u8) -> i32;
     /// Returns true if the process is alive
+    /// ### Safety
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_process_alive(id: *const u8) -> i32;
-    /// Write the stoud of a process to a buffer
+    /// Write the stdout of a process to a buffer.
+    /// Writes nothing if the process does not exist.
     /// ### Safety
-    /// - The out buffer must be equal to `hapi_process_stdout_length` or unallocated memory will be written to.
-    /// - The id must be at least 37-bytes in length and a valid string or unallocated memory will be read from.
-    /// ### Returns
-    /// - `0` On success
-    /// - `-1` on failure
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `out_buffer` must be at least the size of `hapi_process_stdout_length` or unallocated memory will be written to.
     pub fn hapi_process_stdout(id: *const u8, out_buffer: *mut u8);
-    /// Returns the current length of the stdout buffer
+    /// Returns the current length of the stdout buffer, including the null terminator
     /// ### Returns
     /// - The length of the stdout buffer
-    /// - `-1` If the id cannot be read from memory
+    /// - `-1` If the process does not exist or the id cannot be read from memory
     /// ### Safety
-    /// - The id must be at least 37-bytes in length and a valid string or unallocated memory will be read from.
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_process_stdout_length(id: *const u8) -> i32;
     /// Allocate a block of memory and return it's pointer.
     /// ### Returns
-    /// - The pointer to the block
-    /// - `NULL` if the memory allocation failed
+    /// - The pointer to the block, or `NULL` if the memory allocation failed
     pub fn hapi_mem_alloc(size: u32) -> *mut c_void;
     /// Reallocate a block of memory and return the new pointer.
     /// ### Returns
-    /// - The pointer to the block
-    /// - `NULL` if the memory allocation failed
+    /// - The pointer to the block, or `NULL` if the memory allocation failed
+    /// ### Safety
+    /// - `ptr` must be a block allocated with `hapi_mem_alloc`.
     pub fn hapi_mem_realloc(ptr: *mut c_void, size: u32) -> *mut c_void;
-    // hapi_mem_free
-    // Free a block of memory
+    /// Free a block of memory
+    /// ### Safety
+    /// - `ptr` must be a block allocated with `hapi_mem_alloc`.
     pub fn hapi_mem_free(ptr: *mut c_void);
     /// Attempt to take control of the display
     /// ### Returns
@@ -87,11 +151,11 @@ extern "C" {
     /// Release the control over the display.
     /// ### Returns
     /// - `0` On success
-    /// - `-1` If the display is occupied
+    /// - `-1` If the process doesn't have control over the display
     pub fn hapi_display_release_control() -> i32;
     /// Take away the control over the display from the currently controling process,
     /// regardless of whether the process has control.
-    pub fn hapi_display_displace_control(); // "We workers must take control of the means of display" - GetAGripGal
+    pub fn hapi_display_displace_control();
     /// Push stdout to the display's text-mode buffer.
     /// Do nothing if the process does not have control of the display.
     /// ### Returns
@@ -104,13 +168,23 @@ extern "C" {
     /// - `0` On success
     /// - `-1` If the process doesn't have control over the display
     /// - `-2` If the string is invalid
+    /// ### Safety
+    /// - `text` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_display_set_text(text: *const u8) -> i32;
-    /// Get the key in the displays key buffer. Clears it afterwards.
+    /// Same as `hapi_display_set_text`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the process doesn't have control over the display
+    /// - `-2` If the string is invalid
+    /// ### Safety
+    /// - `text` must be at least `text_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_display_set_text_v2(text: *const u8, text_len: u32) -> i32;
+    /// Get the key in the displays key buffer.
     /// Do nothing if the process does not have control of the display.
     /// ### Returns
     /// - The key stored in the key buffer
     /// - `-1` If the process doesn't have control over the display
-    /// - `-2` or if the key buffer is empty.
+    /// - `-2` If the key buffer is empty
     pub fn hapi_display_get_key_buffer() -> i32;
     /// Whether or not the shift key is in the key buffer
     /// Do nothing if the process does not have control of the display.
@@ -126,9 +200,6 @@ extern "C" {
     pub fn hapi_display_get_key_ctrl() -> i32;
     /// Clears the key buffer of the display
     /// Do nothing if the process does not have control of the display.
-    /// ### Returns
-    /// - `0` On Success
-    /// - `-1` If the process doesn't have control over the display
     pub fn hapi_display_clear_key();
     /// Get the time in seconds since the start of the unix epoch
     pub fn hapi_time_since_unix_epoch() -> f64;
@@ -136,18 +207,18 @@ extern "C" {
     pub fn hapi_time_since_startup() -> f64;
     /// Returns a pointer to the user agent.
     /// ### Returns
-    /// - The user agent on success
-    /// - `NULL`` if failed allocate the string.
+    /// - The user agent on success, or `NULL` if failed allocate the string
+    /// ### Safety
+    /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
     pub fn hapi_browser_user_agent() -> *const u8;
     /// Returns the length of the user agent string
     pub fn hapi_browser_user_agent_length() -> u32;
     /// Returns whether the browser is online
     pub fn hapi_browser_is_online() -> u32;
     /// Create a network request and return it's id.
-    /// ### Returns:
-    /// - The id of the request success
-    /// - NULL if the request method was invalid, or when failed to parse headers as json.
-    /// ### Methods:
+    /// ### Returns
+    /// - The id of the request on success, or `NULL` if the request method was invalid, or when failed to parse headers as json
+    /// ### Methods
     /// - Get = 0
     /// - Head = 1
     /// - Post = 2
@@ -157,12 +228,39 @@ extern "C" {
     /// - Options = 6
     /// - Trace = 7
     /// - Patch = 8
+    /// ### Safety
+    /// - `url` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `headers` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
     pub fn hapi_network_request(url: *const u8, method: u32, headers: *const u8) -> *const u8;
+    /// Same as `hapi_network_request`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - The id of the request on success, or `NULL` if the request method was invalid, or when failed to parse headers as json
+    /// ### Methods
+    /// - Get = 0
+    /// - Head = 1
+    /// - Post = 2
+    /// - Put = 3
+    /// - Delete = 4
+    /// - Connect = 5
+    /// - Options = 6
+    /// - Trace = 7
+    /// - Patch = 8
+    /// ### Safety
+    /// - `url` must be at least `url_len` bytes long or unallocated memory will be read from.
+    /// - `headers` must be at least `headers_len` bytes long or unallocated memory will be read from.
+    /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
+    pub fn hapi_network_request_v2(
+        url: *const u8,
+        url_len: u32,
+        method: u32,
+        headers: *const u8,
+        headers_len: u32,
+    ) -> *const u8;
     /// Create a network request to the local server and return it's id.
-    /// ### Returns:
-    /// - The id of the request on success
-    /// - NULL if the request method was invalid, or when failed to parse headers as json.
-    /// ### Methods:
+    /// ### Returns
+    /// - The id of the request on success, or `NULL` if the request method was invalid, or when failed to parse headers as json
+    /// ### Methods
     /// - Get = 0
     /// - Head = 1
     /// - Post = 2
@@ -172,30 +270,61 @@ extern "C" {
     /// - Options = 6
     /// - Trace = 7
     /// - Patch = 8
+    /// ### Safety
+    /// - `url` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `headers` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
     pub fn hapi_network_request_local(url: *const u8, method: u32, headers: *const u8)
         -> *const u8;
+    /// Same as `hapi_network_request_local`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - The id of the request on success, or `NULL` if the request method was invalid, or when failed to parse headers as json
+    /// ### Methods
+    /// - Get = 0
+    /// - Head = 1
+    /// - Post = 2
+    /// - Put = 3
+    /// - Delete = 4
+    /// - Connect = 5
+    /// - Options = 6
+    /// - Trace = 7
+    /// - Patch = 8
+    /// ### Safety
+    /// - `url` must be at least `url_len` bytes long or unallocated memory will be read from.
+    /// - `headers` must be at least `headers_len` bytes long or unallocated memory will be read from.
+    /// - The returned string must be freed with `hapi_mem_free` or memory will be leaked.
+    pub fn hapi_network_request_local_v2(
+        url: *const u8,
+        url_len: u32,
+        method: u32,
+        headers: *const u8,
+        headers_len: u32,
+    ) -> *const u8;
     /// Check the status of the request
     /// ### Returns
-    /// - `-1` if the request does not exists.
-    /// - `0`if the request is pending.
-    /// - `1`if the request succeeded.
-    /// - `2`if the request failed.
-    /// - `3`if the request is still pending
+    /// - `0` if the request is processing, `1` if it succeeded, `2` if it failed, `3` if it is pending
+    /// - `-1` If the request does not exist
+    /// ### Safety
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_network_request_status(id: *const u8) -> i32;
-    /// Check the lenght of the data in bytes.
+    /// Check the length of the data in bytes.
     /// ### Returns
     /// - The data length on success
-    /// - -1 if the request does not exist.
+    /// - `-1` If the request does not exist
+    /// ### Safety
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_network_request_data_length(id: *const u8) -> i32;
     /// Check the data in a request
     /// ### Returns
-    /// - The data on success
-    /// - NULL if the request does not exist,
-    /// - NULL if the request has failed or is still pending,
-    /// - NULL if the memory allocation failed.
+    /// - The data on success, or `NULL` if the request does not exist, has failed, is still pending, or if the memory allocation failed
+    /// ### Safety
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - The returned bytes must be freed with `hapi_mem_free` or memory will be leaked, it's length is returned by `hapi_network_request_data_length`.
     pub fn hapi_network_request_data(id: *const u8) -> *const u8;
     /// Drop the request from memory.
     /// Does nothing if the request does not exist.
+    /// ### Safety
+    /// - `id` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_network_request_drop(id: *const u8);
     /// Register a ram filesystem with the provided label.
     /// ### Returns
@@ -213,27 +342,47 @@ extern "C" {
     /// - `-3` If the path string is invalid
     /// ### Panics
     /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_fs_file_create(path: *const u8) -> i32;
+    /// Same as `hapi_fs_file_create`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the directory doesn't exist
+    /// - `-2` If a file with the name already exists
+    /// - `-3` If the path string is invalid
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_create_v2(path: *const u8, path_len: u32) -> i32;
     /// Find a file at disk and return it's id
     /// ### Returns
     /// - `0` On success
-    /// - `-1` if the file does not exist or if the path is incorrect.
+    /// - `-1` If the file does not exist or if the path is incorrect
     /// ### Panics
     /// Panics if the filesystem is poisoned.
     /// ### Safety
-    /// The destination buffer must be the size of a UUID (37 bytes),
-    /// otherwise the remaining bytes will be written to unallocated memory and can cause UB.
+    /// - `path` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `id_buf` must be at least 37-bytes long or unallocated memory will be written to.
     pub fn hapi_fs_file_get(path: *const u8, id_buf: *mut u8) -> i32;
+    /// Same as `hapi_fs_file_get`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist or if the path is incorrect
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    /// - `id_buf` must be at least 37-bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_file_get_v2(path: *const u8, path_len: u32, id_buf: *mut u8) -> i32;
     /// Write a set amount of bytes to a file
     /// ### Returns
     /// - `0` On success
-    /// - `-1` if the file does not exist or if the path is incorrect.
+    /// - `-1` If the file does not exist
     /// - `-2` If the file label does not correspond to an active fs
     /// - `-3` If there is not enough space
     /// ### Panics
     /// Panics if the filesystem is poisoned.
     /// ### Safety
-    /// If the size of the buffer is smaller than the reported, unallocated memory will be read from and can cause UB.
+    /// - `file_id` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be read from.
     pub fn hapi_fs_file_write(
         fs_label: u8,
         file_id: *const u8,
@@ -244,12 +393,13 @@ extern "C" {
     /// Read a set amount of bytes from the file and write it to a buffer
     /// ### Returns
     /// - `0` On success
-    /// - `-1` if the file does not exist or if the path is incorrect.
+    /// - `-1` If the file does not exist
     /// - `-2` If the file label does not correspond to an active fs
     /// ### Panics
     /// Panics if the filesystem is poisoned.
     /// ### Safety
-    /// If the size of the buffer is smaller than the reported, unallocated memory will be written to and can cause UB.
+    /// - `file_id` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be written to.
     pub fn hapi_fs_file_read(
         fs_label: u8,
         file_id: *const u8,
@@ -259,32 +409,350 @@ extern "C" {
     ) -> i32;
     /// Return a file's length
     /// ### Returns
-    /// - `0` On success
-    /// - `-1` if the file does not exist or if the path is incorrect.
+    /// - The length of the file in bytes
+    /// - `-1` If the file does not exist
     /// - `-2` If the fs label does not correspond to an active fs
     /// ### Panics
     /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `file_id` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_fs_file_size(fs_label: u8, file_id: *const u8) -> i32;
     /// Create a directory at the path.
     /// ### Returns
     /// - `0` On success
-    /// - `-1` If the directory doesn't exist
+    /// - `-1` If the parent directory doesn't exist
     /// - `-2` If a directory with the name already exists
     /// - `-3` If the path string is invalid
     /// ### Panics
     /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be a valid null-terminated string or unallocated memory will be read from.
     pub fn hapi_fs_directory_create(path: *const u8) -> i32;
+    /// Same as `hapi_fs_directory_create`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the parent directory doesn't exist
+    /// - `-2` If a directory with the name already exists
+    /// - `-3` If the path string is invalid
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_directory_create_v2(path: *const u8, path_len: u32) -> i32;
     /// Find a directory at disk and return it's id
     /// ### Returns
     /// - `0` On success
-    /// - `-1` if the directory does not exist or if the path is incorrect.
+    /// - `-1` If the directory does not exist or if the path is incorrect
     /// - `-2` If the fs label does not correspond to an active fs
     /// ### Panics
     /// Panics if the filesystem is poisoned.
     /// ### Safety
-    /// The destination buffer must be the size of a UUID (37 bytes),
-    /// otherwise the remaining bytes will be written to unallocated memory and can cause UB.
+    /// - `path` must be a valid null-terminated string or unallocated memory will be read from.
+    /// - `id_buf` must be at least 37-bytes long or unallocated memory will be written to.
     pub fn hapi_fs_directory_get(path: *const u8, id_buf: *mut u8) -> i32;
+    /// Same as `hapi_fs_directory_get`, with strings passed as a pointer and a length instead of null-terminated.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the directory does not exist or if the path is incorrect
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    /// - `id_buf` must be at least 37-bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_directory_get_v2(path: *const u8, path_len: u32, id_buf: *mut u8) -> i32;
+    /// List the entries of a directory into a buffer, if the listing fits in it.
+    /// Each entry is encoded as its kind as a u8 (`0` for a file, `1` for a directory), its size as a little endian u32 (`0` for directories),
+    /// its 36 byte id, the length of its name as a little endian u32, and its name as utf-8.
+    /// Entries are sorted by name.
+    /// ### Returns
+    /// - The length of the listing in bytes. Nothing is written if it is longer than `size`
+    /// - `-1` If the directory does not exist
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `dir_id` must be at least `dir_id_len` bytes long or unallocated memory will be read from.
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_directory_list(
+        fs_label: u8,
+        dir_id: *const u8,
+        dir_id_len: u32,
+        size: u32,
+        buffer: *mut u8,
+    ) -> i32;
+    /// Remove the file at a path. Open handles to it stop working.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If there is no file at the path
+    /// - `-2` If the path string is invalid
+    /// - `-3` If the path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_remove(path: *const u8, path_len: u32) -> i32;
+    /// Remove the directory at a path.
+    /// Unless `recursive` is `1` the directory must be empty.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If there is no directory at the path
+    /// - `-2` If the directory is not empty and `recursive` is not `1`
+    /// - `-3` If the path string is invalid or is the root of a fs
+    /// - `-4` If the path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_directory_remove(path: *const u8, path_len: u32, recursive: u8) -> i32;
+    /// Move a file or directory to a new path on the same fs, keeping its id.
+    /// A file at the new path is replaced.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If there is nothing at the old path
+    /// - `-2` If the parent directory of the new path doesn't exist
+    /// - `-3` If a directory exists at the new path, or a file exists when moving a directory
+    /// - `-4` If the paths are on different file systems
+    /// - `-5` If a path string is invalid, is the root of a fs, or a directory would be moved into itself
+    /// - `-6` If a path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `from` must be at least `from_len` bytes long or unallocated memory will be read from.
+    /// - `to` must be at least `to_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_rename(from: *const u8, from_len: u32, to: *const u8, to_len: u32) -> i32;
+    /// Copy the contents of a file to a new path, which may be on another fs.
+    /// A file at the new path is overwritten, keeping its id.
+    /// ### Returns
+    /// - The amount of bytes copied
+    /// - `-1` If there is no file at the old path
+    /// - `-2` If the parent directory of the new path doesn't exist
+    /// - `-3` If a directory exists at the new path
+    /// - `-4` If a path string is invalid
+    /// - `-5` If a path is not on an active fs
+    /// - `-6` If there is not enough space on the fs of the new path
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `from` must be at least `from_len` bytes long or unallocated memory will be read from.
+    /// - `to` must be at least `to_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_copy(from: *const u8, from_len: u32, to: *const u8, to_len: u32) -> i32;
+    /// Get the metadata of the file or directory at a path, if it fits in the buffer.
+    /// The metadata is encoded as the kind as a u8 (`0` for a file, `1` for a directory), `1` if it is read-only as a u8,
+    /// the length as a little endian u32 (`0` for directories),
+    /// and the created, modified and accessed times as little endian f64 seconds since the start of the unix epoch.
+    /// Later versions may add fields to the end.
+    /// ### Returns
+    /// - The length of the metadata in bytes. Nothing is written if it is longer than `size`
+    /// - `-1` If there is nothing at the path
+    /// - `-2` If the path string is invalid
+    /// - `-3` If the path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_metadata(path: *const u8, path_len: u32, size: u32, buffer: *mut u8) -> i32;
+    /// Set the modified time of the file or directory at a path, in seconds since the start of the unix epoch.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If there is nothing at the path
+    /// - `-2` If the path string is invalid
+    /// - `-3` If the path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_set_modified(path: *const u8, path_len: u32, modified: f64) -> i32;
+    /// Open the file at a path and write it's id to the buffer, in a single step.
+    /// `flags` is a bit set of `1` to create the file if it doesn't exist, `2` to fail if it exists, and `4` to truncate it to 0 bytes.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist and is not created
+    /// - `-2` If the file exists and `2` is set, or a directory exists at the path
+    /// - `-3` If the parent directory doesn't exist
+    /// - `-4` If the path string is invalid
+    /// - `-5` If the path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    /// - `id_buf` must be at least 37-bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_file_open(path: *const u8, path_len: u32, flags: u32, id_buf: *mut u8) -> i32;
+    /// Truncate or extend a file to a length. New bytes are zero.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// - `-3` If there is not enough space to extend the file
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `file_id` must be at least `file_id_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_set_len(
+        fs_label: u8,
+        file_id: *const u8,
+        file_id_len: u32,
+        len: u32,
+    ) -> i32;
+    /// Write bytes to the end of a file. The length is read and the bytes written in one step,
+    /// so appends from several processes never overwrite each other.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// - `-3` If there is not enough space
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `file_id` must be at least `file_id_len` bytes long or unallocated memory will be read from.
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_append(
+        fs_label: u8,
+        file_id: *const u8,
+        file_id_len: u32,
+        size: u32,
+        buffer: *const u8,
+    ) -> i32;
+    /// Register a ram filesystem with the provided label, that can hold at most `capacity` bytes of file contents.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the label char is invalid
+    /// - `-2` If the label is already occupied
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    pub fn hapi_fs_init_ramfs_with_capacity(label: u8, capacity: u32) -> i32;
+    /// Unmount the filesystem with the provided label. Open files and directories on it stop working.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If no filesystem is mounted at the label
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    pub fn hapi_fs_unmount(label: u8) -> i32;
+    /// List the mounted filesystems into a buffer, if the list fits in it.
+    /// Each filesystem is encoded as its label as a u8, its type as a u8 (`0` for a ram filesystem, `1` for a persistent filesystem, `2` for an overlay filesystem),
+    /// the bytes used by file contents as a little endian u32, and its capacity as a little endian u32 (`0xffffffff` if it has no limit).
+    /// Filesystems are sorted by label.
+    /// ### Returns
+    /// - The length of the list in bytes. Nothing is written if it is longer than `size`
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_mounts(size: u32, buffer: *mut u8) -> i32;
+    /// Register a persistent filesystem with the provided label.
+    /// Its contents are kept in storage by the host, and survive reloads and restarts.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the label char is invalid
+    /// - `-2` If the label is already occupied
+    /// - `-3` If the host has no storage for persistent filesystems
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    pub fn hapi_fs_init_persistent(label: u8) -> i32;
+    /// Register an overlay filesystem with the provided label, showing the `lower` filesystem under a writable ram layer.
+    /// Changed files are copied up to the ram layer and deletions are hidden there, so the lower filesystem is never written to.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If either label char is invalid
+    /// - `-2` If the label is already occupied
+    /// - `-3` If there is no filesystem mounted at the lower label
+    /// - `-4` If the lower filesystem is itself an overlay
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    pub fn hapi_fs_init_overlay(label: u8, lower: u8) -> i32;
+    /// Start watching a file or directory for changes made by any process, and write the watcher's id to the buffer.
+    /// A directory watcher sees changes to the directory and its entries, or everything under it if `recursive` is `1`.
+    /// Changes are queued until they are read with `hapi_fs_watch_poll`.
+    /// The watcher is stopped when the process that started it dies, or the file system is unmounted.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If nothing exists at the path
+    /// - `-2` If the path string is invalid
+    /// - `-3` If the path is not on an active fs
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `path` must be at least `path_len` bytes long or unallocated memory will be read from.
+    /// - `id_buf` must be at least 37-bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_watch(path: *const u8, path_len: u32, recursive: u8, id_buf: *mut u8) -> i32;
+    /// Move the changes queued for a watcher into a buffer, if they all fit in it.
+    /// Each change is encoded as its kind as a u8 (`0` for a create, `1` for a modify, `2` for a remove and `3` for a rename),
+    /// the length of its path as a little endian u32, its path as utf-8, the length of the new path as a little endian u32 (`0` unless it is a rename),
+    /// and the new path as utf-8. Paths are absolute, and changes are in the order they were made.
+    /// At most 1024 changes are queued for a watcher. Once it is full later changes are dropped,
+    /// and a single overflow change (`4`) with the watched path is queued until it is polled, so the app knows to look at the path again.
+    /// ### Returns
+    /// - The length of the queued changes in bytes. Nothing is written or dequeued if it is longer than `size`
+    /// - `-1` If the watcher does not exist
+    /// ### Safety
+    /// - `watcher_id` must be at least `watcher_id_len` bytes long or unallocated memory will be read from.
+    /// - `buffer` must be at least `size` bytes long or unallocated memory will be written to.
+    pub fn hapi_fs_watch_poll(
+        watcher_id: *const u8,
+        watcher_id_len: u32,
+        size: u32,
+        buffer: *mut u8,
+    ) -> i32;
+    /// Block until changes are queued for a watcher, or `timeout_ms` milliseconds have passed.
+    /// Returns at once if changes are already queued, and doesn't dequeue them.
+    /// ### Returns
+    /// - `0` If changes are queued
+    /// - `-1` If the watcher does not exist
+    /// - `-2` If nothing was queued before the timeout
+    /// ### Safety
+    /// - `watcher_id` must be at least `watcher_id_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_watch_wait(watcher_id: *const u8, watcher_id_len: u32, timeout_ms: u32) -> i32;
+    /// Stop a watcher and drop the changes queued for it.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the watcher does not exist
+    /// ### Safety
+    /// - `watcher_id` must be at least `watcher_id_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_unwatch(watcher_id: *const u8, watcher_id_len: u32) -> i32;
+    /// Take an advisory lock on a file for the process, without waiting. `exclusive` is `1` for an exclusive lock and `0` for a shared one.
+    /// Any number of processes can hold a shared lock on a file, but only one an exclusive lock, and not while others hold a shared one.
+    /// A lock the process already holds on the file is replaced. Locks are released when the process dies or the file is removed, and don't stop reads or writes.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// - `-3` If another process holds a lock on the file that conflicts with it
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `file_id` must be at least `file_id_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_lock(
+        fs_label: u8,
+        file_id: *const u8,
+        file_id_len: u32,
+        exclusive: u8,
+    ) -> i32;
+    /// Take an advisory lock on a file like `hapi_fs_file_lock`, blocking while another process holds a conflicting lock
+    /// until it is released or `timeout_ms` milliseconds have passed.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// - `-3` If a conflicting lock was still held when the timeout passed
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `file_id` must be at least `file_id_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_lock_wait(
+        fs_label: u8,
+        file_id: *const u8,
+        file_id_len: u32,
+        exclusive: u8,
+        timeout_ms: u32,
+    ) -> i32;
+    /// Release the lock the process holds on a file.
+    /// ### Returns
+    /// - `0` On success
+    /// - `-1` If the file does not exist
+    /// - `-2` If the fs label does not correspond to an active fs
+    /// - `-3` If the process holds no lock on the file
+    /// ### Panics
+    /// Panics if the filesystem is poisoned.
+    /// ### Safety
+    /// - `file_id` must be at least `file_id_len` bytes long or unallocated memory will be read from.
+    pub fn hapi_fs_file_unlock(fs_label: u8, file_id: *const u8, file_id_len: u32) -> i32;
     /// Spawn a function pointer on a new thread
     pub fn hapi_thread_spawn(f_ptr: *const c_void);
 }
@@ -0,0 +1,223 @@
+//! Decompressing deflate streams, as described in rfc 1951
+
+use crate::fs::error::Error;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order the code lengths of the code length alphabet are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompress a raw deflate stream, failing as soon as it grows past `limit` bytes
+pub(super) fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, Error> {
+    let mut bits = Bits {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::new();
+
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                // Stored blocks start on a byte boundary
+                bits.buffer = 0;
+                bits.count = 0;
+                let len = bits.read(16)? as usize;
+                let inverse = bits.read(16)? as usize;
+                if len != !inverse & 0xffff {
+                    return Err(invalid());
+                }
+                if out.len() + len > limit {
+                    return Err(too_large());
+                }
+                let stored = data
+                    .get(bits.position..bits.position.saturating_add(len))
+                    .ok_or_else(invalid)?;
+                out.extend_from_slice(stored);
+                bits.position += len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut bits, &mut out, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic(&mut bits)?;
+                inflate_block(&mut bits, &mut out, limit, &literals, &distances)?;
+            }
+            _ => return Err(invalid()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// Read the codes of a block compressed with dynamic huffman codes
+fn read_dynamic(bits: &mut Bits) -> Result<(Huffman, Huffman), Error> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for i in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*i] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or_else(invalid)?, 3 + bits.read(2)?),
+            17 => (0, 3 + bits.read(3)?),
+            18 => (0, 11 + bits.read(7)?),
+            _ => return Err(invalid()),
+        };
+        lengths.extend(std::iter::repeat(length).take(repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count || lengths[256] == 0 {
+        return Err(invalid());
+    }
+
+    let (literals, distances) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals), Huffman::new(distances)))
+}
+
+/// Decode the symbols of a block until its end
+fn inflate_block(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), Error> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 if out.len() >= limit => return Err(too_large()),
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                let len = *LENGTH_BASE.get(index).ok_or_else(invalid)? as usize
+                    + bits.read(LENGTH_EXTRA[index])? as usize;
+                let index = distances.decode(bits)? as usize;
+                let distance = *DISTANCE_BASE.get(index).ok_or_else(invalid)? as usize
+                    + bits.read(DISTANCE_EXTRA[index])? as usize;
+                if distance > out.len() {
+                    return Err(invalid());
+                }
+                if out.len() + len > limit {
+                    return Err(too_large());
+                }
+                // The copy may overlap what it is writing, so it goes byte by byte
+                let start = out.len() - distance;
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+/// Reads a stream least significant bit first
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u8,
+}
+
+impl Bits<'_> {
+    /// Read up to 16 bits
+    fn read(&mut self, count: u8) -> Result<u32, Error> {
+        while self.count < count {
+            let byte = *self.data.get(self.position).ok_or_else(invalid)?;
+            self.buffer |= (byte as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+}
+
+/// A canonical huffman code, stored as the count of codes of each length and the symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the code from the code length of each symbol, where 0 means the symbol is unused
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    /// Read one symbol, a bit at a time
+    fn decode(&self, bits: &mut Bits) -> Result<u16, Error> {
+        // The first code of the current length, and the index of its symbol
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for count in &self.counts[1..] {
+            code |= bits.read(1)? as i32;
+            let count = *count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid())
+    }
+}
+
+fn invalid() -> Error {
+    Error::InvalidArchive("zip: bad deflate data".to_owned())
+}
+
+fn too_large() -> Error {
+    Error::InvalidArchive("zip: deflate data is larger than its entry".to_owned())
+}
@@ -0,0 +1,173 @@
+//! Packing directory trees into tar and zip archives, and unpacking them onto a file system.
+//! ```
+//! # use hapi::fs::{self, fslabel::FsLabel, File, RamFileSystem};
+//! # use hapi::network::{Request, RequestMethod};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # RamFileSystem::init(FsLabel::A)?;
+//! # fs::dir::Directory::create("a:/notes")?;
+//! # File::create("a:/notes/readme")?;
+//! # let tar = fs::archive::pack("a:/notes")?;
+//! # hapi::sim::with_kernel(|kernel, _| kernel.network.respond("/apps/notes.tar", tar));
+//! let request = Request::new("/apps/notes.tar", RequestMethod::Get, "{}")?;
+//! request.wait()?;
+//! fs::archive::unpack(&request.data()?, "a:/apps/notes")?;
+//! # File::open("a:/apps/notes/readme")?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod inflate;
+mod tar;
+mod zip;
+
+use std::time::SystemTime;
+
+use super::{
+    dir::{Directory, EntryKind},
+    error::Error,
+    path::HoneyPath,
+    OpenOptions,
+};
+
+/// The archive formats that can be packed and unpacked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A ustar archive, reading gnu long names and pax paths
+    Tar,
+    /// A zip archive. Entries are written uncompressed, and read uncompressed or deflated.
+    Zip,
+}
+
+impl Format {
+    /// Guess the format of an archive from its first bytes
+    pub fn detect(archive: &[u8]) -> Option<Self> {
+        if archive.starts_with(b"PK\x03\x04") || archive.starts_with(b"PK\x05\x06") {
+            return Some(Self::Zip);
+        }
+        let ustar = archive.get(257..262) == Some(b"ustar");
+        // An empty tar archive is only its end blocks
+        let empty = archive.len() >= 1024 && archive[..1024].iter().all(|byte| *byte == 0);
+        (ustar || empty).then_some(Self::Tar)
+    }
+}
+
+/// A file or directory in an archive
+#[derive(Debug, Clone)]
+struct Entry {
+    /// The path relative to the root of the archive, without a trailing slash
+    path: String,
+    kind: EntryKind,
+    data: Vec<u8>,
+    modified: Option<SystemTime>,
+}
+
+/// Unpack a tar or zip archive into a directory, creating it and any missing parents.
+/// Files that already exist at the path of an entry are overwritten.
+/// ### Errors
+/// - `Error::InvalidArchive` When the archive is not a tar or zip archive, or is corrupt
+/// - `Error::InvalidPath` When an entry would be unpacked outside of the directory.
+/// Every path is checked before anything is written.
+pub fn unpack(archive: &[u8], dest: impl AsRef<HoneyPath>) -> Result<(), Error> {
+    let (_, dest) = super::resolve(dest.as_ref())?;
+    let entries = entries(archive)?;
+
+    create_dir_all(&dest)?;
+    for entry in entries {
+        let path = dest.join(&entry.path).normalize();
+        match entry.kind {
+            EntryKind::Directory => create_dir_all(&path)?,
+            EntryKind::File => {
+                if let Some(parent) = path.parent() {
+                    create_dir_all(parent)?;
+                }
+                OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(&path)?
+                    .write(0, &entry.data)?;
+            }
+        }
+        if let Some(modified) = entry.modified {
+            super::set_modified(&path, modified)?;
+        }
+    }
+    Ok(())
+}
+
+/// Pack a directory and everything inside it into a tar archive.
+/// Paths in the archive are relative to the directory.
+pub fn pack(dir: impl AsRef<HoneyPath>) -> Result<Vec<u8>, Error> {
+    pack_as(dir, Format::Tar)
+}
+
+/// Pack a directory and everything inside it into an archive of the format.
+/// Paths in the archive are relative to the directory.
+/// ### Errors
+/// - `Error::InvalidArchive` When the directory holds too much for the format
+pub fn pack_as(dir: impl AsRef<HoneyPath>, format: Format) -> Result<Vec<u8>, Error> {
+    let (_, dir) = super::resolve(dir.as_ref())?;
+
+    let mut entries = Vec::new();
+    for walked in Directory::open(&dir)?.walk() {
+        let walked = walked?;
+        let data = match walked.entry().file() {
+            Some(file) => file.read_all()?,
+            None => Vec::new(),
+        };
+        entries.push(Entry {
+            path: walked.path().to_string(),
+            kind: walked.entry().kind(),
+            data,
+            modified: Some(super::metadata(dir.join(walked.path()))?.modified()),
+        });
+    }
+
+    match format {
+        Format::Tar => Ok(tar::write(&entries)),
+        Format::Zip => zip::write(&entries),
+    }
+}
+
+/// Check that an archive can be unpacked, without writing anything
+pub(super) fn check(archive: &[u8]) -> Result<(), Error> {
+    entries(archive).map(|_| ())
+}
+
+/// Parse the entries of a tar or zip archive, refusing paths that would leave the directory it is unpacked into
+fn entries(archive: &[u8]) -> Result<Vec<Entry>, Error> {
+    let entries = match Format::detect(archive) {
+        Some(Format::Tar) => tar::parse(archive)?,
+        Some(Format::Zip) => zip::parse(archive)?,
+        None => return Err(Error::InvalidArchive("unknown format".to_owned())),
+    };
+
+    let mut checked = Vec::new();
+    for entry in entries {
+        let path = HoneyPath::new(&entry.path);
+        if path.is_absolute() || path.components().any(|component| component == "..") {
+            return Err(Error::InvalidPath(entry.path));
+        }
+        // Entries like `./` name the root of the archive
+        if path.components().next().is_some() {
+            checked.push(entry);
+        }
+    }
+    Ok(checked)
+}
+
+/// Create a directory and any missing parents
+fn create_dir_all(path: &HoneyPath) -> Result<(), Error> {
+    match Directory::open(path) {
+        Ok(_) => return Ok(()),
+        Err(Error::NoSuchFs(label)) => return Err(Error::NoSuchFs(label)),
+        Err(_) => {}
+    }
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    match Directory::create(path) {
+        Ok(_) | Err(Error::DirExists(_)) => Ok(()),
+        Err(error) => Err(error),
+    }
+}
@@ -0,0 +1,247 @@
+//! Reading and writing ustar archives
+
+use std::time::{Duration, SystemTime};
+
+use super::Entry;
+use crate::fs::{dir::EntryKind, error::Error};
+
+const BLOCK: usize = 512;
+
+/// The name of the header gnu tar writes before an entry with a long name
+const LONG_LINK: &str = "././@LongLink";
+
+/// Parse the entries of a tar archive, skipping links and other special files
+pub(super) fn parse(mut archive: &[u8]) -> Result<Vec<Entry>, Error> {
+    let mut entries = Vec::new();
+    // Set by a gnu long name or pax header, for the entry after it
+    let mut next_path = None;
+    let mut next_modified = None;
+
+    while archive.len() >= BLOCK {
+        let (header, rest) = archive.split_at(BLOCK);
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+        if checksum(header) != octal(&header[148..156])? {
+            return Err(invalid("bad header checksum"));
+        }
+
+        let size = octal(&header[124..136])? as usize;
+        let padded = size.div_ceil(BLOCK) * BLOCK;
+        if rest.len() < size {
+            return Err(invalid("unexpected end of archive"));
+        }
+        let data = &rest[..size];
+        archive = &rest[padded.min(rest.len())..];
+
+        let kind = match header[156] {
+            b'0' | b'\0' | b'7' => EntryKind::File,
+            b'5' => EntryKind::Directory,
+            b'L' => {
+                next_path = Some(string(data));
+                continue;
+            }
+            b'x' => {
+                let (path, modified) = parse_pax(data)?;
+                next_path = path.or(next_path);
+                next_modified = modified.or(next_modified);
+                continue;
+            }
+            _ => {
+                next_path = None;
+                next_modified = None;
+                continue;
+            }
+        };
+
+        let path = next_path.take().unwrap_or_else(|| {
+            let (name, prefix) = (string(&header[..100]), string(&header[345..500]));
+            match prefix.is_empty() || &header[257..262] != b"ustar" {
+                true => name,
+                false => format!("{}/{}", prefix, name),
+            }
+        });
+        let modified = next_modified.take().or_else(|| {
+            let seconds = octal(&header[136..148]).ok()?;
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+        });
+        entries.push(Entry {
+            path: path.trim_end_matches('/').to_owned(),
+            kind,
+            data: match kind {
+                EntryKind::File => data.to_vec(),
+                EntryKind::Directory => Vec::new(),
+            },
+            modified,
+        });
+    }
+    Ok(entries)
+}
+
+/// Write the entries to a tar archive
+pub(super) fn write(entries: &[Entry]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    for entry in entries {
+        let name = match entry.kind {
+            EntryKind::File => entry.path.clone(),
+            EntryKind::Directory => format!("{}/", entry.path),
+        };
+        let (kind, mode) = match entry.kind {
+            EntryKind::File => (b'0', 0o644),
+            EntryKind::Directory => (b'5', 0o755),
+        };
+        let modified = entry
+            .modified
+            .map_or(0.0, crate::time::to_unix_epoch)
+            .max(0.0) as u64;
+
+        let (prefix, short_name) = match split_name(&name) {
+            Some(split) => split,
+            None => {
+                // Store the whole name in a gnu long name entry before the header
+                let mut long_name = name.as_bytes().to_vec();
+                long_name.push(0);
+                write_header(&mut archive, "", LONG_LINK, b'L', 0o644, long_name.len(), 0);
+                write_data(&mut archive, &long_name);
+                ("", truncate(&name, 100))
+            }
+        };
+        write_header(
+            &mut archive,
+            prefix,
+            short_name,
+            kind,
+            mode,
+            entry.data.len(),
+            modified,
+        );
+        write_data(&mut archive, &entry.data);
+    }
+    archive.resize(archive.len() + BLOCK * 2, 0);
+    archive
+}
+
+fn write_header(
+    archive: &mut Vec<u8>,
+    prefix: &str,
+    name: &str,
+    kind: u8,
+    mode: u32,
+    size: usize,
+    modified: u64,
+) {
+    let mut header = [0u8; BLOCK];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], mode as u64);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size as u64);
+    write_octal(&mut header[136..148], modified);
+    header[156] = kind;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    let checksum = format!("{:06o}\0 ", checksum(&header));
+    header[148..156].copy_from_slice(checksum.as_bytes());
+    archive.extend_from_slice(&header);
+}
+
+/// Write data padded to a whole block
+fn write_data(archive: &mut Vec<u8>, data: &[u8]) {
+    archive.extend_from_slice(data);
+    archive.resize(archive.len().div_ceil(BLOCK) * BLOCK, 0);
+}
+
+/// Split a name into a ustar prefix and name, None if it doesn't fit in them
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    if name.len() <= 100 {
+        return Some(("", name));
+    }
+    // The slash between them is not stored
+    name.char_indices()
+        .filter(|(_, c)| *c == '/')
+        .map(|(slash, _)| (&name[..slash], &name[slash + 1..]))
+        .find(|(prefix, rest)| prefix.len() <= 155 && rest.len() <= 100 && !rest.is_empty())
+}
+
+/// Cut a string to at most `len` bytes on a char boundary
+fn truncate(string: &str, mut len: usize) -> &str {
+    len = len.min(string.len());
+    while !string.is_char_boundary(len) {
+        len -= 1;
+    }
+    &string[..len]
+}
+
+/// Parse the path and modified time of a pax extended header
+fn parse_pax(mut data: &[u8]) -> Result<(Option<String>, Option<SystemTime>), Error> {
+    let (mut path, mut modified) = (None, None);
+    // Each record is `<length> <key>=<value>\n`, where the length counts the whole record
+    while !data.is_empty() {
+        let space = data
+            .iter()
+            .position(|byte| *byte == b' ')
+            .ok_or_else(|| invalid("bad pax record"))?;
+        let len: usize = std::str::from_utf8(&data[..space])
+            .ok()
+            .and_then(|len| len.parse().ok())
+            .filter(|len| *len > space && *len <= data.len())
+            .ok_or_else(|| invalid("bad pax record"))?;
+        let record = String::from_utf8_lossy(&data[space + 1..len]);
+        data = &data[len..];
+
+        let Some((key, value)) = record.trim_end_matches('\n').split_once('=') else {
+            continue;
+        };
+        match key {
+            "path" => path = Some(value.to_owned()),
+            "mtime" => modified = value.parse().ok().and_then(crate::time::from_unix_epoch),
+            _ => {}
+        }
+    }
+    Ok((path, modified))
+}
+
+/// The sum of the header bytes, counting the checksum field as spaces
+fn checksum(header: &[u8]) -> u64 {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| match i {
+            148..=155 => b' ' as u64,
+            _ => *byte as u64,
+        })
+        .sum()
+}
+
+/// Parse a nul or space terminated octal field
+fn octal(field: &[u8]) -> Result<u64, Error> {
+    let digits = string(field);
+    let digits = digits.trim_matches(' ');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).map_err(|_| invalid("bad number in header"))
+}
+
+/// Write a nul terminated octal field, clamping the value to the largest that fits
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let value = value.min((1 << (3 * width)) - 1);
+    let digits = format!("{:0width$o}", value, width = width);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+}
+
+/// Read a nul terminated string
+fn string(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidArchive(format!("tar: {}", reason))
+}
@@ -0,0 +1,258 @@
+//! Reading and writing zip archives
+
+use super::{inflate, Entry};
+use crate::fs::{dir::EntryKind, error::Error};
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_DIRECTORY: u32 = 0x06054b50;
+/// The length of the end of central directory record without its comment
+const END_LENGTH: usize = 22;
+
+/// Set in the flags when names are utf-8
+const UTF8_FLAG: u16 = 1 << 11;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+/// The version of the zip spec needed to read the archive, 2.0
+const VERSION: u16 = 20;
+
+/// Parse the entries listed in the central directory of a zip archive
+pub(super) fn parse(archive: &[u8]) -> Result<Vec<Entry>, Error> {
+    let end = (0..=archive.len().saturating_sub(END_LENGTH))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|offset| read_u32(archive, *offset) == Some(END_OF_DIRECTORY))
+        .ok_or_else(|| invalid("missing end of central directory"))?;
+    let count = read_u16(archive, end + 10).ok_or_else(|| invalid("truncated"))?;
+    let offset = read_u32(archive, end + 16).ok_or_else(|| invalid("truncated"))?;
+    if count == u16::MAX || offset == u32::MAX {
+        return Err(invalid("zip64 archives are not supported"));
+    }
+
+    let mut entries = Vec::new();
+    let mut offset = offset as usize;
+    for _ in 0..count {
+        let header = offset;
+        if read_u32(archive, header) != Some(CENTRAL_HEADER) {
+            return Err(invalid("bad central directory"));
+        }
+        let field = |at: usize| read_u16(archive, header + at).ok_or_else(|| invalid("truncated"));
+        let long = |at: usize| read_u32(archive, header + at).ok_or_else(|| invalid("truncated"));
+        let (flags, method, time, date) = (field(8)?, field(10)?, field(12)?, field(14)?);
+        let (crc, compressed, len) = (long(16)?, long(20)? as usize, long(24)? as usize);
+        let (name_len, extra_len, comment_len) = (field(28)?, field(30)?, field(32)?);
+        let local = long(42)? as usize;
+        let name = archive
+            .get(header + 46..header + 46 + name_len as usize)
+            .ok_or_else(|| invalid("truncated"))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset = header
+            .checked_add(46 + name_len as usize + extra_len as usize + comment_len as usize)
+            .ok_or_else(|| invalid("truncated"))?;
+
+        if flags & 1 != 0 {
+            return Err(invalid(&format!("{} is encrypted", name)));
+        }
+        if name.ends_with('/') {
+            entries.push(Entry {
+                path: name.trim_end_matches('/').to_owned(),
+                kind: EntryKind::Directory,
+                data: Vec::new(),
+                modified: Some(from_dos(time, date)),
+            });
+            continue;
+        }
+
+        // The local header repeats the name, and may have a different extra field
+        if read_u32(archive, local) != Some(LOCAL_HEADER) {
+            return Err(invalid(&format!("bad local header for {}", name)));
+        }
+        let local_name_len = read_u16(archive, local + 26).ok_or_else(|| invalid("truncated"))?;
+        let local_extra_len = read_u16(archive, local + 28).ok_or_else(|| invalid("truncated"))?;
+        // The offsets come from the archive, so they may overflow on 32 bit targets
+        let raw = local
+            .checked_add(30 + local_name_len as usize + local_extra_len as usize)
+            .and_then(|start| archive.get(start..start.checked_add(compressed)?))
+            .ok_or_else(|| invalid("truncated"))?;
+
+        let data = match method {
+            STORED => raw.to_vec(),
+            DEFLATED => inflate::inflate(raw, len)?,
+            _ => {
+                return Err(invalid(&format!(
+                    "unsupported compression method {}",
+                    method
+                )))
+            }
+        };
+        if data.len() != len || crc32(&data) != crc {
+            return Err(invalid(&format!("{} is corrupt", name)));
+        }
+        entries.push(Entry {
+            path: name,
+            kind: EntryKind::File,
+            data,
+            modified: Some(from_dos(time, date)),
+        });
+    }
+    Ok(entries)
+}
+
+/// Write the entries to a zip archive without compressing them
+pub(super) fn write(entries: &[Entry]) -> Result<Vec<u8>, Error> {
+    if entries.len() >= u16::MAX as usize {
+        return Err(invalid("too many entries"));
+    }
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for entry in entries {
+        let name = match entry.kind {
+            EntryKind::File => entry.path.clone(),
+            EntryKind::Directory => format!("{}/", entry.path),
+        };
+        let (time, date) = to_dos(entry.modified.map_or(0.0, crate::time::to_unix_epoch));
+        let crc = crc32(&entry.data);
+        let len = u32::try_from(entry.data.len()).map_err(|_| invalid("file too large"))?;
+        let local = u32::try_from(archive.len()).map_err(|_| invalid("archive too large"))?;
+
+        // The fields shared by the local and central headers
+        let mut common = Vec::new();
+        for field in [VERSION, UTF8_FLAG, STORED, time, date] {
+            common.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [crc, len, len] {
+            common.extend_from_slice(&field.to_le_bytes());
+        }
+        let name_len =
+            u16::try_from(name.len()).map_err(|_| Error::InvalidPath(entry.path.clone()))?;
+        common.extend_from_slice(&name_len.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        archive.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        archive.extend_from_slice(&common);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(&entry.data);
+
+        // Directories are marked with the ms-dos directory attribute
+        let attributes: u32 = match entry.kind {
+            EntryKind::File => 0,
+            EntryKind::Directory => 0x10,
+        };
+        directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        directory.extend_from_slice(&VERSION.to_le_bytes());
+        directory.extend_from_slice(&common);
+        // The comment length, disk number and internal attributes
+        directory.extend_from_slice(&[0; 6]);
+        directory.extend_from_slice(&attributes.to_le_bytes());
+        directory.extend_from_slice(&local.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let offset = u32::try_from(archive.len()).map_err(|_| invalid("archive too large"))?;
+    let count = entries.len() as u16;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+    // This disk and the disk the central directory starts on
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&count.to_le_bytes());
+    archive.extend_from_slice(&count.to_le_bytes());
+    let directory_len = u32::try_from(directory.len()).map_err(|_| invalid("archive too large"))?;
+    archive.extend_from_slice(&directory_len.to_le_bytes());
+    archive.extend_from_slice(&offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    Ok(archive)
+}
+
+/// Convert seconds since the unix epoch to an ms-dos time and date, clamped to the years 1980 to 2107
+fn to_dos(seconds: f64) -> (u16, u16) {
+    let seconds = (seconds.max(0.0) as i64).clamp(315532800, 4354819199);
+    let (days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    let time = ((seconds / 3600) << 11) | ((seconds % 3600 / 60) << 5) | (seconds % 60 / 2);
+    let date = ((year - 1980) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+/// Convert an ms-dos time and date to seconds since the unix epoch
+fn from_dos(time: u16, date: u16) -> std::time::SystemTime {
+    let (year, month, day) = (
+        1980 + (date >> 9) as i64,
+        ((date >> 5) & 0xf).max(1) as i64,
+        (date & 0x1f).max(1) as i64,
+    );
+    let seconds =
+        (time >> 11) as i64 * 3600 + ((time >> 5) & 0x3f) as i64 * 60 + (time & 0x1f) as i64 * 2;
+    let seconds = days_from_civil(year, month, day) * 86400 + seconds;
+    // Dos dates are between 1980 and 2107, which every system time can hold
+    crate::time::from_unix_epoch(seconds as f64).unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+}
+
+/// The days since the unix epoch of a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The year, month and day of a number of days since the unix epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// The crc-32 checksum zip uses
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = match crc & 1 {
+                    1 => 0xedb88320 ^ (crc >> 1),
+                    _ => crc >> 1,
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !data.iter().fold(!0u32, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn read_u16(archive: &[u8], offset: usize) -> Option<u16> {
+    let bytes = archive.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(archive: &[u8], offset: usize) -> Option<u32> {
+    let bytes = archive.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidArchive(format!("zip: {}", reason))
+}
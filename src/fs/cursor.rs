@@ -0,0 +1,194 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+use super::{error::Error, File};
+
+/// The size of the read-ahead buffer used by [`BufRead`]
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// An open file with a cursor.
+/// Implements [`Read`], [`Write`], [`Seek`] and [`BufRead`] on top of the offset based [`File`] api.
+///
+/// Reads are buffered for [`BufRead`], but reads as large as the buffer skip it,
+/// so wrapping the cursor in a [`io::BufReader`] does not copy the data twice.
+/// Writes go straight to the file, wrap the cursor in a [`io::BufWriter`] to batch small writes.
+/// ```
+/// # use std::io::{BufRead, Seek, Write};
+/// # use hapi::fs::{fslabel::FsLabel, File, RamFileSystem};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # RamFileSystem::init(FsLabel::A)?;
+/// let mut cursor = File::create("a:/hello.txt")?.cursor();
+/// writeln!(cursor, "Hello")?;
+/// cursor.rewind()?;
+/// let mut line = String::new();
+/// cursor.read_line(&mut line)?;
+/// # assert_eq!(line, "Hello\n");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FileCursor {
+    file: File,
+    pos: u64,
+    buffer: Vec<u8>,
+    consumed: usize,
+}
+
+impl FileCursor {
+    /// Create a cursor at the start of the file
+    pub fn new(file: File) -> Self {
+        Self {
+            file,
+            pos: 0,
+            buffer: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Get the file
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Get the file back, dropping the cursor
+    pub fn into_file(self) -> File {
+        self.file
+    }
+
+    /// Get the position of the cursor
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Drop the data read ahead of the cursor
+    fn discard_buffer(&mut self) {
+        self.buffer.clear();
+        self.consumed = 0;
+    }
+
+    /// Read from the file at the cursor without moving it.
+    /// Stops at the end of the file.
+    fn read_at_cursor(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let offset = offset(self.pos)?;
+        let size = self.file.size().map_err(io_error)?;
+        let len = buf.len().min(size.saturating_sub(offset));
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let data = self.file.read(offset, len).map_err(io_error)?;
+        buf[..len].copy_from_slice(&data);
+        Ok(len)
+    }
+}
+
+/// Convert the cursor to an offset of the fs api, which is limited to 32 bits
+fn offset(pos: u64) -> io::Result<usize> {
+    u32::try_from(pos)
+        .map(|offset| offset as usize)
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "offset does not fit in 32 bits",
+            )
+        })
+}
+
+fn io_error(error: Error) -> io::Error {
+    crate::Error::from(error).into()
+}
+
+impl Read for FileCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Large reads go straight to the file
+        if self.consumed == self.buffer.len() && buf.len() >= BUFFER_SIZE {
+            self.discard_buffer();
+            let len = self.read_at_cursor(buf)?;
+            self.pos += len as u64;
+            return Ok(len);
+        }
+
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for FileCursor {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.consumed == self.buffer.len() {
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.resize(BUFFER_SIZE, 0);
+            let len = self.read_at_cursor(&mut buffer)?;
+            buffer.truncate(len);
+            self.buffer = buffer;
+            self.consumed = 0;
+        }
+        Ok(&self.buffer[self.consumed..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.buffer.len() - self.consumed);
+        self.consumed += amt;
+        self.pos += amt as u64;
+    }
+}
+
+impl Write for FileCursor {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let offset = offset(self.pos)?;
+        offset
+            .checked_add(buf.len())
+            .filter(|end| *end <= u32::MAX as usize)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "write past the 4GiB limit")
+            })?;
+
+        self.discard_buffer();
+        self.file.write(offset, buf).map_err(io_error)?;
+        self.pos = match self.file.is_append() {
+            true => self.file.size().map_err(io_error)? as u64,
+            false => self.pos + buf.len() as u64,
+        };
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for FileCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let size = self.file.size().map_err(io_error)? as u64;
+                size.checked_add_signed(delta)
+            }
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )
+        })?;
+
+        // Keep the buffer when seeking within it
+        let start = self.pos - self.consumed as u64;
+        match pos.checked_sub(start) {
+            Some(consumed) if consumed <= self.buffer.len() as u64 => {
+                self.consumed = consumed as usize;
+            }
+            _ => self.discard_buffer(),
+        }
+        self.pos = pos;
+        Ok(pos)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
@@ -1,71 +1,101 @@
-use std::{ffi::CString, path::PathBuf};
+use std::vec;
 
-use super::{error::Error, fslabel::FsLabel};
+use super::{
+    error::Error,
+    fslabel::FsLabel,
+    path::{HoneyPath, HoneyPathBuf},
+    File,
+};
+use crate::{
+    abi::{FsDirectoryCreateResult, FsDirectoryGetError, FsDirectoryListError},
+    handle::{Handle, ID_LENGTH},
+};
+
+/// The size of the first buffer a listing is read into
+const LISTING_SIZE: usize = 1024;
 
 /// Represetns a directory on a file system
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Directory {
     fs_label: FsLabel,
-    id: CString,
+    id: Handle<Directory>,
 }
 
 impl Directory {
-    /// Open a directory
-    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
-        let path: PathBuf = path.into();
-        let path = path.to_str().unwrap();
-
-        let path_cstr = CString::new(path).unwrap();
-        let mut id_buf = vec![0; 37];
-
-        let result = unsafe {
-            crate::ffi::hapi_fs_directory_get(
-                path_cstr.as_ptr() as *const u8,
-                &mut id_buf[0] as *mut u8,
-            )
-        };
-
-        let fs_label = FsLabel::extract_from_path(path)?;
+    /// Open a directory. Relative paths are joined onto the current working directory.
+    pub fn open(path: impl AsRef<HoneyPath>) -> Result<Self, Error> {
+        let (fs_label, path) = super::resolve(path.as_ref())?;
 
-        if result < 0 {
-            return Err(match result {
-                -1 => Error::NoSuchDirectory(path.to_owned()),
-                -2 => Error::NoSuchFs(fs_label),
-                _ => Error::NoSuchDirectory(path.to_owned()),
-            });
-        }
+        let id = crate::host::current()
+            .fs_directory_get(path.as_str())
+            .map_err(|code| match code {
+                FsDirectoryGetError::NoSuchFs => Error::NoSuchFs(fs_label),
+                _ => Error::NoSuchDirectory(path.to_string()),
+            })?;
 
-        let id_str = CString::from_vec_with_nul(id_buf).unwrap();
         Ok(Self {
             fs_label,
-            id: id_str,
+            id: Handle::parse(&id).map_err(|_| Error::InvalidId(id))?,
         })
     }
 
-    /// Create a directory
-    pub fn create(path: impl Into<PathBuf>) -> Result<Self, Error> {
-        let path: PathBuf = path.into();
-        let path = path.to_str().unwrap().to_string();
+    /// Create a directory. Relative paths are joined onto the current working directory.
+    pub fn create(path: impl AsRef<HoneyPath>) -> Result<Self, Error> {
+        let (fs_label, path) = super::resolve(path.as_ref())?;
+        super::check_writable(fs_label)?;
+
+        match crate::host::current().fs_directory_create(path.as_str()) {
+            FsDirectoryCreateResult::Ok => {}
+            FsDirectoryCreateResult::Exists => return Err(Error::DirExists(path.to_string())),
+            _ => return Err(Error::NoSuchDirectory(path.to_string())),
+        }
+
+        Self::open(path)
+    }
 
-        let path_cstr = CString::new(path.clone()).unwrap();
+    /// List the files and directories inside the directory, sorted by name
+    pub fn entries(&self) -> Result<Entries, Error> {
+        let mut buffer = vec![0u8; LISTING_SIZE];
+        loop {
+            let result = crate::host::current().fs_directory_list(
+                self.fs_label.into(),
+                self.id(),
+                &mut buffer,
+            );
+            let len = match result {
+                Ok(len) => len as usize,
+                Err(FsDirectoryListError::NoSuchFs) => return Err(Error::NoSuchFs(self.fs_label)),
+                Err(_) => return Err(Error::NoSuchDirectoryWithId(self.id.to_string())),
+            };
 
-        let result =
-            unsafe { crate::ffi::hapi_fs_directory_create(path_cstr.as_ptr() as *const u8) };
-        if result < 0 {
-            return Err(match result {
-                -1 => Error::NoSuchDirectory(path.to_owned()),
-                -2 => Error::DirExists(path.to_owned()),
-                -3 => Error::NoSuchDirectory(path.to_owned()),
-                _ => Error::NoSuchDirectory(path.to_owned()),
+            // The directory may have grown since the last call, so retry until it fits
+            if len > buffer.len() {
+                buffer.resize(len, 0);
+                continue;
+            }
+            buffer.truncate(len);
+            return Ok(Entries {
+                entries: parse_listing(self.fs_label, &buffer)?.into_iter(),
             });
         }
+    }
 
-        Self::open(path)
+    /// Walk the directory depth first, visiting every directory before its contents.
+    /// Directories that can't be listed are yielded as errors and skipped.
+    pub fn walk(&self) -> Walk {
+        Walk {
+            stack: vec![(String::new(), 0, self.entries())],
+        }
     }
 
     /// Get the id
     pub fn id(&self) -> &str {
-        self.id.to_str().unwrap()
+        self.id.as_str()
+    }
+
+    /// Get the handle
+    pub fn handle(&self) -> Handle<Directory> {
+        self.id
     }
 
     /// Get the fs label
@@ -73,3 +103,207 @@ impl Directory {
         self.fs_label
     }
 }
+
+/// Parse the listing written by `hapi_fs_directory_list`
+fn parse_listing(fs_label: FsLabel, mut listing: &[u8]) -> Result<Vec<DirEntry>, Error> {
+    fn take<'a>(listing: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+        if listing.len() < len {
+            return Err(Error::InvalidListing);
+        }
+        let (head, tail) = listing.split_at(len);
+        *listing = tail;
+        Ok(head)
+    }
+    fn take_u32(listing: &mut &[u8]) -> Result<u32, Error> {
+        let bytes = take(listing, 4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    let mut entries = Vec::new();
+    while !listing.is_empty() {
+        let kind = match take(&mut listing, 1)?[0] {
+            0 => EntryKind::File,
+            1 => EntryKind::Directory,
+            _ => return Err(Error::InvalidListing),
+        };
+        let size = take_u32(&mut listing)? as usize;
+        let id = std::str::from_utf8(take(&mut listing, ID_LENGTH)?)
+            .ok()
+            .and_then(|id| Handle::parse(id).ok())
+            .ok_or(Error::InvalidListing)?;
+        let name_len = take_u32(&mut listing)? as usize;
+        let name = std::str::from_utf8(take(&mut listing, name_len)?)
+            .map_err(|_| Error::InvalidListing)?;
+
+        entries.push(DirEntry {
+            name: name.to_owned(),
+            kind,
+            size,
+            fs_label,
+            id,
+        });
+    }
+    Ok(entries)
+}
+
+/// The kind of an entry in a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    File,
+    Directory,
+}
+
+/// A file or directory inside a directory
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DirEntry {
+    name: String,
+    kind: EntryKind,
+    size: usize,
+    fs_label: FsLabel,
+    id: Handle<DirEntry>,
+}
+
+impl DirEntry {
+    /// Get the name of the entry, without the path of the directory
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the kind of the entry
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    /// Check if the entry is a file
+    pub fn is_file(&self) -> bool {
+        self.kind == EntryKind::File
+    }
+
+    /// Check if the entry is a directory
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+
+    /// Get the size of the file when the directory was listed, or 0 for a directory.
+    /// A file on an http file system is fetched first if it has not been, so its size is right.
+    pub fn size(&self) -> usize {
+        match self.file() {
+            Some(file) if !super::is_fetched(self.fs_label, self.id()) => {
+                file.size().unwrap_or(self.size)
+            }
+            _ => self.size,
+        }
+    }
+
+    /// Get the id
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// Get the file, if the entry is a file
+    pub fn file(&self) -> Option<File> {
+        self.is_file()
+            .then(|| File::from_handle(self.fs_label, self.id.cast()))
+    }
+
+    /// Get the directory, if the entry is a directory
+    pub fn directory(&self) -> Option<Directory> {
+        self.is_dir().then(|| Directory {
+            fs_label: self.fs_label,
+            id: self.id.cast(),
+        })
+    }
+}
+
+/// An iterator over the entries of a directory, returned by [`Directory::entries`]
+#[derive(Debug)]
+pub struct Entries {
+    entries: vec::IntoIter<DirEntry>,
+}
+
+impl Iterator for Entries {
+    type Item = DirEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Entries {}
+
+/// An entry found by [`Directory::walk`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WalkEntry {
+    path: HoneyPathBuf,
+    depth: usize,
+    entry: DirEntry,
+}
+
+impl WalkEntry {
+    /// Get the path of the entry relative to the walked directory, joined with `/`
+    pub fn path(&self) -> &HoneyPath {
+        &self.path
+    }
+
+    /// Get how deep the entry is, starting at 0 for the contents of the walked directory
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the entry
+    pub fn entry(&self) -> &DirEntry {
+        &self.entry
+    }
+
+    /// Get the entry, dropping the path
+    pub fn into_entry(self) -> DirEntry {
+        self.entry
+    }
+}
+
+/// A depth first iterator over a directory tree, returned by [`Directory::walk`]
+#[derive(Debug)]
+pub struct Walk {
+    /// The path, depth and entries of each directory being walked
+    stack: Vec<(String, usize, Result<Entries, Error>)>,
+}
+
+impl Iterator for Walk {
+    type Item = Result<WalkEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (parent, depth, entries) = self.stack.last_mut()?;
+            let entries = match entries {
+                Ok(entries) => entries,
+                Err(_) => {
+                    let (_, _, error) = self.stack.pop()?;
+                    return error.err().map(Err);
+                }
+            };
+            let Some(entry) = entries.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let depth = *depth;
+            let path = match parent.is_empty() {
+                true => entry.name.clone(),
+                false => format!("{}/{}", parent, entry.name),
+            };
+            if let Some(directory) = entry.directory() {
+                self.stack
+                    .push((path.clone(), depth + 1, directory.entries()));
+            }
+            return Some(Ok(WalkEntry {
+                path: path.into(),
+                depth,
+                entry,
+            }));
+        }
+    }
+}
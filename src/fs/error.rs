@@ -9,10 +9,32 @@ pub enum Error {
     NoSuchFileWithId(String),
     FileExists(String),
     NoSuchDirectory(String),
+    NoSuchDirectoryWithId(String),
     DirExists(String),
+    DestinationExists(String),
+    DirNotEmpty(String),
+    CrossDevice(FsLabel, FsLabel),
+    InvalidPath(String),
     NoSuchFs(FsLabel),
     NotEnoughSpace(FsLabel),
     FsAlreadyExists(FsLabel),
+    StorageUnavailable(FsLabel),
+    NestedOverlay(FsLabel),
+    ReadOnly(FsLabel),
+    FetchFailed(String),
+    InvalidManifest(String),
+    InvalidId(String),
+    InvalidListing,
+    InvalidMetadata,
+    InvalidMountList,
+    InvalidArchive(String),
+    InvalidChangeList,
+    NoSuchWatcher(String),
+    Locked(String),
+    NotLocked(String),
+    InvalidOpenOptions(&'static str),
+    NotReadable(String),
+    NotWritable(String),
 }
 
 impl std::error::Error for Error {}
@@ -20,20 +42,52 @@ impl std::error::Error for Error {}
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::NoFsLabel(s) => writeln!(f, "The path: {} contains no fs label", s),
-            Self::NotAFsLabel(s) => writeln!(f, "{} is not a valid fs label", s),
-            Self::NoSuchFile(s) => writeln!(f, "No such file: {}", s),
-            Self::NoSuchFileWithId(s) => writeln!(f, "No such file with id: {}", s),
-            Self::FileExists(s) => writeln!(f, "File already exists at: {}", s),
-            Self::NoSuchDirectory(s) => writeln!(f, "No such directory: {}", s),
-            Self::NoSuchFs(l) => writeln!(f, "No fs mounted at: {}", l),
-            Self::NotEnoughSpace(l) => writeln!(f, "No space left on device: {}", l),
-            Self::DirExists(s) => writeln!(f, "Dir already exists at: {}", s),
-            Self::FsAlreadyExists(l) => writeln!(
+            Self::NoFsLabel(s) => write!(f, "The path: {} contains no fs label", s),
+            Self::NotAFsLabel(s) => write!(f, "{} is not a valid fs label", s),
+            Self::NoSuchFile(s) => write!(f, "No such file: {}", s),
+            Self::NoSuchFileWithId(s) => write!(f, "No such file with id: {}", s),
+            Self::FileExists(s) => write!(f, "File already exists at: {}", s),
+            Self::NoSuchDirectory(s) => write!(f, "No such directory: {}", s),
+            Self::NoSuchDirectoryWithId(s) => write!(f, "No such directory with id: {}", s),
+            Self::NoSuchFs(l) => write!(f, "No fs mounted at: {}", l),
+            Self::NotEnoughSpace(l) => write!(f, "No space left on device: {}", l),
+            Self::DirExists(s) => write!(f, "Dir already exists at: {}", s),
+            Self::DestinationExists(s) => write!(f, "Something already exists at: {}", s),
+            Self::DirNotEmpty(s) => write!(f, "Directory is not empty: {}", s),
+            Self::CrossDevice(from, to) => write!(
+                f,
+                "Can't move between different file systems: {} and {}",
+                from, to
+            ),
+            Self::InvalidPath(s) => write!(f, "Invalid path: {}", s),
+            Self::FsAlreadyExists(l) => write!(
                 f,
                 "Could not mount file sytem. A file system is already mounted at: {}",
                 l
             ),
+            Self::StorageUnavailable(l) => write!(
+                f,
+                "Could not mount persistent file system at: {}. The host has no storage for it",
+                l
+            ),
+            Self::NestedOverlay(l) => {
+                write!(f, "Can't mount an overlay of the overlay at: {}", l)
+            }
+            Self::ReadOnly(l) => write!(f, "The file system is read-only: {}", l),
+            Self::FetchFailed(s) => write!(f, "Failed to fetch: {}", s),
+            Self::InvalidManifest(s) => write!(f, "Invalid http file system manifest: {}", s),
+            Self::InvalidId(s) => write!(f, "The host returned an invalid id: {}", s),
+            Self::InvalidListing => write!(f, "The host returned an invalid directory listing"),
+            Self::InvalidMetadata => write!(f, "The host returned invalid metadata"),
+            Self::InvalidMountList => write!(f, "The host returned an invalid mount list"),
+            Self::InvalidArchive(s) => write!(f, "Invalid archive: {}", s),
+            Self::InvalidChangeList => write!(f, "The host returned an invalid change list"),
+            Self::NoSuchWatcher(s) => write!(f, "No such watcher with id: {}", s),
+            Self::Locked(s) => write!(f, "The file is locked by another process: {}", s),
+            Self::NotLocked(s) => write!(f, "The file is not locked by this process: {}", s),
+            Self::InvalidOpenOptions(s) => write!(f, "Invalid open options: {}", s),
+            Self::NotReadable(s) => write!(f, "The file was not opened for reading: {}", s),
+            Self::NotWritable(s) => write!(f, "The file was not opened for writing: {}", s),
         }
     }
 }
@@ -1,89 +1,113 @@
-use std::{ffi::CString, path::PathBuf};
+use std::hash::{Hash, Hasher};
 
-use super::{error::Error, fslabel::FsLabel};
+use super::{error::Error, fslabel::FsLabel, path::HoneyPath, FileCursor, OpenOptions};
+use crate::{
+    abi::{
+        FsFileAppendResult, FsFileCreateResult, FsFileLockResult, FsFileLockWaitResult,
+        FsFileReadResult, FsFileSetLenResult, FsFileSizeError, FsFileUnlockResult,
+        FsFileWriteResult,
+    },
+    handle::Handle,
+};
 
-/// Represents a file on file system
+/// How long the host blocks for a lock before [`File::lock_shared`] and [`File::lock_exclusive`] ask again
+const LOCK_WAIT_MS: u32 = 1000;
+
+/// Represents a file on file system.
+/// Two files are equal if they have the same id, whatever they were opened for.
 #[derive(Debug, Clone)]
 pub struct File {
     fs_label: FsLabel,
-    id: CString,
+    id: Handle<File>,
+    mode: Mode,
 }
 
-impl File {
-    /// Open a file
-    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
-        let path: PathBuf = path.into();
-        let mut path = path.to_str().unwrap().to_string();
-
-        let path_cstr = CString::new(path.clone()).unwrap();
-        let mut id_buf = vec![0; 37];
+/// What a file was opened for
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Mode {
+    pub read: bool,
+    /// Also set when the file was opened for appending, which implies writing
+    pub write: bool,
+    pub append: bool,
+}
 
-        let result = unsafe {
-            crate::ffi::hapi_fs_file_get(path_cstr.as_ptr() as *const u8, &mut id_buf[0] as *mut u8)
-        };
+impl Mode {
+    /// The mode of files opened without [`OpenOptions`]
+    const READ_WRITE: Self = Self {
+        read: true,
+        write: true,
+        append: false,
+    };
+}
 
-        if result < 0 {
-            return Err(Error::NoSuchFile(path.to_owned()));
-        }
+impl File {
+    /// Open a file. Relative paths are joined onto the current working directory.
+    pub fn open(path: impl AsRef<HoneyPath>) -> Result<Self, Error> {
+        let (fs_label, path) = super::resolve(path.as_ref())?;
 
-        // If the path does not contain a fs label, append the dir to the current working directory
-        let fs_label = match FsLabel::extract_from_path(&path) {
-            Ok(label) => label,
-            Err(_) => {
-                let cwd =
-                    crate::process::cwd().expect("Failed to aquire current working directory");
-                path = format!("{}/{}", cwd, path);
-                FsLabel::extract_from_path(&path)?
-            }
-        };
+        let id = crate::host::current()
+            .fs_file_get(path.as_str())
+            .map_err(|_| Error::NoSuchFile(path.to_string()))?;
 
-        let id_str = CString::from_vec_with_nul(id_buf).unwrap();
-        return Ok(Self {
-            id: id_str,
+        Ok(Self {
+            id: Handle::parse(&id).map_err(|_| Error::InvalidId(id))?,
             fs_label,
-        });
+            mode: Mode::READ_WRITE,
+        })
     }
 
-    /// Create a file
-    pub fn create(path: impl Into<PathBuf>) -> Result<Self, Error> {
-        let path: PathBuf = path.into();
-        let path = path.to_str().unwrap();
+    /// Create a file. Relative paths are joined onto the current working directory.
+    pub fn create(path: impl AsRef<HoneyPath>) -> Result<Self, Error> {
+        let (fs_label, path) = super::resolve(path.as_ref())?;
+        super::check_writable(fs_label)?;
 
-        let path_cstr = CString::new(path).unwrap();
-
-        let result = unsafe { crate::ffi::hapi_fs_file_create(path_cstr.as_ptr() as *const u8) };
-        if result < 0 {
-            return Err(match result {
-                -1 => Error::NoSuchDirectory(path.to_owned()),
-                -2 => Error::FileExists(path.to_owned()),
-                -3 => Error::NoSuchDirectory(path.to_owned()),
-                _ => Error::NoSuchDirectory(path.to_owned()),
-            });
+        match crate::host::current().fs_file_create(path.as_str()) {
+            FsFileCreateResult::Ok => {}
+            FsFileCreateResult::Exists => return Err(Error::FileExists(path.to_string())),
+            _ => return Err(Error::NoSuchDirectory(path.to_string())),
         }
 
         Self::open(path)
     }
 
+    /// Get an [`OpenOptions`] to open a file with
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    /// Create a file from a handle returned by the host
+    pub(super) fn from_handle(fs_label: FsLabel, id: Handle<File>) -> Self {
+        Self::with_mode(fs_label, id, Mode::READ_WRITE)
+    }
+
+    /// Create a file opened for the mode from a handle returned by the host
+    pub(super) fn with_mode(fs_label: FsLabel, id: Handle<File>, mode: Mode) -> Self {
+        Self { fs_label, id, mode }
+    }
+
+    /// Check if writes go to the end of the file
+    pub(super) fn is_append(&self) -> bool {
+        self.mode.append
+    }
+
     /// Read data from the file
     pub fn read(&self, offset: usize, size: usize) -> Result<Vec<u8>, Error> {
+        if !self.mode.read {
+            return Err(Error::NotReadable(self.id.to_string()));
+        }
+        super::fetch(self.fs_label, self.id())?;
         let mut buffer = vec![0u8; size];
-        let result = unsafe {
-            crate::ffi::hapi_fs_file_read(
-                self.fs_label.into(),
-                self.id.as_ptr() as *const u8,
-                offset as u32,
-                size as u32,
-                &mut buffer[0] as *mut u8,
-            )
-        };
-        if result < 0 {
-            return Err(match result {
-                -2 => Error::NoSuchFs(self.fs_label),
-                _ => Error::NoSuchFileWithId(self.id.to_string_lossy().into()),
-            });
+        let result = crate::host::current().fs_file_read(
+            self.fs_label.into(),
+            self.id(),
+            offset as u32,
+            &mut buffer,
+        );
+        match result {
+            FsFileReadResult::Ok => Ok(buffer),
+            FsFileReadResult::NoSuchFs => Err(Error::NoSuchFs(self.fs_label)),
+            _ => Err(Error::NoSuchFileWithId(self.id.to_string())),
         }
-
-        Ok(buffer)
     }
 
     /// Read all the data from the file
@@ -91,44 +115,150 @@ impl File {
         self.read(0, self.size()?)
     }
 
-    /// Write data to the file
+    /// Write data to the file.
+    /// If the file was opened for appending the data is written to the end, ignoring the offset.
+    /// The host finds the end and writes in one step, so appends from other processes are never overwritten.
     pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
-        let result = unsafe {
-            crate::ffi::hapi_fs_file_write(
-                self.fs_label.into(),
-                self.id.as_ptr() as *const u8,
-                offset as u32,
-                data.len() as u32,
-                &data[0] as *const u8,
-            )
-        };
+        if !self.mode.write {
+            return Err(Error::NotWritable(self.id.to_string()));
+        }
+        super::check_writable(self.fs_label)?;
+        if self.mode.append {
+            return self.append(data);
+        }
+
+        let result = crate::host::current().fs_file_write(
+            self.fs_label.into(),
+            self.id(),
+            offset as u32,
+            data,
+        );
 
         match result {
-            -1 => Err(Error::NoSuchFileWithId(self.id.to_string_lossy().into())),
-            -2 => Err(Error::NoSuchFs(self.fs_label)),
-            -3 => Err(Error::NotEnoughSpace(self.fs_label)),
-            _ => Ok(()),
+            FsFileWriteResult::Ok => Ok(()),
+            FsFileWriteResult::NoSuchFs => Err(Error::NoSuchFs(self.fs_label)),
+            FsFileWriteResult::NotEnoughSpace => Err(Error::NotEnoughSpace(self.fs_label)),
+            _ => Err(Error::NoSuchFileWithId(self.id.to_string())),
+        }
+    }
+
+    fn append(&self, data: &[u8]) -> Result<(), Error> {
+        match crate::host::current().fs_file_append(self.fs_label.into(), self.id(), data) {
+            FsFileAppendResult::Ok => Ok(()),
+            FsFileAppendResult::NoSuchFs => Err(Error::NoSuchFs(self.fs_label)),
+            FsFileAppendResult::NotEnoughSpace => Err(Error::NotEnoughSpace(self.fs_label)),
+            _ => Err(Error::NoSuchFileWithId(self.id.to_string())),
+        }
+    }
+
+    /// Open a cursor at the start of the file, to use it with [`std::io`]
+    pub fn cursor(&self) -> FileCursor {
+        FileCursor::new(self.clone())
+    }
+
+    /// Truncate or extend the file to a length, filling it with zeros
+    pub fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        if !self.mode.write {
+            return Err(Error::NotWritable(self.id.to_string()));
+        }
+        super::check_writable(self.fs_label)?;
+        let len = u32::try_from(len).map_err(|_| Error::NotEnoughSpace(self.fs_label))?;
+
+        match crate::host::current().fs_file_set_len(self.fs_label.into(), self.id(), len) {
+            FsFileSetLenResult::Ok => Ok(()),
+            FsFileSetLenResult::NoSuchFs => Err(Error::NoSuchFs(self.fs_label)),
+            FsFileSetLenResult::NotEnoughSpace => Err(Error::NotEnoughSpace(self.fs_label)),
+            _ => Err(Error::NoSuchFileWithId(self.id.to_string())),
         }
     }
 
     /// Get the file size
     pub fn size(&self) -> Result<usize, Error> {
-        let result = unsafe {
-            crate::ffi::hapi_fs_file_size(self.fs_label.into(), self.id.as_ptr() as *const u8)
-        };
-        if result < 0 {
-            return Err(match result {
-                -1 => Error::NoSuchFileWithId(self.id.to_string_lossy().into()),
-                -2 => Error::NoSuchFs(self.fs_label),
-                _ => Error::NoSuchFileWithId(self.id.to_string_lossy().into()),
-            });
+        super::fetch(self.fs_label, self.id())?;
+        match crate::host::current().fs_file_size(self.fs_label.into(), self.id()) {
+            Ok(size) => Ok(size as usize),
+            Err(FsFileSizeError::NoSuchFs) => Err(Error::NoSuchFs(self.fs_label)),
+            Err(_) => Err(Error::NoSuchFileWithId(self.id.to_string())),
+        }
+    }
+
+    /// Take a shared lock on the file, waiting while another process holds an exclusive one.
+    /// Locks are advisory and held by the process, so they only stop other processes from locking the file,
+    /// not from reading or writing it. They are released with [`File::unlock`], when the process dies,
+    /// or when the file is removed.
+    pub fn lock_shared(&self) -> Result<(), Error> {
+        self.lock_waiting(false)
+    }
+
+    /// Take an exclusive lock on the file, waiting while another process holds any lock on it.
+    /// A shared lock the process holds on the file is upgraded.
+    pub fn lock_exclusive(&self) -> Result<(), Error> {
+        self.lock_waiting(true)
+    }
+
+    /// Take an exclusive lock on the file without waiting
+    /// ### Errors
+    /// - `Error::Locked` When another process holds a lock on the file
+    pub fn try_lock(&self) -> Result<(), Error> {
+        self.lock(true)
+    }
+
+    /// Take a shared lock on the file without waiting
+    /// ### Errors
+    /// - `Error::Locked` When another process holds an exclusive lock on the file
+    pub fn try_lock_shared(&self) -> Result<(), Error> {
+        self.lock(false)
+    }
+
+    /// Release the lock the process holds on the file
+    /// ### Errors
+    /// - `Error::NotLocked` When the process holds no lock on the file
+    pub fn unlock(&self) -> Result<(), Error> {
+        match crate::host::current().fs_file_unlock(self.fs_label.into(), self.id()) {
+            FsFileUnlockResult::Ok => Ok(()),
+            FsFileUnlockResult::NoSuchFs => Err(Error::NoSuchFs(self.fs_label)),
+            FsFileUnlockResult::NotLocked => Err(Error::NotLocked(self.id.to_string())),
+            _ => Err(Error::NoSuchFileWithId(self.id.to_string())),
+        }
+    }
+
+    /// Block in the host until the lock is taken, asking again each time the wait times out
+    fn lock_waiting(&self, exclusive: bool) -> Result<(), Error> {
+        loop {
+            let result = crate::host::current().fs_file_lock_wait(
+                self.fs_label.into(),
+                self.id(),
+                exclusive as u8,
+                LOCK_WAIT_MS,
+            );
+            match result {
+                FsFileLockWaitResult::Ok => return Ok(()),
+                FsFileLockWaitResult::Locked => continue,
+                FsFileLockWaitResult::NoSuchFs => return Err(Error::NoSuchFs(self.fs_label)),
+                _ => return Err(Error::NoSuchFileWithId(self.id.to_string())),
+            }
+        }
+    }
+
+    fn lock(&self, exclusive: bool) -> Result<(), Error> {
+        let result =
+            crate::host::current().fs_file_lock(self.fs_label.into(), self.id(), exclusive as u8);
+        match result {
+            FsFileLockResult::Ok => Ok(()),
+            FsFileLockResult::NoSuchFs => Err(Error::NoSuchFs(self.fs_label)),
+            FsFileLockResult::Locked => Err(Error::Locked(self.id.to_string())),
+            _ => Err(Error::NoSuchFileWithId(self.id.to_string())),
         }
-        Ok(result as usize)
     }
 
     /// Get the id
     pub fn id(&self) -> &str {
-        self.id.to_str().unwrap()
+        self.id.as_str()
+    }
+
+    /// Get the handle
+    pub fn handle(&self) -> Handle<File> {
+        self.id
     }
 
     /// Get the fs label
@@ -136,3 +266,18 @@ impl File {
         self.fs_label
     }
 }
+
+impl PartialEq for File {
+    fn eq(&self, other: &Self) -> bool {
+        self.fs_label == other.fs_label && self.id == other.id
+    }
+}
+
+impl Eq for File {}
+
+impl Hash for File {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fs_label.hash(state);
+        self.id.hash(state);
+    }
+}
@@ -15,17 +15,14 @@ pub enum FsLabel {
 }
 
 impl FsLabel {
-    /// Extract the fs label from a path
+    /// Extract the fs label from the start of a path, such as the `a` of `a:/docs`.
+    /// Only the ascii letters are labels, so the label and colon are always the first two bytes.
     pub fn extract_from_path(path: &str) -> Result<Self, Error> {
-        let (fs_label_str, _) = path.split_at(3);
-        if !path.contains(':') {
-            return Err(Error::NoFsLabel(path.to_owned()));
+        let mut chars = path.chars();
+        match (chars.next(), chars.next()) {
+            (Some(label), Some(':')) if label.is_ascii_alphabetic() => label.to_string().parse(),
+            _ => Err(Error::NoFsLabel(path.to_owned())),
         }
-
-        let fs_char = fs_label_str
-            .get(0..1)
-            .ok_or(Error::NoFsLabel(path.to_owned()))?;
-        fs_char.parse()
     }
 }
 
@@ -35,44 +32,52 @@ impl std::fmt::Display for FsLabel {
     }
 }
 
-impl Into<u8> for FsLabel {
-    fn into(self) -> u8 {
-        match self {
-            Self::A => 'a' as u8,
-            Self::B => 'b' as u8,
-            Self::C => 'c' as u8,
-            Self::D => 'd' as u8,
-            Self::E => 'e' as u8,
-            Self::F => 'f' as u8,
-            Self::G => 'g' as u8,
-            Self::H => 'h' as u8,
-            Self::I => 'i' as u8,
-            Self::J => 'j' as u8,
-            Self::K => 'k' as u8,
-            Self::L => 'l' as u8,
-            Self::M => 'm' as u8,
-            Self::N => 'n' as u8,
-            Self::O => 'o' as u8,
-            Self::P => 'p' as u8,
-            Self::Q => 'q' as u8,
-            Self::R => 'r' as u8,
-            Self::S => 's' as u8,
-            Self::T => 't' as u8,
-            Self::U => 'u' as u8,
-            Self::V => 'v' as u8,
-            Self::W => 'w' as u8,
-            Self::X => 'x' as u8,
-            Self::Y => 'y' as u8,
-            Self::Z => 'z' as u8,
+impl From<FsLabel> for u8 {
+    fn from(label: FsLabel) -> u8 {
+        match label {
+            FsLabel::A => b'a',
+            FsLabel::B => b'b',
+            FsLabel::C => b'c',
+            FsLabel::D => b'd',
+            FsLabel::E => b'e',
+            FsLabel::F => b'f',
+            FsLabel::G => b'g',
+            FsLabel::H => b'h',
+            FsLabel::I => b'i',
+            FsLabel::J => b'j',
+            FsLabel::K => b'k',
+            FsLabel::L => b'l',
+            FsLabel::M => b'm',
+            FsLabel::N => b'n',
+            FsLabel::O => b'o',
+            FsLabel::P => b'p',
+            FsLabel::Q => b'q',
+            FsLabel::R => b'r',
+            FsLabel::S => b's',
+            FsLabel::T => b't',
+            FsLabel::U => b'u',
+            FsLabel::V => b'v',
+            FsLabel::W => b'w',
+            FsLabel::X => b'x',
+            FsLabel::Y => b'y',
+            FsLabel::Z => b'z',
         }
     }
 }
 
+impl TryFrom<u8> for FsLabel {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        (value as char).to_string().parse()
+    }
+}
+
 impl FromStr for FsLabel {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
+        match s.to_ascii_lowercase().as_str() {
             "a" => Ok(Self::A),
             "b" => Ok(Self::B),
             "c" => Ok(Self::C),
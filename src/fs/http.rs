@@ -0,0 +1,241 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use super::{
+    dir::Directory, error::Error, fslabel::FsLabel, path::HoneyPath, root, unmount, File,
+    RamFileSystem,
+};
+use crate::{
+    abi::FsFileWriteResult,
+    network::{Request, RequestMethod, RequestStatus},
+};
+
+/// The files that have not been fetched yet on each http file system mounted by the process,
+/// from their id to their url, by the process and label.
+/// The host doesn't know about them, which is why the file system is only valid in the process that mounted it.
+static MOUNTS: Mutex<BTreeMap<(u64, u8), BTreeMap<String, String>>> = Mutex::new(BTreeMap::new());
+
+/// Represents a read-only file system listed by a json manifest served over http.
+/// It is a ram file system with an empty file for each file in the manifest,
+/// and each file is fetched with a [`Request`] the first time the process reads it, or asks for its size.
+/// The manifest lists the paths of the files and any empty directories:
+/// ```json
+/// { "files": ["textures/grass.png", "sounds/click.wav"], "directories": ["saves"] }
+/// ```
+/// Changes made through hapi fail with `Error::ReadOnly`.
+///
+/// The files still to fetch and the read-only check are kept by the mounting process, not the host,
+/// so the file system only behaves like this inside the process that mounted it, until it exits.
+/// Any other process, or the app after a restart, sees a plain writable ram file system
+/// where the files that were never fetched stay empty. Mount it again from each process that reads it.
+/// ```
+/// # use hapi::fs::{fslabel::FsLabel, File, HttpFileSystem};
+/// # fn main() -> Result<(), hapi::fs::error::Error> {
+/// # hapi::sim::with_kernel(|kernel, _| {
+/// #     kernel.network.respond("/assets/manifest.json", r#"{ "files": ["logo.txt"] }"#);
+/// #     kernel.network.respond("/assets/logo.txt", "honey");
+/// # });
+/// HttpFileSystem::init(FsLabel::A, "/assets/manifest.json")?;
+/// let logo = File::open("a:/logo.txt")?.read_all()?;
+/// # assert_eq!(logo, b"honey");
+/// # Ok(())
+/// # }
+/// ```
+pub struct HttpFileSystem;
+
+impl HttpFileSystem {
+    /// Fetch the manifest at the url and mount the files it lists at the label.
+    /// Files are fetched from their path relative to the directory of the manifest.
+    /// ### Errors
+    /// - `Error::FsAlreadyExists` When a file system is already mounted at the label
+    /// - `Error::FetchFailed` When the manifest could not be fetched
+    /// - `Error::InvalidManifest` When the manifest is not valid json or lists a path outside of its directory
+    pub fn init(label: FsLabel, url: &str) -> Result<(), Error> {
+        let manifest = fetch(url)?;
+        let (directories, files) =
+            parse_manifest(&manifest).ok_or_else(|| Error::InvalidManifest(url.to_owned()))?;
+
+        RamFileSystem::init(label)?;
+        let base = url.rsplit_once('/').map_or("", |(base, _)| base);
+        match create(label, base, &directories, &files) {
+            Ok(pending) => {
+                lock().insert(key(label), pending);
+                Ok(())
+            }
+            Err(error) => {
+                unmount(label).ok();
+                Err(error)
+            }
+        }
+    }
+
+    /// Unmount the file system at the label, dropping the files fetched for it
+    /// ### Errors
+    /// - `Error::NoSuchFs` When no file system is mounted at the label
+    pub fn unmount(label: FsLabel) -> Result<(), Error> {
+        unmount(label)
+    }
+}
+
+/// Check if the file system at the label is an http file system mounted by the process
+pub(super) fn is_mounted(label: FsLabel) -> bool {
+    lock().contains_key(&key(label))
+}
+
+/// Check if a file is on an http file system and has not been fetched yet
+pub(super) fn is_pending(label: FsLabel, id: &str) -> bool {
+    lock()
+        .get(&key(label))
+        .is_some_and(|pending| pending.contains_key(id))
+}
+
+/// Forget the files of the http file system at the label once it is unmounted
+pub(super) fn forget(label: FsLabel) {
+    lock().remove(&key(label));
+}
+
+/// Fetch the contents of a file on an http file system if they have not been fetched yet
+pub(super) fn fetch_file(label: FsLabel, id: &str) -> Result<(), Error> {
+    let Some(url) = lock()
+        .get_mut(&key(label))
+        .and_then(|pending| pending.remove(id))
+    else {
+        return Ok(());
+    };
+    // The file is written through the host, as the file system is read-only to everything else
+    let written = fetch(&url).and_then(|data| {
+        match crate::host::current().fs_file_write(label.into(), id, 0, &data) {
+            FsFileWriteResult::Ok => Ok(()),
+            FsFileWriteResult::NotEnoughSpace => Err(Error::NotEnoughSpace(label)),
+            FsFileWriteResult::NoSuchFs => Err(Error::NoSuchFs(label)),
+            _ => Err(Error::NoSuchFileWithId(id.to_owned())),
+        }
+    });
+    if written.is_err() {
+        if let Some(pending) = lock().get_mut(&key(label)) {
+            pending.insert(id.to_owned(), url);
+        }
+    }
+    written
+}
+
+/// Fetch the contents of the file at a path if it is on an http file system and has not been fetched yet
+pub(super) fn fetch_path(label: FsLabel, path: &HoneyPath) -> Result<(), Error> {
+    if !is_mounted(label) {
+        return Ok(());
+    }
+    match File::open(path) {
+        Ok(file) => fetch_file(label, file.id()),
+        // Missing files are reported by the caller
+        Err(_) => Ok(()),
+    }
+}
+
+/// Fetch every file on the http file system at the label that has not been fetched yet
+pub(super) fn fetch_all(label: FsLabel) -> Result<(), Error> {
+    let ids: Vec<_> = lock()
+        .get(&key(label))
+        .map(|pending| pending.keys().cloned().collect())
+        .unwrap_or_default();
+    ids.iter().try_for_each(|id| fetch_file(label, id))
+}
+
+fn lock() -> MutexGuard<'static, BTreeMap<(u64, u8), BTreeMap<String, String>>> {
+    MOUNTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The key of the http file system at the label in [`MOUNTS`].
+/// A wasm process has its own statics, but every simulated process shares them.
+fn key(label: FsLabel) -> (u64, u8) {
+    #[cfg(feature = "sim")]
+    let process = crate::sim::context().id;
+    #[cfg(not(feature = "sim"))]
+    let process = 0;
+    (process, label.into())
+}
+
+/// Get the data at a url, waiting for the request to complete
+fn fetch(url: &str) -> Result<Vec<u8>, Error> {
+    let failed = |_| Error::FetchFailed(url.to_owned());
+    let request = Request::new(url, RequestMethod::Get, "{}").map_err(failed)?;
+    match request.wait().map_err(failed)? {
+        RequestStatus::Success => request.data().map_err(failed),
+        _ => Err(Error::FetchFailed(url.to_owned())),
+    }
+}
+
+/// Create the directories and empty files listed by a manifest, returning the url of each file by its id
+fn create(
+    label: FsLabel,
+    base: &str,
+    directories: &[String],
+    files: &[String],
+) -> Result<BTreeMap<String, String>, Error> {
+    let root = root(label);
+    for directory in directories {
+        create_dir_all(&root, directory)?;
+    }
+
+    let mut pending = BTreeMap::new();
+    for file in files {
+        if let Some((parent, _)) = file.rsplit_once('/') {
+            create_dir_all(&root, parent)?;
+        }
+        let file_id = File::create(format!("{}{}", root, file))?.id().to_owned();
+        pending.insert(file_id, format!("{}/{}", base, file));
+    }
+    Ok(pending)
+}
+
+/// Create a directory under the root and any missing parents
+fn create_dir_all(root: &str, path: &str) -> Result<(), Error> {
+    let mut current = root.to_owned();
+    for component in path.split('/').filter(|component| !component.is_empty()) {
+        current.push_str(component);
+        match Directory::create(&current) {
+            Ok(_) | Err(Error::DirExists(_)) => {}
+            Err(error) => return Err(error),
+        }
+        current.push('/');
+    }
+    Ok(())
+}
+
+/// Parse the directories and files listed by a manifest, as paths relative to its directory.
+/// Returns None if it is invalid, or a path leaves the directory.
+fn parse_manifest(manifest: &[u8]) -> Option<(Vec<String>, Vec<String>)> {
+    let manifest: serde_json::Value = serde_json::from_slice(manifest).ok()?;
+    let paths = |key: &str| -> Option<Vec<String>> {
+        match manifest.get(key) {
+            Some(paths) => paths
+                .as_array()?
+                .iter()
+                .map(|path| relative_path(path.as_str()?))
+                .collect(),
+            None => Some(Vec::new()),
+        }
+    };
+    let files = paths("files")?;
+    if manifest.get("files").is_none() || files.iter().any(String::is_empty) {
+        return None;
+    }
+    Some((paths("directories")?, files))
+}
+
+/// Normalize a path from a manifest. Returns None if it leaves the directory of the manifest.
+fn relative_path(path: &str) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => return None,
+            component if component.contains(':') => return None,
+            component => components.push(component),
+        }
+    }
+    Some(components.join("/"))
+}
@@ -0,0 +1,135 @@
+use std::time::SystemTime;
+
+use super::{dir::EntryKind, error::Error, path::HoneyPath};
+use crate::abi::{FsMetadataError, FsSetModifiedResult};
+
+/// The length of the metadata written by `hapi_fs_metadata` in abi version 13, the current [`ABI_VERSION`](crate::abi::ABI_VERSION).
+/// The layout has not changed since the import was added in version 6.
+const METADATA_LENGTH: usize = 30;
+
+/// The metadata of a file or directory, returned by [`metadata`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    kind: EntryKind,
+    len: u64,
+    read_only: bool,
+    created: SystemTime,
+    modified: SystemTime,
+    accessed: SystemTime,
+}
+
+impl Metadata {
+    /// Parse the metadata written by `hapi_fs_metadata`, ignoring fields added by later versions
+    fn parse(metadata: &[u8]) -> Result<Self, Error> {
+        let metadata = metadata
+            .get(..METADATA_LENGTH)
+            .ok_or(Error::InvalidMetadata)?;
+        let time = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&metadata[offset..offset + 8]);
+            crate::time::from_unix_epoch(f64::from_le_bytes(bytes)).ok_or(Error::InvalidMetadata)
+        };
+
+        Ok(Self {
+            kind: match metadata[0] {
+                0 => EntryKind::File,
+                1 => EntryKind::Directory,
+                _ => return Err(Error::InvalidMetadata),
+            },
+            read_only: metadata[1] == 1,
+            len: u32::from_le_bytes([metadata[2], metadata[3], metadata[4], metadata[5]]) as u64,
+            created: time(6)?,
+            modified: time(14)?,
+            accessed: time(22)?,
+        })
+    }
+
+    /// Get the kind of the entry
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    /// Check if the entry is a file
+    pub fn is_file(&self) -> bool {
+        self.kind == EntryKind::File
+    }
+
+    /// Check if the entry is a directory
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+
+    /// Get the length of the file in bytes, or 0 for a directory
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Check if the file is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check if the entry can't be written to
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Get the time the entry was created
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Get the time the entry was last written to
+    pub fn modified(&self) -> SystemTime {
+        self.modified
+    }
+
+    /// Get the time the entry was last read
+    pub fn accessed(&self) -> SystemTime {
+        self.accessed
+    }
+}
+
+/// Get the metadata of a file or directory.
+/// A file on an http file system is fetched first if it has not been, so its length is right.
+/// ### Errors
+/// - `Error::InvalidMetadata` When the host returns a time too far from the epoch for a `SystemTime`
+/// - `Error::FetchFailed` When a file on an http file system could not be fetched
+pub fn metadata(path: impl AsRef<HoneyPath>) -> Result<Metadata, Error> {
+    let (fs_label, path) = super::resolve(path.as_ref())?;
+    #[cfg(feature = "http")]
+    super::http::fetch_path(fs_label, &path)?;
+
+    let mut buffer = vec![0u8; METADATA_LENGTH];
+    loop {
+        let len = match crate::host::current().fs_metadata(path.as_str(), &mut buffer) {
+            Ok(len) => len as usize,
+            Err(FsMetadataError::NoSuchFs) => return Err(Error::NoSuchFs(fs_label)),
+            Err(FsMetadataError::InvalidPath) => {
+                return Err(Error::InvalidPath(path.into_string()))
+            }
+            Err(_) => return Err(Error::NoSuchFile(path.into_string())),
+        };
+
+        // A newer host may return more fields than this version knows about
+        if len > buffer.len() {
+            buffer.resize(len, 0);
+            continue;
+        }
+        return Metadata::parse(&buffer[..len]);
+    }
+}
+
+/// Set the time a file or directory was last written to
+pub fn set_modified(path: impl AsRef<HoneyPath>, modified: SystemTime) -> Result<(), Error> {
+    let (fs_label, path) = super::resolve(path.as_ref())?;
+    super::check_writable(fs_label)?;
+    let modified = crate::time::to_unix_epoch(modified);
+
+    match crate::host::current().fs_set_modified(path.as_str(), modified) {
+        FsSetModifiedResult::Ok => Ok(()),
+        FsSetModifiedResult::NoSuchFs => Err(Error::NoSuchFs(fs_label)),
+        FsSetModifiedResult::InvalidPath => Err(Error::InvalidPath(path.into_string())),
+        _ => Err(Error::NoSuchFile(path.into_string())),
+    }
+}
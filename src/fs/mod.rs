@@ -1,22 +1,264 @@
+pub mod archive;
+pub mod cursor;
 pub mod dir;
 pub mod error;
 pub mod file;
 pub mod fslabel;
+#[cfg(feature = "http")]
+mod http;
+pub mod metadata;
+pub mod mount;
+pub mod options;
+pub mod path;
+pub mod snapshot;
+pub mod transaction;
+pub mod watch;
 
+pub use cursor::FileCursor;
 pub use file::*;
+#[cfg(feature = "http")]
+pub use http::HttpFileSystem;
+pub use metadata::{metadata, set_modified, Metadata};
+pub use mount::{mounts, FsType, Mount};
+pub use options::OpenOptions;
+pub use path::{HoneyPath, HoneyPathBuf};
+pub use snapshot::Snapshot;
+pub use transaction::{write_atomic, Transaction};
+pub use watch::{watch, Event, Watcher};
 
 use self::{error::Error, fslabel::FsLabel};
+use crate::abi::{
+    FsDirectoryRemoveResult, FsFileCopyError, FsFileRemoveResult, FsInitOverlayResult,
+    FsInitPersistentResult, FsInitRamfsResult, FsInitRamfsWithCapacityResult, FsRenameResult,
+    FsUnmountResult,
+};
 
 /// Represents a ram file system
 pub struct RamFileSystem;
 
 impl RamFileSystem {
-    /// Initialzzie a ram file system and mount it at the label
+    /// Initialize a ram file system and mount it at the label
     pub fn init(label: FsLabel) -> Result<(), Error> {
-        let result = unsafe { crate::ffi::hapi_fs_init_ramfs(label.into()) };
-        if result < 0 {
-            return Err(Error::FsAlreadyExists(label));
+        match crate::host::current().fs_init_ramfs(label.into()) {
+            FsInitRamfsResult::Ok => Ok(()),
+            _ => Err(Error::FsAlreadyExists(label)),
         }
-        Ok(())
+    }
+
+    /// Initialize a ram file system that can hold at most the bytes, and mount it at the label.
+    /// Capacities above `u32::MAX` are clamped.
+    /// ### Errors
+    /// - `Error::FsAlreadyExists` When a file system is already mounted at the label
+    pub fn with_capacity(label: FsLabel, bytes: usize) -> Result<(), Error> {
+        let capacity = bytes.min(u32::MAX as usize) as u32;
+        match crate::host::current().fs_init_ramfs_with_capacity(label.into(), capacity) {
+            FsInitRamfsWithCapacityResult::Ok => Ok(()),
+            _ => Err(Error::FsAlreadyExists(label)),
+        }
+    }
+
+    /// Unmount the file system at the label, dropping everything on it.
+    /// Open files and directories on it stop working.
+    /// ### Errors
+    /// - `Error::NoSuchFs` When no file system is mounted at the label
+    pub fn unmount(label: FsLabel) -> Result<(), Error> {
+        unmount(label)
+    }
+}
+
+/// Represents a file system whose contents survive reloads and restarts.
+/// HoneyOS keeps it in browser storage, and the simulated host in a directory on the host.
+pub struct PersistentFileSystem;
+
+impl PersistentFileSystem {
+    /// Mount the persistent file system at the label, with the contents it had when it was last mounted
+    /// ### Errors
+    /// - `Error::FsAlreadyExists` When a file system is already mounted at the label
+    /// - `Error::StorageUnavailable` When the host has no storage for persistent file systems
+    pub fn init(label: FsLabel) -> Result<(), Error> {
+        match crate::host::current().fs_init_persistent(label.into()) {
+            FsInitPersistentResult::Ok => Ok(()),
+            FsInitPersistentResult::Unavailable => Err(Error::StorageUnavailable(label)),
+            _ => Err(Error::FsAlreadyExists(label)),
+        }
+    }
+
+    /// Unmount the file system at the label. The contents stay in storage for the next mount.
+    /// ### Errors
+    /// - `Error::NoSuchFs` When no file system is mounted at the label
+    pub fn unmount(label: FsLabel) -> Result<(), Error> {
+        unmount(label)
+    }
+}
+
+/// Represents an overlay of another file system under a writable ram layer.
+/// Files changed through the overlay are copied up to the ram layer, and deleted ones are hidden there,
+/// so the file system under it is never written to.
+pub struct OverlayFileSystem;
+
+impl OverlayFileSystem {
+    /// Mount an overlay of the file system at `lower` at the label.
+    /// The lower file system should not be changed while the overlay is mounted,
+    /// so every file on an [`HttpFileSystem`] is fetched before an overlay of it is mounted.
+    /// ### Errors
+    /// - `Error::FsAlreadyExists` When a file system is already mounted at the label
+    /// - `Error::NoSuchFs` When no file system is mounted at `lower`
+    /// - `Error::NestedOverlay` When the file system at `lower` is an overlay
+    pub fn init(label: FsLabel, lower: FsLabel) -> Result<(), Error> {
+        #[cfg(feature = "http")]
+        http::fetch_all(lower)?;
+        match crate::host::current().fs_init_overlay(label.into(), lower.into()) {
+            FsInitOverlayResult::Ok => Ok(()),
+            FsInitOverlayResult::NoSuchFs => Err(Error::NoSuchFs(lower)),
+            FsInitOverlayResult::InvalidLower => Err(Error::NestedOverlay(lower)),
+            _ => Err(Error::FsAlreadyExists(label)),
+        }
+    }
+
+    /// Unmount the overlay at the label, dropping the changes made through it
+    /// ### Errors
+    /// - `Error::NoSuchFs` When no file system is mounted at the label
+    pub fn unmount(label: FsLabel) -> Result<(), Error> {
+        unmount(label)
+    }
+}
+
+fn unmount(label: FsLabel) -> Result<(), Error> {
+    match crate::host::current().fs_unmount(label.into()) {
+        FsUnmountResult::Ok => {
+            #[cfg(feature = "http")]
+            http::forget(label);
+            Ok(())
+        }
+        _ => Err(Error::NoSuchFs(label)),
+    }
+}
+
+/// Fail with `Error::ReadOnly` if the file system at the label is an http file system
+#[cfg_attr(not(feature = "http"), allow(unused_variables))]
+fn check_writable(label: FsLabel) -> Result<(), Error> {
+    #[cfg(feature = "http")]
+    if http::is_mounted(label) {
+        return Err(Error::ReadOnly(label));
+    }
+    Ok(())
+}
+
+/// Fetch the contents of a file on an http file system if they have not been fetched yet
+#[cfg_attr(not(feature = "http"), allow(unused_variables))]
+fn fetch(label: FsLabel, id: &str) -> Result<(), Error> {
+    #[cfg(feature = "http")]
+    http::fetch_file(label, id)?;
+    Ok(())
+}
+
+/// Check if the contents of a file are on the host, which they are unless it is on an http file system
+/// and has not been fetched yet
+#[cfg_attr(not(feature = "http"), allow(unused_variables))]
+fn is_fetched(label: FsLabel, id: &str) -> bool {
+    #[cfg(feature = "http")]
+    if http::is_pending(label, id) {
+        return false;
+    }
+    true
+}
+
+/// Remove a file
+pub fn remove_file(path: impl AsRef<HoneyPath>) -> Result<(), Error> {
+    let (fs_label, path) = resolve(path.as_ref())?;
+    check_writable(fs_label)?;
+
+    match crate::host::current().fs_file_remove(path.as_str()) {
+        FsFileRemoveResult::Ok => Ok(()),
+        FsFileRemoveResult::NoSuchFs => Err(Error::NoSuchFs(fs_label)),
+        FsFileRemoveResult::InvalidPath => Err(Error::InvalidPath(path.into_string())),
+        _ => Err(Error::NoSuchFile(path.into_string())),
+    }
+}
+
+/// Remove an empty directory
+pub fn remove_dir(path: impl AsRef<HoneyPath>) -> Result<(), Error> {
+    remove_directory(path.as_ref(), false)
+}
+
+/// Remove a directory and everything inside it
+pub fn remove_dir_all(path: impl AsRef<HoneyPath>) -> Result<(), Error> {
+    remove_directory(path.as_ref(), true)
+}
+
+fn remove_directory(path: &HoneyPath, recursive: bool) -> Result<(), Error> {
+    let (fs_label, path) = resolve(path)?;
+    check_writable(fs_label)?;
+
+    match crate::host::current().fs_directory_remove(path.as_str(), recursive as u8) {
+        FsDirectoryRemoveResult::Ok => Ok(()),
+        FsDirectoryRemoveResult::NotEmpty => Err(Error::DirNotEmpty(path.into_string())),
+        FsDirectoryRemoveResult::NoSuchFs => Err(Error::NoSuchFs(fs_label)),
+        FsDirectoryRemoveResult::InvalidPath => Err(Error::InvalidPath(path.into_string())),
+        _ => Err(Error::NoSuchDirectory(path.into_string())),
+    }
+}
+
+/// Move a file or directory to a new path on the same file system.
+/// Open files and directories keep working, and a file at the new path is replaced.
+pub fn rename(from: impl AsRef<HoneyPath>, to: impl AsRef<HoneyPath>) -> Result<(), Error> {
+    let (from_label, from) = resolve(from.as_ref())?;
+    let (to_label, to) = resolve(to.as_ref())?;
+    check_writable(from_label)?;
+    check_writable(to_label)?;
+
+    match crate::host::current().fs_rename(from.as_str(), to.as_str()) {
+        FsRenameResult::Ok => Ok(()),
+        FsRenameResult::NoSuchDirectory => Err(Error::NoSuchDirectory(to.into_string())),
+        FsRenameResult::Exists => Err(Error::DestinationExists(to.into_string())),
+        FsRenameResult::CrossDevice => Err(Error::CrossDevice(from_label, to_label)),
+        FsRenameResult::InvalidPath => Err(Error::InvalidPath(to.into_string())),
+        FsRenameResult::NoSuchFs => Err(Error::NoSuchFs(unmounted(from_label, to_label))),
+        _ => Err(Error::NoSuchFile(from.into_string())),
+    }
+}
+
+/// Copy the contents of a file to a new path, which may be on another file system.
+/// A file at the new path is overwritten. Returns the amount of bytes copied.
+/// ### Errors
+/// - `Error::DirExists` When a directory exists at the new path
+pub fn copy(from: impl AsRef<HoneyPath>, to: impl AsRef<HoneyPath>) -> Result<u64, Error> {
+    let (from_label, from) = resolve(from.as_ref())?;
+    let (to_label, to) = resolve(to.as_ref())?;
+    check_writable(to_label)?;
+    #[cfg(feature = "http")]
+    http::fetch_path(from_label, &from)?;
+
+    match crate::host::current().fs_file_copy(from.as_str(), to.as_str()) {
+        Ok(len) => Ok(len as u64),
+        Err(FsFileCopyError::NoSuchDirectory) => Err(Error::NoSuchDirectory(to.into_string())),
+        // Files at the destination are overwritten, so only a directory can be in the way
+        Err(FsFileCopyError::Exists) => Err(Error::DirExists(to.into_string())),
+        Err(FsFileCopyError::InvalidPath) => Err(Error::InvalidPath(to.into_string())),
+        Err(FsFileCopyError::NotEnoughSpace) => Err(Error::NotEnoughSpace(to_label)),
+        Err(FsFileCopyError::NoSuchFs) => Err(Error::NoSuchFs(unmounted(from_label, to_label))),
+        Err(_) => Err(Error::NoSuchFile(from.into_string())),
+    }
+}
+
+/// Resolve a path against the working directory, and get the label of the file system it is on
+fn resolve(path: &HoneyPath) -> Result<(FsLabel, HoneyPathBuf), Error> {
+    let path = path.resolve()?;
+    let fs_label = path
+        .label()
+        .ok_or_else(|| Error::NoFsLabel(path.to_string()))?;
+    Ok((fs_label, path))
+}
+
+/// The path of the root directory of the file system at the label
+fn root(label: FsLabel) -> String {
+    format!("{}:/", u8::from(label) as char)
+}
+
+/// Find which of the two labels has no file system mounted
+fn unmounted(from: FsLabel, to: FsLabel) -> FsLabel {
+    match crate::host::current().fs_directory_get(&root(from)) {
+        Err(crate::abi::FsDirectoryGetError::NoSuchFs) => from,
+        _ => to,
     }
 }
@@ -0,0 +1,114 @@
+use super::{error::Error, fslabel::FsLabel};
+
+/// The length of each mount written by `hapi_fs_mounts`
+const MOUNT_LENGTH: usize = 10;
+
+/// The size of the first buffer the mount list is read into
+const MOUNTS_SIZE: usize = MOUNT_LENGTH * 8;
+
+/// The kind of a mounted file system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FsType {
+    Ram,
+    Persistent,
+    Overlay,
+    /// A read-only file system served over http, mounted by the process with `HttpFileSystem`
+    Http,
+    /// A type added by a newer host
+    Unknown(u8),
+}
+
+impl From<u8> for FsType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Ram,
+            1 => Self::Persistent,
+            2 => Self::Overlay,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+/// A mounted file system, returned by [`mounts`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mount {
+    label: FsLabel,
+    fs_type: FsType,
+    used: u64,
+    total: Option<u64>,
+}
+
+impl Mount {
+    /// Get the label the file system is mounted at
+    pub fn label(&self) -> FsLabel {
+        self.label
+    }
+
+    /// Get the kind of the file system
+    pub fn fs_type(&self) -> FsType {
+        self.fs_type
+    }
+
+    /// Get the bytes used by file contents
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Get the most bytes the file system can hold, None if it has no limit
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Get the bytes left before the file system is full, None if it has no limit
+    pub fn available(&self) -> Option<u64> {
+        self.total.map(|total| total.saturating_sub(self.used))
+    }
+}
+
+/// List the mounted file systems, sorted by label
+pub fn mounts() -> Result<Vec<Mount>, Error> {
+    let mut buffer = vec![0u8; MOUNTS_SIZE];
+    loop {
+        let len = crate::host::current().fs_mounts(&mut buffer) as usize;
+
+        // A file system may have been mounted since the last call, so retry until it fits
+        if len > buffer.len() {
+            buffer.resize(len, 0);
+            continue;
+        }
+        return parse_mounts(&buffer[..len]);
+    }
+}
+
+/// Parse the list written by `hapi_fs_mounts`
+fn parse_mounts(list: &[u8]) -> Result<Vec<Mount>, Error> {
+    if list.len() % MOUNT_LENGTH != 0 {
+        return Err(Error::InvalidMountList);
+    }
+
+    list.chunks_exact(MOUNT_LENGTH)
+        .map(|mount| {
+            let label = FsLabel::try_from(mount[0]).map_err(|_| Error::InvalidMountList)?;
+            let used = u32::from_le_bytes([mount[2], mount[3], mount[4], mount[5]]);
+            let total = u32::from_le_bytes([mount[6], mount[7], mount[8], mount[9]]);
+            Ok(Mount {
+                label,
+                fs_type: fs_type(label, mount[1]),
+                used: used as u64,
+                total: (total != u32::MAX).then_some(total as u64),
+            })
+        })
+        .collect()
+}
+
+/// The kind of the file system at the label, from the type the host gave it
+#[cfg_attr(not(feature = "http"), allow(unused_variables))]
+fn fs_type(label: FsLabel, fs_type: u8) -> FsType {
+    // The host sees an http file system as a ram file system
+    #[cfg(feature = "http")]
+    if super::http::is_mounted(label) {
+        return FsType::Http;
+    }
+    fs_type.into()
+}
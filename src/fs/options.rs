@@ -0,0 +1,124 @@
+use super::{error::Error, file::Mode, path::HoneyPath, File};
+use crate::{abi::FsFileOpenError, handle::Handle};
+
+/// Create the file if it doesn't exist
+const CREATE: u32 = 1;
+/// Fail if the file exists
+const CREATE_NEW: u32 = 2;
+/// Truncate the file to 0 bytes
+const TRUNCATE: u32 = 4;
+
+/// Options for opening a file, like [`std::fs::OpenOptions`].
+/// Creating and truncating happen in a single call to the host.
+/// ```
+/// # use hapi::fs::{fslabel::FsLabel, File, OpenOptions, RamFileSystem};
+/// # fn main() -> Result<(), hapi::fs::error::Error> {
+/// # RamFileSystem::init(FsLabel::A)?;
+/// let mut log = OpenOptions::new().append(true).create(true).open("a:/log.txt")?;
+/// log.write(0, b"started\n")?;
+/// # log.write(0, b"stopped\n")?;
+/// # assert_eq!(File::open("a:/log.txt")?.read_all()?, b"started\nstopped\n");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+}
+
+impl OpenOptions {
+    /// Create options with everything turned off
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow reading the file
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.read = read;
+        self
+    }
+
+    /// Allow writing the file
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.write = write;
+        self
+    }
+
+    /// Write to the end of the file, whatever the offset. Implies `write`.
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.append = append;
+        self
+    }
+
+    /// Truncate the file to 0 bytes when it is opened. Requires `write`.
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Create the file if it doesn't exist. Requires `write` or `append`.
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Create the file, failing if it exists. Requires `write` or `append`.
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Open the file at the path with the options
+    pub fn open(&self, path: impl AsRef<HoneyPath>) -> Result<File, Error> {
+        let writes = self.write || self.append;
+        if !self.read && !writes {
+            return Err(Error::InvalidOpenOptions(
+                "the file must be opened for reading, writing or appending",
+            ));
+        }
+        if self.truncate && !self.write {
+            return Err(Error::InvalidOpenOptions("truncating requires write"));
+        }
+        if (self.create || self.create_new) && !writes {
+            return Err(Error::InvalidOpenOptions(
+                "creating requires write or append",
+            ));
+        }
+
+        let (fs_label, path) = super::resolve(path.as_ref())?;
+        if writes {
+            super::check_writable(fs_label)?;
+        }
+        let flags = [
+            (self.create, CREATE),
+            (self.create_new, CREATE_NEW),
+            (self.truncate, TRUNCATE),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+
+        let id = crate::host::current()
+            .fs_file_open(path.as_str(), flags)
+            .map_err(|code| match code {
+                FsFileOpenError::Exists => Error::FileExists(path.to_string()),
+                FsFileOpenError::NoSuchDirectory => Error::NoSuchDirectory(path.to_string()),
+                FsFileOpenError::InvalidPath => Error::InvalidPath(path.to_string()),
+                FsFileOpenError::NoSuchFs => Error::NoSuchFs(fs_label),
+                _ => Error::NoSuchFile(path.to_string()),
+            })?;
+
+        let mode = Mode {
+            read: self.read,
+            write: writes,
+            append: self.append,
+        };
+        let id = Handle::parse(&id).map_err(|_| Error::InvalidId(id))?;
+        Ok(File::with_mode(fs_label, id, mode))
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::{error::Error, fslabel::FsLabel};
+
+/// A borrowed honeyos path, like `a:/docs/notes.txt` or `../notes.txt`.
+/// Paths starting with a fs label are absolute, the rest are relative to the working directory.
+/// This is the [`Path`] of honeyos, see [`HoneyPathBuf`] for the owned version.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct HoneyPath {
+    inner: str,
+}
+
+impl HoneyPath {
+    /// Wrap a string as a path
+    pub fn new<S: AsRef<str> + ?Sized>(path: &S) -> &Self {
+        // HoneyPath is a transparent wrapper around str
+        unsafe { &*(path.as_ref() as *const str as *const Self) }
+    }
+
+    /// Get the path as a string
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
+    /// Get the fs label the path starts with
+    pub fn label(&self) -> Option<FsLabel> {
+        FsLabel::extract_from_path(&self.inner).ok()
+    }
+
+    /// Check if the path starts with a fs label
+    pub fn is_absolute(&self) -> bool {
+        self.label().is_some()
+    }
+
+    /// Get the path without its fs label
+    fn rest(&self) -> &str {
+        match self.is_absolute() {
+            true => &self.inner[2..],
+            false => &self.inner,
+        }
+    }
+
+    /// Iterate over the names in the path, skipping empty names and `.`
+    pub fn components(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.rest()
+            .split('/')
+            .filter(|component| !component.is_empty() && *component != ".")
+    }
+
+    /// Get the last name in the path
+    pub fn file_name(&self) -> Option<&str> {
+        self.components().next_back().filter(|name| *name != "..")
+    }
+
+    /// Get the path without its last name, None for a root or an empty path
+    pub fn parent(&self) -> Option<&HoneyPath> {
+        let name = self.components().next_back()?;
+        let end = self.inner.rfind(name)?;
+        let parent = self.inner[..end].trim_end_matches('/');
+        // Keep the slash after the label of a root
+        match self.is_absolute() && parent.len() == 2 {
+            true => Some(Self::new(&self.inner[..end])),
+            false => Some(Self::new(parent)),
+        }
+    }
+
+    /// Join a path onto this one.
+    /// An absolute path replaces this one, like [`Path::join`].
+    pub fn join(&self, path: impl AsRef<HoneyPath>) -> HoneyPathBuf {
+        let mut buf = self.to_owned();
+        buf.push(path);
+        buf
+    }
+
+    /// Resolve `.` and `..` without touching the file system.
+    /// `..` at the root of a fs stays at the root, and leading `..` of a relative path are kept.
+    pub fn normalize(&self) -> HoneyPathBuf {
+        let mut components = Vec::new();
+        for component in self.components() {
+            match component {
+                ".." if components.last().is_some_and(|last| *last != "..") => {
+                    components.pop();
+                }
+                ".." if self.is_absolute() => {}
+                _ => components.push(component),
+            }
+        }
+
+        let inner = match self.label() {
+            Some(label) => format!("{}:/{}", u8::from(label) as char, components.join("/")),
+            None => components.join("/"),
+        };
+        HoneyPathBuf { inner }
+    }
+
+    /// Join the path onto the working directory if it is relative, and normalize it
+    /// ### Errors
+    /// - `Error::NoFsLabel` When the path is relative and the working directory has no fs label
+    pub fn resolve(&self) -> Result<HoneyPathBuf, Error> {
+        if self.is_absolute() {
+            return Ok(self.normalize());
+        }
+
+        let no_label = || Error::NoFsLabel(self.inner.to_owned());
+        let cwd = crate::process::cwd().map_err(|_| no_label())?;
+        let path = HoneyPath::new(&cwd).join(self).normalize();
+        match path.is_absolute() {
+            true => Ok(path),
+            false => Err(no_label()),
+        }
+    }
+}
+
+impl fmt::Debug for HoneyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl fmt::Display for HoneyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
+
+impl ToOwned for HoneyPath {
+    type Owned = HoneyPathBuf;
+
+    fn to_owned(&self) -> HoneyPathBuf {
+        HoneyPathBuf {
+            inner: self.inner.to_owned(),
+        }
+    }
+}
+
+impl AsRef<HoneyPath> for HoneyPath {
+    fn as_ref(&self) -> &HoneyPath {
+        self
+    }
+}
+
+impl AsRef<str> for HoneyPath {
+    fn as_ref(&self) -> &str {
+        &self.inner
+    }
+}
+
+impl AsRef<HoneyPath> for str {
+    fn as_ref(&self) -> &HoneyPath {
+        HoneyPath::new(self)
+    }
+}
+
+impl AsRef<HoneyPath> for String {
+    fn as_ref(&self) -> &HoneyPath {
+        HoneyPath::new(self)
+    }
+}
+
+/// Fails with `Error::InvalidPath` if the path is not valid utf-8
+impl<'a> TryFrom<&'a Path> for &'a HoneyPath {
+    type Error = Error;
+
+    fn try_from(path: &'a Path) -> Result<Self, Self::Error> {
+        path.to_str()
+            .map(HoneyPath::new)
+            .ok_or_else(|| Error::InvalidPath(path.to_string_lossy().into_owned()))
+    }
+}
+
+/// An owned honeyos path, see [`HoneyPath`]
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HoneyPathBuf {
+    inner: String,
+}
+
+impl HoneyPathBuf {
+    /// Create an empty path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the borrowed path
+    pub fn as_path(&self) -> &HoneyPath {
+        HoneyPath::new(&self.inner)
+    }
+
+    /// Get the path as a string
+    pub fn into_string(self) -> String {
+        self.inner
+    }
+
+    /// Add a path to the end of this one.
+    /// An absolute path replaces this one, like [`PathBuf::push`].
+    pub fn push(&mut self, path: impl AsRef<HoneyPath>) {
+        let path = path.as_ref();
+        if path.is_absolute() || self.inner.is_empty() {
+            path.inner.clone_into(&mut self.inner);
+            return;
+        }
+        if !self.inner.ends_with('/') {
+            self.inner.push('/');
+        }
+        self.inner.push_str(&path.inner);
+    }
+
+    /// Remove the last name from the path.
+    /// Returns false if there is no parent to go back to.
+    pub fn pop(&mut self) -> bool {
+        match self.parent().map(|parent| parent.inner.len()) {
+            Some(len) => {
+                self.inner.truncate(len);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Deref for HoneyPathBuf {
+    type Target = HoneyPath;
+
+    fn deref(&self) -> &HoneyPath {
+        self.as_path()
+    }
+}
+
+impl Borrow<HoneyPath> for HoneyPathBuf {
+    fn borrow(&self) -> &HoneyPath {
+        self.as_path()
+    }
+}
+
+impl AsRef<HoneyPath> for HoneyPathBuf {
+    fn as_ref(&self) -> &HoneyPath {
+        self.as_path()
+    }
+}
+
+impl AsRef<str> for HoneyPathBuf {
+    fn as_ref(&self) -> &str {
+        &self.inner
+    }
+}
+
+impl From<String> for HoneyPathBuf {
+    fn from(inner: String) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<&str> for HoneyPathBuf {
+    fn from(path: &str) -> Self {
+        Self {
+            inner: path.to_owned(),
+        }
+    }
+}
+
+impl From<&HoneyPath> for HoneyPathBuf {
+    fn from(path: &HoneyPath) -> Self {
+        path.to_owned()
+    }
+}
+
+/// Fails with `Error::InvalidPath` if the path is not valid utf-8
+impl TryFrom<PathBuf> for HoneyPathBuf {
+    type Error = Error;
+
+    fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
+        path.into_os_string()
+            .into_string()
+            .map(HoneyPathBuf::from)
+            .map_err(|path| Error::InvalidPath(path.to_string_lossy().into_owned()))
+    }
+}
+
+impl FromStr for HoneyPathBuf {
+    type Err = std::convert::Infallible;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Ok(path.into())
+    }
+}
+
+impl fmt::Debug for HoneyPathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl fmt::Display for HoneyPathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{
+    archive,
+    dir::{Directory, EntryKind},
+    error::Error,
+    fslabel::FsLabel,
+    remove_dir_all, root, RamFileSystem,
+};
+
+/// Counts the hidden directories made by rollbacks, so each gets a new name
+static HIDDEN_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The contents of a whole mounted file system at one point in time, kept as a tar archive of its root.
+/// Ids are not kept, so files and directories opened before a restore or rollback should be opened again.
+/// Only the contents and modification times are kept. Files come back writable,
+/// and their created and accessed times are the time they were restored.
+/// ```
+/// # use hapi::fs::{fslabel::FsLabel, File, RamFileSystem, Snapshot};
+/// # fn main() -> Result<(), hapi::fs::error::Error> {
+/// # RamFileSystem::init(FsLabel::A)?;
+/// let factory = Snapshot::take(FsLabel::A)?;
+/// // ...
+/// # File::create("a:/settings")?;
+/// factory.rollback(FsLabel::A)?;
+/// # assert!(File::open("a:/settings").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    archive: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Take a snapshot of the file system mounted at the label
+    /// ### Errors
+    /// - `Error::NoSuchFs` When no file system is mounted at the label
+    pub fn take(label: FsLabel) -> Result<Self, Error> {
+        Ok(Self {
+            archive: archive::pack(root(label))?,
+        })
+    }
+
+    /// Read a snapshot saved with [`Snapshot::into_bytes`], or any tar or zip archive
+    /// ### Errors
+    /// - `Error::InvalidArchive` When the bytes are not a valid archive
+    /// - `Error::InvalidPath` When the archive has paths outside of its root
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        archive::check(&bytes)?;
+        Ok(Self { archive: bytes })
+    }
+
+    /// The bytes of the snapshot, to save or send to another process
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.archive
+    }
+
+    /// Take the bytes of the snapshot
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.archive
+    }
+
+    /// Mount a new ram file system at the label with the contents of the snapshot
+    /// ### Errors
+    /// - `Error::FsAlreadyExists` When a file system is already mounted at the label
+    pub fn restore(&self, label: FsLabel) -> Result<(), Error> {
+        RamFileSystem::init(label)?;
+        archive::unpack(&self.archive, root(label)).map_err(|error| {
+            let _ = RamFileSystem::unmount(label);
+            error
+        })
+    }
+
+    /// Replace everything on the file system mounted at the label with the contents of the snapshot.
+    /// The snapshot is unpacked into a hidden directory first, so the file system is left as it was
+    /// if it can't be unpacked. It then needs room for both the old and new contents.
+    /// ### Errors
+    /// - `Error::NoSuchFs` When no file system is mounted at the label
+    /// - `Error::NotEnoughSpace` When the file system can't hold the new contents next to the old ones
+    pub fn rollback(&self, label: FsLabel) -> Result<(), Error> {
+        let root = root(label);
+        let staged = create_hidden(&root, "rollback")?;
+        if let Err(error) = archive::unpack(&self.archive, &staged) {
+            remove_dir_all(&staged).ok();
+            return Err(error);
+        }
+        let old = create_hidden(&root, "rollback").map_err(|error| {
+            remove_dir_all(&staged).ok();
+            error
+        })?;
+
+        // Renames don't need more space, but if one fails the old entries are moved back
+        let hidden = [staged.as_str(), old.as_str()];
+        let moved = move_entries(&root, &old, &hidden).and_then(|()| {
+            move_entries(&staged, &root, &[]).map_err(|error| {
+                remove_entries(&root, &hidden);
+                error
+            })
+        });
+        if let Err(error) = moved {
+            remove_dir_all(&staged).ok();
+            // If the old entries can't all be moved back, the rest are left in the hidden directory
+            if move_entries(&old, &root, &[]).is_ok() {
+                remove_dir_all(&old).ok();
+            }
+            return Err(error);
+        }
+        remove_dir_all(&staged)?;
+        remove_dir_all(&old)
+    }
+}
+
+/// Create an empty hidden directory in the root, with a name no other entry has
+fn create_hidden(root: &str, name: &str) -> Result<String, Error> {
+    loop {
+        let count = HIDDEN_COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = format!("{}.{}.{}", root, name, count);
+        match Directory::create(&path) {
+            Ok(_) => return Ok(path),
+            Err(Error::DirExists(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Remove every entry of the root, except the ones named in `skip`
+fn remove_entries(root: &str, skip: &[&str]) {
+    let Ok(entries) = Directory::open(root).and_then(|dir| dir.entries()) else {
+        return;
+    };
+    for entry in entries {
+        let path = format!("{}{}", root, entry.name());
+        if !skip.contains(&path.as_str()) {
+            match entry.kind() {
+                EntryKind::File => super::remove_file(&path).ok(),
+                EntryKind::Directory => remove_dir_all(&path).ok(),
+            };
+        }
+    }
+}
+
+/// Move every entry of a directory into another, except the ones named in `skip`
+fn move_entries(from: &str, to: &str, skip: &[&str]) -> Result<(), Error> {
+    for entry in Directory::open(from)?.entries()? {
+        let path = format!("{}/{}", from.trim_end_matches('/'), entry.name());
+        if !skip.contains(&path.as_str()) {
+            super::rename(
+                path,
+                format!("{}/{}", to.trim_end_matches('/'), entry.name()),
+            )?;
+        }
+    }
+    Ok(())
+}
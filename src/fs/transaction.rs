@@ -0,0 +1,235 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{
+    error::Error,
+    metadata,
+    path::{HoneyPath, HoneyPathBuf},
+    remove_file, rename, OpenOptions,
+};
+
+/// Counts the temporary files made by the process, so each gets a new name
+static TEMP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Replace the contents of a file in one step, creating it if it doesn't exist.
+/// The data is written to a temporary file next to it, which is then renamed over it,
+/// so the file never has half of the new contents. Relative paths are joined onto the current working directory.
+/// ### Errors
+/// - `Error::NoSuchDirectory` When the parent directory does not exist
+/// - `Error::DestinationExists` When a directory exists at the path
+/// - `Error::NotEnoughSpace` When the file system can't hold the new contents
+pub fn write_atomic(path: impl AsRef<HoneyPath>, data: &[u8]) -> Result<(), Error> {
+    let (_, path) = super::resolve(path.as_ref())?;
+    let temp = write_temp(&path, data)?;
+    rename(&temp, &path).map_err(|error| {
+        remove_file(&temp).ok();
+        error
+    })
+}
+
+/// A set of file writes, creates and removes that are made together or not at all.
+/// The new contents are written to temporary files next to the files first, so nothing changes if one can't be written.
+/// Committing then renames them into place, moving the replaced and removed files aside,
+/// and if a change fails the files moved so far are moved back.
+/// Other processes can see the files change one at a time while it commits.
+/// ```
+/// # use hapi::fs::{dir::Directory, fslabel::FsLabel, File, RamFileSystem, Transaction};
+/// # fn main() -> Result<(), hapi::fs::error::Error> {
+/// # RamFileSystem::init(FsLabel::A)?;
+/// # Directory::create("a:/db")?;
+/// # File::create("a:/db/journal")?;
+/// # let (index, page) = (b"index".to_vec(), b"page".to_vec());
+/// let mut transaction = Transaction::new();
+/// transaction
+///     .write("a:/db/index", index)
+///     .create("a:/db/page-4", page)
+///     .remove("a:/db/journal");
+/// transaction.commit()?;
+/// # assert!(File::open("a:/db/journal").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone)]
+enum Change {
+    Write(HoneyPathBuf, Vec<u8>),
+    Create(HoneyPathBuf, Vec<u8>),
+    Remove(HoneyPathBuf),
+}
+
+/// A file written to a temporary file, waiting to be moved into place
+struct Staged {
+    path: HoneyPathBuf,
+    temp: Option<HoneyPathBuf>,
+    must_exist: bool,
+    must_not_exist: bool,
+}
+
+/// How to take back a step of a commit
+enum Undo {
+    /// Move a file that was moved aside back to its path
+    Restore {
+        backup: HoneyPathBuf,
+        path: HoneyPathBuf,
+    },
+    /// Remove a file that was moved into place
+    Remove(HoneyPathBuf),
+}
+
+impl Transaction {
+    /// Create a transaction with no changes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the contents of a file, creating it if it doesn't exist
+    pub fn write(&mut self, path: impl AsRef<HoneyPath>, data: impl Into<Vec<u8>>) -> &mut Self {
+        let change = Change::Write(path.as_ref().to_owned(), data.into());
+        self.changes.push(change);
+        self
+    }
+
+    /// Create a file with the contents, failing the commit if it exists
+    pub fn create(&mut self, path: impl AsRef<HoneyPath>, data: impl Into<Vec<u8>>) -> &mut Self {
+        let change = Change::Create(path.as_ref().to_owned(), data.into());
+        self.changes.push(change);
+        self
+    }
+
+    /// Remove a file, failing the commit if it doesn't exist
+    pub fn remove(&mut self, path: impl AsRef<HoneyPath>) -> &mut Self {
+        self.changes.push(Change::Remove(path.as_ref().to_owned()));
+        self
+    }
+
+    /// Make the changes in the order they were added.
+    /// Relative paths are joined onto the working directory at the time of the commit.
+    /// ### Errors
+    /// The error of the first change that failed, once the changes before it are taken back
+    /// - `Error::FileExists` When a file exists at a path passed to [`Transaction::create`]
+    /// - `Error::NoSuchFile` When no file exists at a path passed to [`Transaction::remove`]
+    /// - `Error::DirExists` When a directory exists at a path
+    pub fn commit(self) -> Result<(), Error> {
+        let mut staged = Vec::new();
+        for change in self.changes {
+            match stage(change) {
+                Ok(change) => staged.push(change),
+                Err(error) => {
+                    remove_temps(&staged);
+                    return Err(error);
+                }
+            }
+        }
+
+        let mut undo = Vec::new();
+        for change in &staged {
+            if let Err(error) = apply(change, &mut undo) {
+                for step in undo.into_iter().rev() {
+                    match step {
+                        Undo::Restore { backup, path } => rename(backup, path).ok(),
+                        Undo::Remove(path) => remove_file(path).ok(),
+                    };
+                }
+                remove_temps(&staged);
+                return Err(error);
+            }
+        }
+
+        for step in undo {
+            if let Undo::Restore { backup, .. } = step {
+                remove_file(backup).ok();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write the new contents of a change to a temporary file
+fn stage(change: Change) -> Result<Staged, Error> {
+    let (path, data, must_exist, must_not_exist) = match change {
+        Change::Write(path, data) => (path, Some(data), false, false),
+        Change::Create(path, data) => (path, Some(data), false, true),
+        Change::Remove(path) => (path, None, true, false),
+    };
+    let (_, path) = super::resolve(&path)?;
+    let temp = data.map(|data| write_temp(&path, &data)).transpose()?;
+    Ok(Staged {
+        path,
+        temp,
+        must_exist,
+        must_not_exist,
+    })
+}
+
+/// Move the file at the path of a change aside and its new contents into place
+fn apply(change: &Staged, undo: &mut Vec<Undo>) -> Result<(), Error> {
+    let path = &change.path;
+    let exists = match metadata(path) {
+        Ok(metadata) if metadata.is_file() => true,
+        Ok(_) => return Err(Error::DirExists(path.to_string())),
+        Err(Error::NoSuchFile(_)) => false,
+        Err(error) => return Err(error),
+    };
+    if exists && change.must_not_exist {
+        return Err(Error::FileExists(path.to_string()));
+    }
+    if !exists && change.must_exist {
+        return Err(Error::NoSuchFile(path.to_string()));
+    }
+
+    if exists {
+        // The file is renamed over an empty temporary file, which reserves a name no one else uses
+        let (backup, _) = create_temp(path)?;
+        if let Err(error) = rename(path, &backup) {
+            remove_file(&backup).ok();
+            return Err(error);
+        }
+        undo.push(Undo::Restore {
+            backup,
+            path: path.clone(),
+        });
+    }
+    if let Some(temp) = &change.temp {
+        rename(temp, path)?;
+        undo.push(Undo::Remove(path.clone()));
+    }
+    Ok(())
+}
+
+/// Remove the temporary files of changes that were not moved into place
+fn remove_temps(staged: &[Staged]) {
+    for temp in staged.iter().filter_map(|change| change.temp.as_ref()) {
+        remove_file(temp).ok();
+    }
+}
+
+/// Write data to a new temporary file next to the path, and get the path of it
+fn write_temp(path: &HoneyPath, data: &[u8]) -> Result<HoneyPathBuf, Error> {
+    let (temp, mut file) = create_temp(path)?;
+    match file.write(0, data) {
+        Ok(()) => Ok(temp),
+        Err(error) => {
+            remove_file(&temp).ok();
+            Err(error)
+        }
+    }
+}
+
+/// Create an empty hidden file next to the path, with a name no other file has
+fn create_temp(path: &HoneyPath) -> Result<(HoneyPathBuf, super::File), Error> {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(Error::InvalidPath(path.to_string()));
+    };
+    loop {
+        let count = TEMP_COUNT.fetch_add(1, Ordering::Relaxed);
+        let temp = parent.join(format!(".{}.{}.tmp", name, count));
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => return Ok((temp, file)),
+            Err(Error::FileExists(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
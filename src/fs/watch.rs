@@ -0,0 +1,272 @@
+use std::{collections::VecDeque, time::Duration};
+#[cfg(all(feature = "async", feature = "thread"))]
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::Waker,
+};
+
+use super::{
+    error::Error,
+    path::{HoneyPath, HoneyPathBuf},
+};
+use crate::{
+    abi::{FsWatchError, FsWatchWaitResult},
+    handle::Handle,
+};
+
+/// The size of the first buffer changes are read into
+const CHANGES_SIZE: usize = 1024;
+
+/// How long the thread waking a watcher stream blocks in the host at a time
+#[cfg(all(feature = "async", feature = "thread"))]
+const STREAM_WAIT_MS: u32 = 1000;
+
+/// A change to a watched file or directory. Paths are absolute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    Create(HoneyPathBuf),
+    Modify(HoneyPathBuf),
+    Remove(HoneyPathBuf),
+    Rename {
+        from: HoneyPathBuf,
+        to: HoneyPathBuf,
+    },
+    /// The host's queue for the watcher was full, so later changes were dropped.
+    /// The path is the watched path, which should be looked at again.
+    Overflow(HoneyPathBuf),
+}
+
+impl Event {
+    /// Get the path that changed, the old path of a rename
+    pub fn path(&self) -> &HoneyPath {
+        match self {
+            Self::Create(path) | Self::Modify(path) | Self::Remove(path) | Self::Overflow(path) => {
+                path
+            }
+            Self::Rename { from, .. } => from,
+        }
+    }
+}
+
+/// Watch a file or directory for changes made by any process.
+/// A directory watcher sees changes to the directory and its entries, or everything under it if `recursive` is true.
+/// Relative paths are joined onto the current working directory.
+/// ```
+/// # use hapi::fs::{self, dir::Directory, fslabel::FsLabel, File, RamFileSystem};
+/// # fn main() -> Result<(), fs::error::Error> {
+/// # RamFileSystem::init(FsLabel::A)?;
+/// # Directory::create("a:/src")?;
+/// let mut watcher = fs::watch("a:/src", true)?;
+/// # File::create("a:/src/main.rs")?;
+/// for event in watcher.events() {
+///     println!("{:?}", event?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+/// ### Errors
+/// - `Error::NoSuchFile` When nothing exists at the path
+/// - `Error::NoSuchFs` When no file system is mounted at the label of the path
+pub fn watch(path: impl AsRef<HoneyPath>, recursive: bool) -> Result<Watcher, Error> {
+    let (fs_label, path) = super::resolve(path.as_ref())?;
+
+    let id = crate::host::current()
+        .fs_watch(path.as_str(), recursive as u8)
+        .map_err(|code| match code {
+            FsWatchError::NoSuchFs => Error::NoSuchFs(fs_label),
+            FsWatchError::InvalidPath => Error::InvalidPath(path.to_string()),
+            _ => Error::NoSuchFile(path.to_string()),
+        })?;
+
+    Ok(Watcher {
+        id: Handle::parse(&id).map_err(|_| Error::InvalidId(id))?,
+        pending: VecDeque::new(),
+        #[cfg(all(feature = "async", feature = "thread"))]
+        stream: Arc::default(),
+    })
+}
+
+/// Watches a file or directory, created by [`watch`].
+/// Changes are queued by the host until they are polled, and the watcher is stopped when it is dropped.
+/// The host queues at most 1024 changes, then an [`Event::Overflow`] in place of the ones it dropped.
+/// With the `async` and `thread` features it is also a `Stream` of events that never ends.
+/// Polling the stream never blocks: while nothing changes, a thread waits in the host for the next change
+/// and then wakes the task.
+#[derive(Debug)]
+pub struct Watcher {
+    id: Handle<Watcher>,
+    pending: VecDeque<Event>,
+    #[cfg(all(feature = "async", feature = "thread"))]
+    stream: Arc<StreamWaker>,
+}
+
+impl Watcher {
+    /// Get the next change without waiting, None if nothing has changed since the last poll
+    /// ### Errors
+    /// - `Error::NoSuchWatcher` When the host has stopped the watcher
+    pub fn poll(&mut self) -> Result<Option<Event>, Error> {
+        if self.pending.is_empty() {
+            self.pending.extend(self.read_changes()?);
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Get the next change, waiting up to the timeout for one. None if nothing changed before the timeout.
+    /// ### Errors
+    /// - `Error::NoSuchWatcher` When the host has stopped the watcher
+    pub fn wait(&mut self, timeout: Duration) -> Result<Option<Event>, Error> {
+        if self.pending.is_empty() {
+            let timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
+            match crate::host::current().fs_watch_wait(self.id(), timeout_ms) {
+                FsWatchWaitResult::Ok => {}
+                FsWatchWaitResult::Timeout => return Ok(None),
+                _ => return Err(Error::NoSuchWatcher(self.id.to_string())),
+            }
+        }
+        self.poll()
+    }
+
+    /// An iterator over the changes made since the last poll, that ends once they have all been read.
+    /// It can be called again later to get newer changes.
+    pub fn events(&mut self) -> Events<'_> {
+        Events { watcher: self }
+    }
+
+    /// Get the id
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// Get the handle
+    pub fn handle(&self) -> Handle<Watcher> {
+        self.id
+    }
+
+    /// Move the changes queued by the host out of it
+    fn read_changes(&self) -> Result<Vec<Event>, Error> {
+        let mut buffer = vec![0u8; CHANGES_SIZE];
+        loop {
+            let len = match crate::host::current().fs_watch_poll(self.id(), &mut buffer) {
+                Ok(len) => len as usize,
+                Err(_) => return Err(Error::NoSuchWatcher(self.id.to_string())),
+            };
+
+            // More changes may have been queued since the last call, so retry until they fit
+            if len > buffer.len() {
+                buffer.resize(len, 0);
+                continue;
+            }
+            buffer.truncate(len);
+            return parse_changes(&buffer);
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        crate::host::current().fs_unwatch(self.id());
+    }
+}
+
+#[cfg(all(feature = "async", feature = "thread"))]
+impl futures::Stream for Watcher {
+    type Item = Result<Event, Error>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let watcher = self.get_mut();
+        if let Some(event) = watcher.poll().transpose() {
+            return std::task::Poll::Ready(Some(event));
+        }
+
+        let stream = &watcher.stream;
+        *stream.waker.lock().unwrap_or_else(PoisonError::into_inner) = Some(cx.waker().clone());
+        // A change queued since the poll is still seen, as the thread checks the queue before it blocks
+        if !stream.waiting.swap(true, Ordering::AcqRel) {
+            let (id, stream) = (watcher.id.to_string(), stream.clone());
+            crate::thread::spawn(move || stream.wait(&id));
+        }
+        std::task::Poll::Pending
+    }
+}
+
+/// Wakes the task polling a [`Watcher`] stream from another thread, as the host can't wake a task
+#[cfg(all(feature = "async", feature = "thread"))]
+#[derive(Debug, Default)]
+struct StreamWaker {
+    waker: Mutex<Option<Waker>>,
+    /// Set while a thread is waiting for changes
+    waiting: AtomicBool,
+}
+
+#[cfg(all(feature = "async", feature = "thread"))]
+impl StreamWaker {
+    /// Block until changes are queued for the watcher or it is stopped, then wake the task
+    fn wait(&self, id: &str) {
+        while crate::host::current().fs_watch_wait(id, STREAM_WAIT_MS) == FsWatchWaitResult::Timeout
+        {
+        }
+        self.waiting.store(false, Ordering::Release);
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// An iterator over the changes seen by a [`Watcher`] since it was last polled
+#[derive(Debug)]
+pub struct Events<'a> {
+    watcher: &'a mut Watcher,
+}
+
+impl Iterator for Events<'_> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.watcher.poll().transpose()
+    }
+}
+
+/// Parse the changes written by `hapi_fs_watch_poll`
+fn parse_changes(mut changes: &[u8]) -> Result<Vec<Event>, Error> {
+    fn take<'a>(changes: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+        if changes.len() < len {
+            return Err(Error::InvalidChangeList);
+        }
+        let (head, tail) = changes.split_at(len);
+        *changes = tail;
+        Ok(head)
+    }
+    fn take_path(changes: &mut &[u8]) -> Result<HoneyPathBuf, Error> {
+        let bytes = take(changes, 4)?;
+        let len = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let path =
+            std::str::from_utf8(take(changes, len)?).map_err(|_| Error::InvalidChangeList)?;
+        Ok(HoneyPathBuf::from(path))
+    }
+
+    let mut events = Vec::new();
+    while !changes.is_empty() {
+        let kind = take(&mut changes, 1)?[0];
+        let (path, to) = (take_path(&mut changes)?, take_path(&mut changes)?);
+        events.push(match kind {
+            0 => Event::Create(path),
+            1 => Event::Modify(path),
+            2 => Event::Remove(path),
+            3 => Event::Rename { from: path, to },
+            4 => Event::Overflow(path),
+            _ => return Err(Error::InvalidChangeList),
+        });
+    }
+    Ok(events)
+}
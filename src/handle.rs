@@ -0,0 +1,140 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use crate::{
+    error::{Error, ErrorKind},
+    prelude::*,
+};
+
+/// The length of an id, without the null terminator
+pub const ID_LENGTH: usize = 36;
+
+/// The uuid formatted id of a resource owned by the host.
+/// The kind is the type of the resource, such as `Handle<File>` or `Handle<Process>`,
+/// so handles of different resources can't be mixed up.
+pub struct Handle<K> {
+    id: [u8; ID_LENGTH],
+    _kind: PhantomData<fn() -> K>,
+}
+
+impl<K> Handle<K> {
+    /// Parse and validate an id
+    /// ### Errors
+    /// - `ErrorKind::InvalidInput` When the id is not a uuid
+    pub fn parse(id: &str) -> Result<Self, Error> {
+        let bytes = id.as_bytes();
+        let valid = bytes.len() == ID_LENGTH
+            && bytes.iter().enumerate().all(|(i, byte)| match i {
+                8 | 13 | 18 | 23 => *byte == b'-',
+                _ => byte.is_ascii_hexdigit(),
+            });
+        if !valid {
+            return Err(Error::other(
+                ErrorKind::InvalidInput,
+                format!("{:?} is not a valid id", id),
+            ));
+        }
+
+        let mut handle = Self {
+            id: [0; ID_LENGTH],
+            _kind: PhantomData,
+        };
+        handle.id.copy_from_slice(bytes);
+        Ok(handle)
+    }
+
+    /// Read an id written by an import into a 37 byte buffer.
+    /// Returns the code of the import if it is negative, or `-1` if the id is not valid.
+    pub fn read(f: impl FnOnce(*mut u8) -> i32) -> Result<Self, i32> {
+        let mut buffer = [0u8; ID_LENGTH + 1];
+        let result = f(buffer.as_mut_ptr());
+        if result < 0 {
+            return Err(result);
+        }
+        let id = core::str::from_utf8(&buffer[..ID_LENGTH]).map_err(|_| -1)?;
+        match buffer[ID_LENGTH] {
+            0 => Self::parse(id).map_err(|_| -1),
+            _ => Err(-1),
+        }
+    }
+
+    /// Reinterpret the handle as the handle of another kind of resource
+    #[cfg(feature = "fs")]
+    pub(crate) fn cast<L>(self) -> Handle<L> {
+        Handle {
+            id: self.id,
+            _kind: PhantomData,
+        }
+    }
+
+    /// Get the id
+    pub fn as_str(&self) -> &str {
+        // The id was validated to be ascii when it was parsed
+        core::str::from_utf8(&self.id).unwrap()
+    }
+}
+
+impl<K> Clone for Handle<K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K> Copy for Handle<K> {}
+
+impl<K> PartialEq for Handle<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<K> Eq for Handle<K> {}
+
+impl<K> PartialOrd for Handle<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K> Ord for Handle<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<K> Hash for Handle<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<K> fmt::Debug for Handle<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.as_str()).finish()
+    }
+}
+
+impl<K> fmt::Display for Handle<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<K> FromStr for Handle<K> {
+    type Err = Error;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Self::parse(id)
+    }
+}
+
+impl<K> AsRef<str> for Handle<K> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
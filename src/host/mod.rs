@@ -0,0 +1,579 @@
+//! The host that the safe apis call into.
+//! By default every call goes straight to the `hapi` wasm imports through [`WasmHost`],
+//! but an alternative host can be installed for the whole process or for a single thread.
+//! Installing hosts needs the `std` feature.
+//! ```
+//! # use std::sync::Mutex;
+//! # use hapi::host::HapiHost;
+//! #[derive(Default)]
+//! struct Recorder(Mutex<Vec<String>>);
+//!
+//! impl HapiHost for Recorder {
+//!     fn stdout_write(&self, string: &str) {
+//!         self.0.lock().unwrap().push(string.to_owned());
+//!     }
+//!
+//!     fn thread_spawn(&self, f: Box<dyn FnOnce()>) {
+//!         f()
+//!     }
+//! }
+//!
+//! let _guard = hapi::host::install_local(Recorder::default());
+//! hapi::println!("Hello"); // Goes to the recorder instead of the wasm imports
+//! # assert_eq!(hapi::sim::stdout(), "");
+//! ```
+
+#[cfg(feature = "trace")]
+pub mod replay;
+#[cfg(feature = "trace")]
+pub mod trace;
+mod wasm;
+
+use alloc::sync::Arc;
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::{cell::RefCell, os::raw::c_void, sync::RwLock};
+
+pub use crate::abi::{Capability, ABI_VERSION};
+pub use wasm::WasmHost;
+
+// Only the codes of the enabled subsystems are used
+#[allow(unused_imports)]
+use crate::abi::*;
+use crate::prelude::*;
+
+/// The functionality of a honeyos host.
+/// Every method mirrors the `hapi_*` import with the same name, with it's codes decoded into the enums in [`crate::abi`].
+/// Strings and buffers are passed as rust types instead of pointers,
+/// and memory allocated by the host is owned by the caller.
+///
+/// Every method defaults to calling the wasm imports,
+/// so a host only has to implement the calls it replaces.
+pub trait HapiHost: Send + Sync {
+    /// Get the version of the abi the host implements
+    fn abi_version(&self) -> u32 {
+        WasmHost.abi_version()
+    }
+
+    /// Get the capabilities the host supports, as a bit set of [`Capability::bit`]
+    fn abi_capabilities(&self) -> u32 {
+        WasmHost.abi_capabilities()
+    }
+
+    /// Log a string to the js console as info
+    #[cfg(feature = "js")]
+    fn js_console_log_info(&self, string: &str) {
+        WasmHost.js_console_log_info(string)
+    }
+
+    /// Log a string to the js console as a warning
+    #[cfg(feature = "js")]
+    fn js_console_log_warn(&self, string: &str) {
+        WasmHost.js_console_log_warn(string)
+    }
+
+    /// Log a string to the js console as an error
+    #[cfg(feature = "js")]
+    fn js_console_log_error(&self, string: &str) {
+        WasmHost.js_console_log_error(string)
+    }
+
+    /// Evaluate a string as js code. Returns the result as json, or None if it failed.
+    #[cfg(feature = "js")]
+    fn js_console_eval(&self, source: &str) -> Option<String> {
+        WasmHost.js_console_eval(source)
+    }
+
+    /// Clear the process's stdout
+    fn stdout_clear(&self) {
+        WasmHost.stdout_clear()
+    }
+
+    /// Clear the last line in the process's stdout
+    fn stdout_clear_line(&self) {
+        WasmHost.stdout_clear_line()
+    }
+
+    /// Clear N number of lines in the process's stdout
+    fn stdout_clear_lines(&self, num: u32) {
+        WasmHost.stdout_clear_lines(num)
+    }
+
+    /// Print a string to the process's stdout
+    fn stdout_write(&self, string: &str) {
+        WasmHost.stdout_write(string)
+    }
+
+    /// Get the process id
+    #[cfg(feature = "process")]
+    fn process_get_pid(&self) -> Option<String> {
+        WasmHost.process_get_pid()
+    }
+
+    /// Get the current working directory
+    #[cfg(feature = "process")]
+    fn process_get_cwd(&self) -> Option<String> {
+        WasmHost.process_get_cwd()
+    }
+
+    /// Set the current working directory
+    #[cfg(feature = "process")]
+    fn process_set_cwd(&self, path: &str) -> ProcessSetCwdResult {
+        WasmHost.process_set_cwd(path)
+    }
+
+    /// Spawn a wasm binary as a subprocess and return it's pid
+    #[cfg(feature = "process")]
+    fn process_spawn_subprocess(
+        &self,
+        bin: &[u8],
+    ) -> Result<String, ProcessSpawnSubprocessV2Error> {
+        WasmHost.process_spawn_subprocess(bin)
+    }
+
+    /// Whether the process is alive
+    #[cfg(feature = "process")]
+    fn process_alive(&self, id: &str) -> bool {
+        WasmHost.process_alive(id)
+    }
+
+    /// Get the stdout of a process
+    #[cfg(feature = "process")]
+    fn process_stdout(&self, id: &str) -> Option<String> {
+        WasmHost.process_stdout(id)
+    }
+
+    /// Attempt to take control of the display
+    #[cfg(feature = "display")]
+    fn display_assume_control(&self) -> DisplayAssumeControlResult {
+        WasmHost.display_assume_control()
+    }
+
+    /// Override the control over the display
+    #[cfg(feature = "display")]
+    fn display_override_control(&self) {
+        WasmHost.display_override_control()
+    }
+
+    /// Release the control over the display
+    #[cfg(feature = "display")]
+    fn display_release_control(&self) -> DisplayReleaseControlResult {
+        WasmHost.display_release_control()
+    }
+
+    /// Take away the control over the display from the currently controling process
+    #[cfg(feature = "display")]
+    fn display_displace_control(&self) {
+        WasmHost.display_displace_control()
+    }
+
+    /// Push stdout to the display's text-mode buffer
+    #[cfg(feature = "display")]
+    fn display_push_stdout(&self) -> DisplayPushStdoutResult {
+        WasmHost.display_push_stdout()
+    }
+
+    /// Set the text in the display's text-mode buffer
+    #[cfg(feature = "display")]
+    fn display_set_text(&self, text: &str) -> DisplaySetTextResult {
+        WasmHost.display_set_text(text)
+    }
+
+    /// Get the key in the display's key buffer
+    #[cfg(feature = "display")]
+    fn display_get_key_buffer(&self) -> Result<i32, DisplayGetKeyBufferError> {
+        WasmHost.display_get_key_buffer()
+    }
+
+    /// Whether the shift key is in the key buffer
+    #[cfg(feature = "display")]
+    fn display_get_key_shift(&self) -> Result<bool, DisplayGetKeyShiftError> {
+        WasmHost.display_get_key_shift()
+    }
+
+    /// Whether the control key is in the key buffer
+    #[cfg(feature = "display")]
+    fn display_get_key_ctrl(&self) -> Result<bool, DisplayGetKeyCtrlError> {
+        WasmHost.display_get_key_ctrl()
+    }
+
+    /// Clear the key buffer of the display
+    #[cfg(feature = "display")]
+    fn display_clear_key(&self) {
+        WasmHost.display_clear_key()
+    }
+
+    /// Get the time in seconds since the start of the unix epoch
+    fn time_since_unix_epoch(&self) -> f64 {
+        WasmHost.time_since_unix_epoch()
+    }
+
+    /// Get the time in seconds since the start of the process
+    fn time_since_startup(&self) -> f64 {
+        WasmHost.time_since_startup()
+    }
+
+    /// Get the user agent of the browser
+    fn browser_user_agent(&self) -> Option<String> {
+        WasmHost.browser_user_agent()
+    }
+
+    /// Whether the browser is online
+    fn browser_is_online(&self) -> bool {
+        WasmHost.browser_is_online()
+    }
+
+    /// Create a network request and return it's id
+    #[cfg(feature = "network")]
+    fn network_request(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        WasmHost.network_request(url, method, headers)
+    }
+
+    /// Create a network request to the local server and return it's id
+    #[cfg(feature = "network")]
+    fn network_request_local(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        WasmHost.network_request_local(url, method, headers)
+    }
+
+    /// Check the status of a request
+    #[cfg(feature = "network")]
+    fn network_request_status(&self, id: &str) -> Result<u32, NetworkRequestStatusError> {
+        WasmHost.network_request_status(id)
+    }
+
+    /// Get the data of a request
+    #[cfg(feature = "network")]
+    fn network_request_data(&self, id: &str) -> Option<Vec<u8>> {
+        WasmHost.network_request_data(id)
+    }
+
+    /// Drop a request
+    #[cfg(feature = "network")]
+    fn network_request_drop(&self, id: &str) {
+        WasmHost.network_request_drop(id)
+    }
+
+    /// Register a ram filesystem
+    #[cfg(feature = "fs")]
+    fn fs_init_ramfs(&self, label: u8) -> FsInitRamfsResult {
+        WasmHost.fs_init_ramfs(label)
+    }
+
+    /// Create a file
+    #[cfg(feature = "fs")]
+    fn fs_file_create(&self, path: &str) -> FsFileCreateResult {
+        WasmHost.fs_file_create(path)
+    }
+
+    /// Find a file and return it's id
+    #[cfg(feature = "fs")]
+    fn fs_file_get(&self, path: &str) -> Result<String, FsFileGetError> {
+        WasmHost.fs_file_get(path)
+    }
+
+    /// Write data to a file
+    #[cfg(feature = "fs")]
+    fn fs_file_write(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        data: &[u8],
+    ) -> FsFileWriteResult {
+        WasmHost.fs_file_write(fs_label, file_id, offset, data)
+    }
+
+    /// Read data from a file into the buffer
+    #[cfg(feature = "fs")]
+    fn fs_file_read(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &mut [u8],
+    ) -> FsFileReadResult {
+        WasmHost.fs_file_read(fs_label, file_id, offset, buffer)
+    }
+
+    /// Get the length of a file
+    #[cfg(feature = "fs")]
+    fn fs_file_size(&self, fs_label: u8, file_id: &str) -> Result<u32, FsFileSizeError> {
+        WasmHost.fs_file_size(fs_label, file_id)
+    }
+
+    /// Create a directory
+    #[cfg(feature = "fs")]
+    fn fs_directory_create(&self, path: &str) -> FsDirectoryCreateResult {
+        WasmHost.fs_directory_create(path)
+    }
+
+    /// Find a directory and return it's id
+    #[cfg(feature = "fs")]
+    fn fs_directory_get(&self, path: &str) -> Result<String, FsDirectoryGetError> {
+        WasmHost.fs_directory_get(path)
+    }
+
+    /// List a directory into the buffer and return the length of the listing.
+    /// Nothing is written if the listing is longer than the buffer.
+    #[cfg(feature = "fs")]
+    fn fs_directory_list(
+        &self,
+        fs_label: u8,
+        dir_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsDirectoryListError> {
+        WasmHost.fs_directory_list(fs_label, dir_id, buffer)
+    }
+
+    /// Remove a file
+    #[cfg(feature = "fs")]
+    fn fs_file_remove(&self, path: &str) -> FsFileRemoveResult {
+        WasmHost.fs_file_remove(path)
+    }
+
+    /// Remove a directory, and it's contents if recursive is `1`
+    #[cfg(feature = "fs")]
+    fn fs_directory_remove(&self, path: &str, recursive: u8) -> FsDirectoryRemoveResult {
+        WasmHost.fs_directory_remove(path, recursive)
+    }
+
+    /// Move a file or directory
+    #[cfg(feature = "fs")]
+    fn fs_rename(&self, from: &str, to: &str) -> FsRenameResult {
+        WasmHost.fs_rename(from, to)
+    }
+
+    /// Copy a file and return the amount of bytes copied
+    #[cfg(feature = "fs")]
+    fn fs_file_copy(&self, from: &str, to: &str) -> Result<u32, FsFileCopyError> {
+        WasmHost.fs_file_copy(from, to)
+    }
+
+    /// Write the metadata of a path into the buffer and return it's length.
+    /// Nothing is written if the metadata is longer than the buffer.
+    #[cfg(feature = "fs")]
+    fn fs_metadata(&self, path: &str, buffer: &mut [u8]) -> Result<u32, FsMetadataError> {
+        WasmHost.fs_metadata(path, buffer)
+    }
+
+    /// Set the modified time of a path, in seconds since the unix epoch
+    #[cfg(feature = "fs")]
+    fn fs_set_modified(&self, path: &str, modified: f64) -> FsSetModifiedResult {
+        WasmHost.fs_set_modified(path, modified)
+    }
+
+    /// Open a file, creating or truncating it depending on the flags, and return it's id
+    #[cfg(feature = "fs")]
+    fn fs_file_open(&self, path: &str, flags: u32) -> Result<String, FsFileOpenError> {
+        WasmHost.fs_file_open(path, flags)
+    }
+
+    /// Truncate or extend a file
+    #[cfg(feature = "fs")]
+    fn fs_file_set_len(&self, fs_label: u8, file_id: &str, len: u32) -> FsFileSetLenResult {
+        WasmHost.fs_file_set_len(fs_label, file_id, len)
+    }
+
+    /// Write data to the end of a file in one step
+    #[cfg(feature = "fs")]
+    fn fs_file_append(&self, fs_label: u8, file_id: &str, data: &[u8]) -> FsFileAppendResult {
+        WasmHost.fs_file_append(fs_label, file_id, data)
+    }
+
+    /// Mount a ram file system that can hold at most the capacity in bytes
+    #[cfg(feature = "fs")]
+    fn fs_init_ramfs_with_capacity(
+        &self,
+        label: u8,
+        capacity: u32,
+    ) -> FsInitRamfsWithCapacityResult {
+        WasmHost.fs_init_ramfs_with_capacity(label, capacity)
+    }
+
+    /// Unmount a file system
+    #[cfg(feature = "fs")]
+    fn fs_unmount(&self, label: u8) -> FsUnmountResult {
+        WasmHost.fs_unmount(label)
+    }
+
+    /// List the mounted file systems into the buffer, returns the length of the list
+    #[cfg(feature = "fs")]
+    fn fs_mounts(&self, buffer: &mut [u8]) -> u32 {
+        WasmHost.fs_mounts(buffer)
+    }
+
+    /// Mount a file system that is kept in the host's storage
+    #[cfg(feature = "fs")]
+    fn fs_init_persistent(&self, label: u8) -> FsInitPersistentResult {
+        WasmHost.fs_init_persistent(label)
+    }
+
+    /// Mount a writable overlay of another file system
+    #[cfg(feature = "fs")]
+    fn fs_init_overlay(&self, label: u8, lower: u8) -> FsInitOverlayResult {
+        WasmHost.fs_init_overlay(label, lower)
+    }
+
+    /// Start watching a file or directory for changes
+    #[cfg(feature = "fs")]
+    fn fs_watch(&self, path: &str, recursive: u8) -> Result<String, FsWatchError> {
+        WasmHost.fs_watch(path, recursive)
+    }
+
+    /// Move the changes queued for a watcher into a buffer
+    #[cfg(feature = "fs")]
+    fn fs_watch_poll(&self, watcher_id: &str, buffer: &mut [u8]) -> Result<u32, FsWatchPollError> {
+        WasmHost.fs_watch_poll(watcher_id, buffer)
+    }
+
+    /// Block until changes are queued for a watcher, or the timeout has passed
+    #[cfg(feature = "fs")]
+    fn fs_watch_wait(&self, watcher_id: &str, timeout_ms: u32) -> FsWatchWaitResult {
+        WasmHost.fs_watch_wait(watcher_id, timeout_ms)
+    }
+
+    /// Stop a watcher
+    #[cfg(feature = "fs")]
+    fn fs_unwatch(&self, watcher_id: &str) -> FsUnwatchResult {
+        WasmHost.fs_unwatch(watcher_id)
+    }
+
+    /// Take an advisory lock on a file without waiting
+    #[cfg(feature = "fs")]
+    fn fs_file_lock(&self, fs_label: u8, file_id: &str, exclusive: u8) -> FsFileLockResult {
+        WasmHost.fs_file_lock(fs_label, file_id, exclusive)
+    }
+
+    /// Take an advisory lock on a file, blocking while it is held until the timeout has passed
+    #[cfg(feature = "fs")]
+    fn fs_file_lock_wait(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        exclusive: u8,
+        timeout_ms: u32,
+    ) -> FsFileLockWaitResult {
+        WasmHost.fs_file_lock_wait(fs_label, file_id, exclusive, timeout_ms)
+    }
+
+    /// Release an advisory lock on a file
+    #[cfg(feature = "fs")]
+    fn fs_file_unlock(&self, fs_label: u8, file_id: &str) -> FsFileUnlockResult {
+        WasmHost.fs_file_unlock(fs_label, file_id)
+    }
+
+    /// Run a function on a new thread
+    #[cfg(feature = "thread")]
+    fn thread_spawn(&self, f: Box<dyn FnOnce()>);
+}
+
+/// The host installed for the whole process
+#[cfg(feature = "std")]
+static HOST: RwLock<Option<Arc<dyn HapiHost>>> = RwLock::new(None);
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// The host installed for the current thread
+    static LOCAL_HOST: RefCell<Option<Arc<dyn HapiHost>>> = const { RefCell::new(None) };
+}
+
+/// The default host
+static WASM_HOST: WasmHost = WasmHost;
+
+/// A reference to the current host
+pub enum HostRef {
+    Default(&'static WasmHost),
+    Installed(Arc<dyn HapiHost>),
+}
+
+impl Deref for HostRef {
+    type Target = dyn HapiHost;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            HostRef::Default(host) => *host,
+            HostRef::Installed(host) => host.as_ref(),
+        }
+    }
+}
+
+/// Get the host of the current thread.
+/// Returns the thread's host if one is installed, otherwise the process's host.
+#[cfg(feature = "std")]
+pub fn current() -> HostRef {
+    if let Some(host) = local() {
+        return HostRef::Installed(host);
+    }
+    match HOST.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(host) => HostRef::Installed(host.clone()),
+        None => HostRef::Default(&WASM_HOST),
+    }
+}
+
+/// Get the host. Without `std` hosts can't be installed, so this is always [`WasmHost`].
+#[cfg(not(feature = "std"))]
+pub fn current() -> HostRef {
+    HostRef::Default(&WASM_HOST)
+}
+
+/// Get the version of the abi implemented by the current host.
+/// The imports added after it are stubs on a host older than [`ABI_VERSION`].
+pub fn abi_version() -> u32 {
+    current().abi_version()
+}
+
+/// Whether the current host supports the capability.
+/// The imports of an unsupported capability are stubs, which hosts must define so apps can load.
+pub fn supports(capability: Capability) -> bool {
+    current().abi_capabilities() & capability.bit() != 0
+}
+
+/// Install a host for the whole process
+#[cfg(feature = "std")]
+pub fn install(host: impl HapiHost + 'static) {
+    *HOST.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(host));
+}
+
+/// Go back to using the wasm imports for the whole process
+#[cfg(feature = "std")]
+pub fn uninstall() {
+    *HOST.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Install a host for the current thread, and the threads it spawns.
+/// The previous host is restored when the guard is dropped.
+#[cfg(feature = "std")]
+pub fn install_local(host: impl HapiHost + 'static) -> LocalHostGuard {
+    install_local_shared(Arc::new(host))
+}
+
+/// Install a shared host for the current thread, and the threads it spawns.
+/// The previous host is restored when the guard is dropped.
+#[cfg(feature = "std")]
+pub fn install_local_shared(host: Arc<dyn HapiHost>) -> LocalHostGuard {
+    let previous = LOCAL_HOST.with(|local| local.borrow_mut().replace(host));
+    LocalHostGuard {
+        previous,
+        _not_send: std::marker::PhantomData,
+    }
+}
+
+/// Get the host installed for the current thread
+#[cfg(feature = "std")]
+pub fn local() -> Option<Arc<dyn HapiHost>> {
+    LOCAL_HOST.with(|local| local.borrow().clone())
+}
+
+/// Restores the previous host of the thread when dropped
+#[cfg(feature = "std")]
+pub struct LocalHostGuard {
+    previous: Option<Arc<dyn HapiHost>>,
+    _not_send: std::marker::PhantomData<*const c_void>,
+}
+
+#[cfg(feature = "std")]
+impl Drop for LocalHostGuard {
+    fn drop(&mut self) {
+        LOCAL_HOST.with(|local| *local.borrow_mut() = self.previous.take());
+    }
+}
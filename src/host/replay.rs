@@ -0,0 +1,431 @@
+//! Deterministic replay of a recorded trace.
+//! A [`ReplayHost`] answers every call with the result recorded in the trace,
+//! and panics as soon as the app makes a call that differs from the recording.
+//! ```
+//! # use std::sync::{Arc, Mutex};
+//! # use hapi::host::{replay::ReplayHost, trace::{TraceSink, TracingHost}, WasmHost};
+//! # fn run_app() {
+//! #     hapi::println!("pid {:?}", hapi::process::pid());
+//! # }
+//! # fn main() -> Result<(), usize> {
+//! # let events = Arc::new(Mutex::new(Vec::new()));
+//! # let host = TracingHost::new(Arc::new(WasmHost), TraceSink::Memory(events.clone())).unwrap();
+//! # {
+//! #     let _guard = hapi::host::install_local(host);
+//! #     run_app();
+//! # }
+//! # let recorded: String = events.lock().unwrap().iter().map(|event| format!("{}\n", event.to_json())).collect();
+//! let trace = hapi::host::trace::parse(&recorded)?;
+//! let _guard = hapi::host::install_local(ReplayHost::new(trace));
+//! run_app();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use serde_json::{json, Value};
+
+use super::{
+    trace::{to_hex, TraceEvent, TraceValue},
+    HapiHost,
+};
+use crate::abi::*;
+
+/// A host that answers calls from a recorded trace.
+/// Threads are run on the spot when they are spawned, so apps whose threads race may diverge.
+pub struct ReplayHost {
+    events: Mutex<VecDeque<TraceEvent>>,
+    replayed: Mutex<usize>,
+}
+
+impl ReplayHost {
+    /// Replay the events in order
+    pub fn new(events: impl IntoIterator<Item = TraceEvent>) -> Self {
+        Self {
+            events: Mutex::new(events.into_iter().collect()),
+            replayed: Mutex::new(0),
+        }
+    }
+
+    /// The number of events that have not been replayed yet
+    pub fn remaining(&self) -> usize {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Take the next event, checking it matches the call.
+    /// ### Panics
+    /// Panics if the trace is exhausted, or if the next event is a different call or has different arguments.
+    fn next(&self, call: &str, args: Value) -> TraceEvent {
+        let event = self
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front();
+        let mut replayed = self.replayed.lock().unwrap_or_else(|e| e.into_inner());
+        let index = *replayed;
+        *replayed += 1;
+
+        let Some(event) = event else {
+            panic!(
+                "Replay diverged at event {}: {} was not recorded",
+                index, call
+            );
+        };
+        let args = args.as_array().cloned().unwrap_or_default();
+        if event.call != call || event.args != args {
+            panic!(
+                "Replay diverged at event {}: expected {}({:?}) but got {}({:?})",
+                index, event.call, event.args, call, args
+            );
+        }
+        event
+    }
+
+    fn replay<R: TraceValue>(&self, call: &str, args: Value) -> R {
+        let event = self.next(call, args);
+        R::from_value(&event.ret).unwrap_or_else(|| {
+            panic!(
+                "Replay failed: {} can not return the recorded {}",
+                call, event.ret
+            )
+        })
+    }
+}
+
+impl HapiHost for ReplayHost {
+    fn abi_version(&self) -> u32 {
+        self.replay("hapi_abi_version", json!([]))
+    }
+
+    fn abi_capabilities(&self) -> u32 {
+        self.replay("hapi_abi_capabilities", json!([]))
+    }
+
+    fn js_console_log_info(&self, string: &str) {
+        self.replay("hapi_js_console_log_info", json!([string]))
+    }
+
+    fn js_console_log_warn(&self, string: &str) {
+        self.replay("hapi_js_console_log_warn", json!([string]))
+    }
+
+    fn js_console_log_error(&self, string: &str) {
+        self.replay("hapi_js_console_log_error", json!([string]))
+    }
+
+    fn js_console_eval(&self, source: &str) -> Option<String> {
+        self.replay("hapi_js_console_eval", json!([source]))
+    }
+
+    fn stdout_clear(&self) {
+        self.replay("hapi_stdout_clear", json!([]))
+    }
+
+    fn stdout_clear_line(&self) {
+        self.replay("hapi_stdout_clear_line", json!([]))
+    }
+
+    fn stdout_clear_lines(&self, num: u32) {
+        self.replay("hapi_stdout_clear_lines", json!([num]))
+    }
+
+    fn stdout_write(&self, string: &str) {
+        self.replay("hapi_stdout_write", json!([string]))
+    }
+
+    fn process_get_pid(&self) -> Option<String> {
+        self.replay("hapi_process_get_pid", json!([]))
+    }
+
+    fn process_get_cwd(&self) -> Option<String> {
+        self.replay("hapi_process_get_cwd", json!([]))
+    }
+
+    fn process_set_cwd(&self, path: &str) -> ProcessSetCwdResult {
+        self.replay("hapi_process_set_cwd", json!([path]))
+    }
+
+    fn process_spawn_subprocess(
+        &self,
+        bin: &[u8],
+    ) -> Result<String, ProcessSpawnSubprocessV2Error> {
+        self.replay("hapi_process_spawn_subprocess", json!([to_hex(bin)]))
+    }
+
+    fn process_alive(&self, id: &str) -> bool {
+        self.replay("hapi_process_alive", json!([id]))
+    }
+
+    fn process_stdout(&self, id: &str) -> Option<String> {
+        self.replay("hapi_process_stdout", json!([id]))
+    }
+
+    fn display_assume_control(&self) -> DisplayAssumeControlResult {
+        self.replay("hapi_display_assume_control", json!([]))
+    }
+
+    fn display_override_control(&self) {
+        self.replay("hapi_display_override_control", json!([]))
+    }
+
+    fn display_release_control(&self) -> DisplayReleaseControlResult {
+        self.replay("hapi_display_release_control", json!([]))
+    }
+
+    fn display_displace_control(&self) {
+        self.replay("hapi_display_displace_control", json!([]))
+    }
+
+    fn display_push_stdout(&self) -> DisplayPushStdoutResult {
+        self.replay("hapi_display_push_stdout", json!([]))
+    }
+
+    fn display_set_text(&self, text: &str) -> DisplaySetTextResult {
+        self.replay("hapi_display_set_text", json!([text]))
+    }
+
+    fn display_get_key_buffer(&self) -> Result<i32, DisplayGetKeyBufferError> {
+        self.replay("hapi_display_get_key_buffer", json!([]))
+    }
+
+    fn display_get_key_shift(&self) -> Result<bool, DisplayGetKeyShiftError> {
+        self.replay("hapi_display_get_key_shift", json!([]))
+    }
+
+    fn display_get_key_ctrl(&self) -> Result<bool, DisplayGetKeyCtrlError> {
+        self.replay("hapi_display_get_key_ctrl", json!([]))
+    }
+
+    fn display_clear_key(&self) {
+        self.replay("hapi_display_clear_key", json!([]))
+    }
+
+    fn time_since_unix_epoch(&self) -> f64 {
+        self.replay("hapi_time_since_unix_epoch", json!([]))
+    }
+
+    fn time_since_startup(&self) -> f64 {
+        self.replay("hapi_time_since_startup", json!([]))
+    }
+
+    fn browser_user_agent(&self) -> Option<String> {
+        self.replay("hapi_browser_user_agent", json!([]))
+    }
+
+    fn browser_is_online(&self) -> bool {
+        self.replay("hapi_browser_is_online", json!([]))
+    }
+
+    fn network_request(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        self.replay("hapi_network_request", json!([url, method, headers]))
+    }
+
+    fn network_request_local(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        self.replay("hapi_network_request_local", json!([url, method, headers]))
+    }
+
+    fn network_request_status(&self, id: &str) -> Result<u32, NetworkRequestStatusError> {
+        self.replay("hapi_network_request_status", json!([id]))
+    }
+
+    fn network_request_data(&self, id: &str) -> Option<Vec<u8>> {
+        self.replay("hapi_network_request_data", json!([id]))
+    }
+
+    fn network_request_drop(&self, id: &str) {
+        self.replay("hapi_network_request_drop", json!([id]))
+    }
+
+    fn fs_init_ramfs(&self, label: u8) -> FsInitRamfsResult {
+        self.replay("hapi_fs_init_ramfs", json!([label]))
+    }
+
+    fn fs_file_create(&self, path: &str) -> FsFileCreateResult {
+        self.replay("hapi_fs_file_create", json!([path]))
+    }
+
+    fn fs_file_get(&self, path: &str) -> Result<String, FsFileGetError> {
+        self.replay("hapi_fs_file_get", json!([path]))
+    }
+
+    fn fs_file_write(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        data: &[u8],
+    ) -> FsFileWriteResult {
+        self.replay(
+            "hapi_fs_file_write",
+            json!([fs_label, file_id, offset, to_hex(data)]),
+        )
+    }
+
+    fn fs_file_read(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &mut [u8],
+    ) -> FsFileReadResult {
+        let event = self.next(
+            "hapi_fs_file_read",
+            json!([fs_label, file_id, offset, buffer.len()]),
+        );
+        if let Some(out) = &event.out {
+            let len = out.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&out[..len]);
+        }
+        FsFileReadResult::from_value(&event.ret).unwrap_or(FsFileReadResult::NoSuchFile)
+    }
+
+    fn fs_file_size(&self, fs_label: u8, file_id: &str) -> Result<u32, FsFileSizeError> {
+        self.replay("hapi_fs_file_size", json!([fs_label, file_id]))
+    }
+
+    fn fs_directory_create(&self, path: &str) -> FsDirectoryCreateResult {
+        self.replay("hapi_fs_directory_create", json!([path]))
+    }
+
+    fn fs_directory_get(&self, path: &str) -> Result<String, FsDirectoryGetError> {
+        self.replay("hapi_fs_directory_get", json!([path]))
+    }
+
+    fn fs_directory_list(
+        &self,
+        fs_label: u8,
+        dir_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsDirectoryListError> {
+        let event = self.next(
+            "hapi_fs_directory_list",
+            json!([fs_label, dir_id, buffer.len()]),
+        );
+        if let Some(out) = &event.out {
+            let len = out.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&out[..len]);
+        }
+        Result::from_value(&event.ret).unwrap_or(Err(FsDirectoryListError::NoSuchDirectory))
+    }
+
+    fn fs_file_remove(&self, path: &str) -> FsFileRemoveResult {
+        self.replay("hapi_fs_file_remove", json!([path]))
+    }
+
+    fn fs_directory_remove(&self, path: &str, recursive: u8) -> FsDirectoryRemoveResult {
+        self.replay("hapi_fs_directory_remove", json!([path, recursive]))
+    }
+
+    fn fs_rename(&self, from: &str, to: &str) -> FsRenameResult {
+        self.replay("hapi_fs_rename", json!([from, to]))
+    }
+
+    fn fs_file_copy(&self, from: &str, to: &str) -> Result<u32, FsFileCopyError> {
+        self.replay("hapi_fs_file_copy", json!([from, to]))
+    }
+
+    fn fs_metadata(&self, path: &str, buffer: &mut [u8]) -> Result<u32, FsMetadataError> {
+        let event = self.next("hapi_fs_metadata", json!([path, buffer.len()]));
+        if let Some(out) = &event.out {
+            let len = out.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&out[..len]);
+        }
+        Result::from_value(&event.ret).unwrap_or(Err(FsMetadataError::NotFound))
+    }
+
+    fn fs_set_modified(&self, path: &str, modified: f64) -> FsSetModifiedResult {
+        self.replay("hapi_fs_set_modified", json!([path, modified]))
+    }
+
+    fn fs_file_open(&self, path: &str, flags: u32) -> Result<String, FsFileOpenError> {
+        self.replay("hapi_fs_file_open", json!([path, flags]))
+    }
+
+    fn fs_file_set_len(&self, fs_label: u8, file_id: &str, len: u32) -> FsFileSetLenResult {
+        self.replay("hapi_fs_file_set_len", json!([fs_label, file_id, len]))
+    }
+
+    fn fs_file_append(&self, fs_label: u8, file_id: &str, data: &[u8]) -> FsFileAppendResult {
+        self.replay(
+            "hapi_fs_file_append",
+            json!([fs_label, file_id, to_hex(data)]),
+        )
+    }
+
+    fn fs_init_ramfs_with_capacity(
+        &self,
+        label: u8,
+        capacity: u32,
+    ) -> FsInitRamfsWithCapacityResult {
+        self.replay("hapi_fs_init_ramfs_with_capacity", json!([label, capacity]))
+    }
+
+    fn fs_unmount(&self, label: u8) -> FsUnmountResult {
+        self.replay("hapi_fs_unmount", json!([label]))
+    }
+
+    fn fs_mounts(&self, buffer: &mut [u8]) -> u32 {
+        let event = self.next("hapi_fs_mounts", json!([buffer.len()]));
+        if let Some(out) = &event.out {
+            let len = out.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&out[..len]);
+        }
+        u32::from_value(&event.ret).unwrap_or(0)
+    }
+
+    fn fs_init_persistent(&self, label: u8) -> FsInitPersistentResult {
+        self.replay("hapi_fs_init_persistent", json!([label]))
+    }
+
+    fn fs_init_overlay(&self, label: u8, lower: u8) -> FsInitOverlayResult {
+        self.replay("hapi_fs_init_overlay", json!([label, lower]))
+    }
+
+    fn fs_watch(&self, path: &str, recursive: u8) -> Result<String, FsWatchError> {
+        self.replay("hapi_fs_watch", json!([path, recursive]))
+    }
+
+    fn fs_watch_poll(&self, watcher_id: &str, buffer: &mut [u8]) -> Result<u32, FsWatchPollError> {
+        let event = self.next("hapi_fs_watch_poll", json!([watcher_id, buffer.len()]));
+        if let Some(out) = &event.out {
+            let len = out.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&out[..len]);
+        }
+        Result::from_value(&event.ret).unwrap_or(Err(FsWatchPollError::NoSuchWatcher))
+    }
+
+    fn fs_watch_wait(&self, watcher_id: &str, timeout_ms: u32) -> FsWatchWaitResult {
+        self.replay("hapi_fs_watch_wait", json!([watcher_id, timeout_ms]))
+    }
+
+    fn fs_unwatch(&self, watcher_id: &str) -> FsUnwatchResult {
+        self.replay("hapi_fs_unwatch", json!([watcher_id]))
+    }
+
+    fn fs_file_lock(&self, fs_label: u8, file_id: &str, exclusive: u8) -> FsFileLockResult {
+        self.replay("hapi_fs_file_lock", json!([fs_label, file_id, exclusive]))
+    }
+
+    fn fs_file_lock_wait(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        exclusive: u8,
+        timeout_ms: u32,
+    ) -> FsFileLockWaitResult {
+        self.replay(
+            "hapi_fs_file_lock_wait",
+            json!([fs_label, file_id, exclusive, timeout_ms]),
+        )
+    }
+
+    fn fs_file_unlock(&self, fs_label: u8, file_id: &str) -> FsFileUnlockResult {
+        self.replay("hapi_fs_file_unlock", json!([fs_label, file_id]))
+    }
+
+    fn thread_spawn(&self, f: Box<dyn FnOnce()>) {
+        self.replay::<()>("hapi_thread_spawn", json!([]));
+        f()
+    }
+}
@@ -0,0 +1,825 @@
+//! Tracing of every call made to the host.
+//! Wrap a host in a [`TracingHost`] to log each call with it's arguments, result and duration,
+//! and feed a recorded trace to a [`ReplayHost`](super::replay::ReplayHost) to reproduce it.
+//! ```
+//! # use hapi::{fs::{fslabel::FsLabel, RamFileSystem}, host::trace::TraceSink};
+//! # fn main() -> Result<(), hapi::fs::error::Error> {
+//! // Log every call to the js console
+//! hapi::host::trace::start(TraceSink::Console)?;
+//!
+//! // Or append them to a file as json lines
+//! # RamFileSystem::init(FsLabel::A)?;
+//! hapi::host::trace::start(TraceSink::File("a:/trace.jsonl".into()))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use super::HapiHost;
+use crate::{
+    abi::*,
+    fs::{error::Error, fslabel::FsLabel},
+};
+
+/// A single call to the host
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEvent {
+    /// The name of the import
+    pub call: String,
+    pub args: Vec<Value>,
+    /// The value returned by the host
+    pub ret: Value,
+    /// The bytes the host wrote to an out buffer, for calls that have one
+    pub out: Option<Vec<u8>>,
+    /// The duration of the call in seconds
+    pub duration: f64,
+}
+
+impl TraceEvent {
+    /// Convert the event to json
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "call": self.call,
+            "args": self.args,
+            "ret": self.ret,
+            "duration": self.duration,
+        });
+        if let Some(out) = &self.out {
+            value["out"] = Value::String(to_hex(out));
+        }
+        value
+    }
+
+    /// Parse an event from json
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            call: value.get("call")?.as_str()?.to_owned(),
+            args: value.get("args")?.as_array()?.clone(),
+            ret: value.get("ret").cloned().unwrap_or(Value::Null),
+            out: match value.get("out") {
+                Some(out) => Some(from_hex(out.as_str()?)?),
+                None => None,
+            },
+            duration: value.get("duration").and_then(Value::as_f64).unwrap_or(0.0),
+        })
+    }
+}
+
+/// Parse a trace written as json lines.
+/// Returns the number of the first line that is not a valid event on failure.
+pub fn parse(trace: &str) -> Result<Vec<TraceEvent>, usize> {
+    trace
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .ok()
+                .as_ref()
+                .and_then(TraceEvent::from_json)
+                .ok_or(index + 1)
+        })
+        .collect()
+}
+
+/// Encode bytes as a hex string
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        string.push_str(&format!("{:02x}", byte));
+    }
+    string
+}
+
+/// Decode a hex string
+pub(crate) fn from_hex(string: &str) -> Option<Vec<u8>> {
+    if string.len() % 2 != 0 {
+        return None;
+    }
+    (0..string.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(string.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// A value that can be returned by the host and recorded in a trace
+pub trait TraceValue: Sized {
+    fn to_value(&self) -> Value;
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl TraceValue for () {
+    fn to_value(&self) -> Value {
+        Value::Null
+    }
+
+    fn from_value(_: &Value) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl TraceValue for i32 {
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64()?.try_into().ok()
+    }
+}
+
+impl TraceValue for u32 {
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_u64()?.try_into().ok()
+    }
+}
+
+impl TraceValue for f64 {
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64()
+    }
+}
+
+impl TraceValue for String {
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().map(str::to_owned)
+    }
+}
+
+impl TraceValue for Option<String> {
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            Value::String(string) => Some(Some(string.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl TraceValue for Option<Vec<u8>> {
+    fn to_value(&self) -> Value {
+        match self {
+            Some(bytes) => Value::String(to_hex(bytes)),
+            None => Value::Null,
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            Value::String(string) => Some(Some(from_hex(string)?)),
+            _ => None,
+        }
+    }
+}
+
+impl TraceValue for bool {
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl<C: AbiCode> TraceValue for C {
+    fn to_value(&self) -> Value {
+        json!(self.code())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(C::from_code(i32::from_value(value)?))
+    }
+}
+
+impl<T: TraceValue, E: AbiCode> TraceValue for Result<T, E> {
+    fn to_value(&self) -> Value {
+        match self {
+            Ok(value) => json!({ "ok": value.to_value() }),
+            Err(code) => json!({ "err": code.code() }),
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        if let Some(value) = value.get("ok") {
+            return Some(Ok(T::from_value(value)?));
+        }
+        Some(Err(E::from_code(i32::from_value(value.get("err")?)?)))
+    }
+}
+
+/// Where traced calls are written to
+#[derive(Debug, Clone)]
+pub enum TraceSink {
+    /// Log every call to the js console as info
+    Console,
+    /// Append every call as a json line to a file, creating it if it does not exist
+    File(String),
+    /// Collect the calls in memory
+    Memory(Arc<Mutex<Vec<TraceEvent>>>),
+}
+
+/// An opened sink
+enum Sink {
+    Console,
+    File {
+        fs_label: FsLabel,
+        id: String,
+        offset: Mutex<u32>,
+    },
+    Memory(Arc<Mutex<Vec<TraceEvent>>>),
+}
+
+/// A host that records every call before passing it on to another host
+pub struct TracingHost {
+    inner: Arc<dyn HapiHost>,
+    sink: Sink,
+}
+
+impl TracingHost {
+    /// Trace the calls made to the inner host.
+    /// The sink is written to through the inner host, so writing the trace is not traced itself.
+    pub fn new(inner: Arc<dyn HapiHost>, sink: TraceSink) -> Result<Self, Error> {
+        let sink = match sink {
+            TraceSink::Console => Sink::Console,
+            TraceSink::Memory(events) => Sink::Memory(events),
+            TraceSink::File(path) => {
+                let fs_label = FsLabel::extract_from_path(&path)?;
+                match inner.fs_file_create(&path) {
+                    FsFileCreateResult::Ok | FsFileCreateResult::Exists => {}
+                    _ => return Err(Error::NoSuchDirectory(path)),
+                }
+                let id = inner
+                    .fs_file_get(&path)
+                    .map_err(|_| Error::NoSuchFile(path.clone()))?;
+                let size = inner.fs_file_size(fs_label.into(), &id).unwrap_or(0);
+                Sink::File {
+                    fs_label,
+                    id,
+                    offset: Mutex::new(size),
+                }
+            }
+        };
+        Ok(Self { inner, sink })
+    }
+
+    fn trace<R: TraceValue>(&self, call: &str, args: Value, f: impl FnOnce() -> R) -> R {
+        let start = self.inner.time_since_startup();
+        let ret = f();
+        let duration = self.inner.time_since_startup() - start;
+        self.record(TraceEvent {
+            call: call.to_owned(),
+            args: args.as_array().cloned().unwrap_or_default(),
+            ret: ret.to_value(),
+            out: None,
+            duration,
+        });
+        ret
+    }
+
+    fn record(&self, event: TraceEvent) {
+        match &self.sink {
+            Sink::Console => self
+                .inner
+                .js_console_log_info(&format!("[hapi] {}", event.to_json())),
+            Sink::File {
+                fs_label,
+                id,
+                offset,
+            } => {
+                let line = format!("{}\n", event.to_json());
+                let mut offset = offset.lock().unwrap_or_else(|e| e.into_inner());
+                if self
+                    .inner
+                    .fs_file_write((*fs_label).into(), id, *offset, line.as_bytes())
+                    == FsFileWriteResult::Ok
+                {
+                    *offset += line.len() as u32;
+                }
+            }
+            Sink::Memory(events) => events.lock().unwrap_or_else(|e| e.into_inner()).push(event),
+        }
+    }
+}
+
+/// Start tracing every call made by the process, wrapping the current host
+pub fn start(sink: TraceSink) -> Result<(), Error> {
+    let inner: Arc<dyn HapiHost> = match super::current() {
+        super::HostRef::Default(host) => Arc::new(*host),
+        super::HostRef::Installed(host) => host,
+    };
+    super::install(TracingHost::new(inner, sink)?);
+    Ok(())
+}
+
+impl HapiHost for TracingHost {
+    fn abi_version(&self) -> u32 {
+        self.trace("hapi_abi_version", json!([]), || self.inner.abi_version())
+    }
+
+    fn abi_capabilities(&self) -> u32 {
+        self.trace("hapi_abi_capabilities", json!([]), || {
+            self.inner.abi_capabilities()
+        })
+    }
+
+    fn js_console_log_info(&self, string: &str) {
+        self.trace("hapi_js_console_log_info", json!([string]), || {
+            self.inner.js_console_log_info(string)
+        })
+    }
+
+    fn js_console_log_warn(&self, string: &str) {
+        self.trace("hapi_js_console_log_warn", json!([string]), || {
+            self.inner.js_console_log_warn(string)
+        })
+    }
+
+    fn js_console_log_error(&self, string: &str) {
+        self.trace("hapi_js_console_log_error", json!([string]), || {
+            self.inner.js_console_log_error(string)
+        })
+    }
+
+    fn js_console_eval(&self, source: &str) -> Option<String> {
+        self.trace("hapi_js_console_eval", json!([source]), || {
+            self.inner.js_console_eval(source)
+        })
+    }
+
+    fn stdout_clear(&self) {
+        self.trace("hapi_stdout_clear", json!([]), || self.inner.stdout_clear())
+    }
+
+    fn stdout_clear_line(&self) {
+        self.trace("hapi_stdout_clear_line", json!([]), || {
+            self.inner.stdout_clear_line()
+        })
+    }
+
+    fn stdout_clear_lines(&self, num: u32) {
+        self.trace("hapi_stdout_clear_lines", json!([num]), || {
+            self.inner.stdout_clear_lines(num)
+        })
+    }
+
+    fn stdout_write(&self, string: &str) {
+        self.trace("hapi_stdout_write", json!([string]), || {
+            self.inner.stdout_write(string)
+        })
+    }
+
+    fn process_get_pid(&self) -> Option<String> {
+        self.trace("hapi_process_get_pid", json!([]), || {
+            self.inner.process_get_pid()
+        })
+    }
+
+    fn process_get_cwd(&self) -> Option<String> {
+        self.trace("hapi_process_get_cwd", json!([]), || {
+            self.inner.process_get_cwd()
+        })
+    }
+
+    fn process_set_cwd(&self, path: &str) -> ProcessSetCwdResult {
+        self.trace("hapi_process_set_cwd", json!([path]), || {
+            self.inner.process_set_cwd(path)
+        })
+    }
+
+    fn process_spawn_subprocess(
+        &self,
+        bin: &[u8],
+    ) -> Result<String, ProcessSpawnSubprocessV2Error> {
+        self.trace(
+            "hapi_process_spawn_subprocess",
+            json!([to_hex(bin)]),
+            || self.inner.process_spawn_subprocess(bin),
+        )
+    }
+
+    fn process_alive(&self, id: &str) -> bool {
+        self.trace("hapi_process_alive", json!([id]), || {
+            self.inner.process_alive(id)
+        })
+    }
+
+    fn process_stdout(&self, id: &str) -> Option<String> {
+        self.trace("hapi_process_stdout", json!([id]), || {
+            self.inner.process_stdout(id)
+        })
+    }
+
+    fn display_assume_control(&self) -> DisplayAssumeControlResult {
+        self.trace("hapi_display_assume_control", json!([]), || {
+            self.inner.display_assume_control()
+        })
+    }
+
+    fn display_override_control(&self) {
+        self.trace("hapi_display_override_control", json!([]), || {
+            self.inner.display_override_control()
+        })
+    }
+
+    fn display_release_control(&self) -> DisplayReleaseControlResult {
+        self.trace("hapi_display_release_control", json!([]), || {
+            self.inner.display_release_control()
+        })
+    }
+
+    fn display_displace_control(&self) {
+        self.trace("hapi_display_displace_control", json!([]), || {
+            self.inner.display_displace_control()
+        })
+    }
+
+    fn display_push_stdout(&self) -> DisplayPushStdoutResult {
+        self.trace("hapi_display_push_stdout", json!([]), || {
+            self.inner.display_push_stdout()
+        })
+    }
+
+    fn display_set_text(&self, text: &str) -> DisplaySetTextResult {
+        self.trace("hapi_display_set_text", json!([text]), || {
+            self.inner.display_set_text(text)
+        })
+    }
+
+    fn display_get_key_buffer(&self) -> Result<i32, DisplayGetKeyBufferError> {
+        self.trace("hapi_display_get_key_buffer", json!([]), || {
+            self.inner.display_get_key_buffer()
+        })
+    }
+
+    fn display_get_key_shift(&self) -> Result<bool, DisplayGetKeyShiftError> {
+        self.trace("hapi_display_get_key_shift", json!([]), || {
+            self.inner.display_get_key_shift()
+        })
+    }
+
+    fn display_get_key_ctrl(&self) -> Result<bool, DisplayGetKeyCtrlError> {
+        self.trace("hapi_display_get_key_ctrl", json!([]), || {
+            self.inner.display_get_key_ctrl()
+        })
+    }
+
+    fn display_clear_key(&self) {
+        self.trace("hapi_display_clear_key", json!([]), || {
+            self.inner.display_clear_key()
+        })
+    }
+
+    fn time_since_unix_epoch(&self) -> f64 {
+        self.trace("hapi_time_since_unix_epoch", json!([]), || {
+            self.inner.time_since_unix_epoch()
+        })
+    }
+
+    fn time_since_startup(&self) -> f64 {
+        self.trace("hapi_time_since_startup", json!([]), || {
+            self.inner.time_since_startup()
+        })
+    }
+
+    fn browser_user_agent(&self) -> Option<String> {
+        self.trace("hapi_browser_user_agent", json!([]), || {
+            self.inner.browser_user_agent()
+        })
+    }
+
+    fn browser_is_online(&self) -> bool {
+        self.trace("hapi_browser_is_online", json!([]), || {
+            self.inner.browser_is_online()
+        })
+    }
+
+    fn network_request(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        self.trace(
+            "hapi_network_request",
+            json!([url, method, headers]),
+            || self.inner.network_request(url, method, headers),
+        )
+    }
+
+    fn network_request_local(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        self.trace(
+            "hapi_network_request_local",
+            json!([url, method, headers]),
+            || self.inner.network_request_local(url, method, headers),
+        )
+    }
+
+    fn network_request_status(&self, id: &str) -> Result<u32, NetworkRequestStatusError> {
+        self.trace("hapi_network_request_status", json!([id]), || {
+            self.inner.network_request_status(id)
+        })
+    }
+
+    fn network_request_data(&self, id: &str) -> Option<Vec<u8>> {
+        self.trace("hapi_network_request_data", json!([id]), || {
+            self.inner.network_request_data(id)
+        })
+    }
+
+    fn network_request_drop(&self, id: &str) {
+        self.trace("hapi_network_request_drop", json!([id]), || {
+            self.inner.network_request_drop(id)
+        })
+    }
+
+    fn fs_init_ramfs(&self, label: u8) -> FsInitRamfsResult {
+        self.trace("hapi_fs_init_ramfs", json!([label]), || {
+            self.inner.fs_init_ramfs(label)
+        })
+    }
+
+    fn fs_file_create(&self, path: &str) -> FsFileCreateResult {
+        self.trace("hapi_fs_file_create", json!([path]), || {
+            self.inner.fs_file_create(path)
+        })
+    }
+
+    fn fs_file_get(&self, path: &str) -> Result<String, FsFileGetError> {
+        self.trace("hapi_fs_file_get", json!([path]), || {
+            self.inner.fs_file_get(path)
+        })
+    }
+
+    fn fs_file_write(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        data: &[u8],
+    ) -> FsFileWriteResult {
+        self.trace(
+            "hapi_fs_file_write",
+            json!([fs_label, file_id, offset, to_hex(data)]),
+            || self.inner.fs_file_write(fs_label, file_id, offset, data),
+        )
+    }
+
+    fn fs_file_read(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &mut [u8],
+    ) -> FsFileReadResult {
+        let start = self.inner.time_since_startup();
+        let ret = self.inner.fs_file_read(fs_label, file_id, offset, buffer);
+        let duration = self.inner.time_since_startup() - start;
+        self.record(TraceEvent {
+            call: "hapi_fs_file_read".to_owned(),
+            args: vec![
+                json!(fs_label),
+                json!(file_id),
+                json!(offset),
+                json!(buffer.len()),
+            ],
+            ret: ret.to_value(),
+            out: Some(buffer.to_vec()),
+            duration,
+        });
+        ret
+    }
+
+    fn fs_file_size(&self, fs_label: u8, file_id: &str) -> Result<u32, FsFileSizeError> {
+        self.trace("hapi_fs_file_size", json!([fs_label, file_id]), || {
+            self.inner.fs_file_size(fs_label, file_id)
+        })
+    }
+
+    fn fs_directory_create(&self, path: &str) -> FsDirectoryCreateResult {
+        self.trace("hapi_fs_directory_create", json!([path]), || {
+            self.inner.fs_directory_create(path)
+        })
+    }
+
+    fn fs_directory_get(&self, path: &str) -> Result<String, FsDirectoryGetError> {
+        self.trace("hapi_fs_directory_get", json!([path]), || {
+            self.inner.fs_directory_get(path)
+        })
+    }
+
+    fn fs_directory_list(
+        &self,
+        fs_label: u8,
+        dir_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsDirectoryListError> {
+        let start = self.inner.time_since_startup();
+        let ret = self.inner.fs_directory_list(fs_label, dir_id, buffer);
+        let duration = self.inner.time_since_startup() - start;
+        self.record(TraceEvent {
+            call: "hapi_fs_directory_list".to_owned(),
+            args: vec![json!(fs_label), json!(dir_id), json!(buffer.len())],
+            ret: ret.to_value(),
+            out: Some(buffer.to_vec()),
+            duration,
+        });
+        ret
+    }
+
+    fn fs_file_remove(&self, path: &str) -> FsFileRemoveResult {
+        self.trace("hapi_fs_file_remove", json!([path]), || {
+            self.inner.fs_file_remove(path)
+        })
+    }
+
+    fn fs_directory_remove(&self, path: &str, recursive: u8) -> FsDirectoryRemoveResult {
+        self.trace("hapi_fs_directory_remove", json!([path, recursive]), || {
+            self.inner.fs_directory_remove(path, recursive)
+        })
+    }
+
+    fn fs_rename(&self, from: &str, to: &str) -> FsRenameResult {
+        self.trace("hapi_fs_rename", json!([from, to]), || {
+            self.inner.fs_rename(from, to)
+        })
+    }
+
+    fn fs_file_copy(&self, from: &str, to: &str) -> Result<u32, FsFileCopyError> {
+        self.trace("hapi_fs_file_copy", json!([from, to]), || {
+            self.inner.fs_file_copy(from, to)
+        })
+    }
+
+    fn fs_metadata(&self, path: &str, buffer: &mut [u8]) -> Result<u32, FsMetadataError> {
+        let start = self.inner.time_since_startup();
+        let ret = self.inner.fs_metadata(path, buffer);
+        let duration = self.inner.time_since_startup() - start;
+        self.record(TraceEvent {
+            call: "hapi_fs_metadata".to_owned(),
+            args: vec![json!(path), json!(buffer.len())],
+            ret: ret.to_value(),
+            out: Some(buffer.to_vec()),
+            duration,
+        });
+        ret
+    }
+
+    fn fs_set_modified(&self, path: &str, modified: f64) -> FsSetModifiedResult {
+        self.trace("hapi_fs_set_modified", json!([path, modified]), || {
+            self.inner.fs_set_modified(path, modified)
+        })
+    }
+
+    fn fs_file_open(&self, path: &str, flags: u32) -> Result<String, FsFileOpenError> {
+        self.trace("hapi_fs_file_open", json!([path, flags]), || {
+            self.inner.fs_file_open(path, flags)
+        })
+    }
+
+    fn fs_file_set_len(&self, fs_label: u8, file_id: &str, len: u32) -> FsFileSetLenResult {
+        self.trace(
+            "hapi_fs_file_set_len",
+            json!([fs_label, file_id, len]),
+            || self.inner.fs_file_set_len(fs_label, file_id, len),
+        )
+    }
+
+    fn fs_file_append(&self, fs_label: u8, file_id: &str, data: &[u8]) -> FsFileAppendResult {
+        self.trace(
+            "hapi_fs_file_append",
+            json!([fs_label, file_id, to_hex(data)]),
+            || self.inner.fs_file_append(fs_label, file_id, data),
+        )
+    }
+
+    fn fs_init_ramfs_with_capacity(
+        &self,
+        label: u8,
+        capacity: u32,
+    ) -> FsInitRamfsWithCapacityResult {
+        self.trace(
+            "hapi_fs_init_ramfs_with_capacity",
+            json!([label, capacity]),
+            || self.inner.fs_init_ramfs_with_capacity(label, capacity),
+        )
+    }
+
+    fn fs_unmount(&self, label: u8) -> FsUnmountResult {
+        self.trace("hapi_fs_unmount", json!([label]), || {
+            self.inner.fs_unmount(label)
+        })
+    }
+
+    fn fs_mounts(&self, buffer: &mut [u8]) -> u32 {
+        let start = self.inner.time_since_startup();
+        let ret = self.inner.fs_mounts(buffer);
+        let duration = self.inner.time_since_startup() - start;
+        self.record(TraceEvent {
+            call: "hapi_fs_mounts".to_owned(),
+            args: vec![json!(buffer.len())],
+            ret: ret.to_value(),
+            out: Some(buffer.to_vec()),
+            duration,
+        });
+        ret
+    }
+
+    fn fs_init_persistent(&self, label: u8) -> FsInitPersistentResult {
+        self.trace("hapi_fs_init_persistent", json!([label]), || {
+            self.inner.fs_init_persistent(label)
+        })
+    }
+
+    fn fs_init_overlay(&self, label: u8, lower: u8) -> FsInitOverlayResult {
+        self.trace("hapi_fs_init_overlay", json!([label, lower]), || {
+            self.inner.fs_init_overlay(label, lower)
+        })
+    }
+
+    fn fs_watch(&self, path: &str, recursive: u8) -> Result<String, FsWatchError> {
+        self.trace("hapi_fs_watch", json!([path, recursive]), || {
+            self.inner.fs_watch(path, recursive)
+        })
+    }
+
+    fn fs_watch_poll(&self, watcher_id: &str, buffer: &mut [u8]) -> Result<u32, FsWatchPollError> {
+        let start = self.inner.time_since_startup();
+        let ret = self.inner.fs_watch_poll(watcher_id, buffer);
+        let duration = self.inner.time_since_startup() - start;
+        self.record(TraceEvent {
+            call: "hapi_fs_watch_poll".to_owned(),
+            args: vec![json!(watcher_id), json!(buffer.len())],
+            ret: ret.to_value(),
+            out: Some(buffer.to_vec()),
+            duration,
+        });
+        ret
+    }
+
+    fn fs_watch_wait(&self, watcher_id: &str, timeout_ms: u32) -> FsWatchWaitResult {
+        self.trace(
+            "hapi_fs_watch_wait",
+            json!([watcher_id, timeout_ms]),
+            || self.inner.fs_watch_wait(watcher_id, timeout_ms),
+        )
+    }
+
+    fn fs_unwatch(&self, watcher_id: &str) -> FsUnwatchResult {
+        self.trace("hapi_fs_unwatch", json!([watcher_id]), || {
+            self.inner.fs_unwatch(watcher_id)
+        })
+    }
+
+    fn fs_file_lock(&self, fs_label: u8, file_id: &str, exclusive: u8) -> FsFileLockResult {
+        self.trace(
+            "hapi_fs_file_lock",
+            json!([fs_label, file_id, exclusive]),
+            || self.inner.fs_file_lock(fs_label, file_id, exclusive),
+        )
+    }
+
+    fn fs_file_lock_wait(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        exclusive: u8,
+        timeout_ms: u32,
+    ) -> FsFileLockWaitResult {
+        self.trace(
+            "hapi_fs_file_lock_wait",
+            json!([fs_label, file_id, exclusive, timeout_ms]),
+            || {
+                self.inner
+                    .fs_file_lock_wait(fs_label, file_id, exclusive, timeout_ms)
+            },
+        )
+    }
+
+    fn fs_file_unlock(&self, fs_label: u8, file_id: &str) -> FsFileUnlockResult {
+        self.trace("hapi_fs_file_unlock", json!([fs_label, file_id]), || {
+            self.inner.fs_file_unlock(fs_label, file_id)
+        })
+    }
+
+    fn thread_spawn(&self, f: Box<dyn FnOnce()>) {
+        self.trace("hapi_thread_spawn", json!([]), || {
+            self.inner.thread_spawn(f)
+        })
+    }
+}
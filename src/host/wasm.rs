@@ -0,0 +1,742 @@
+use alloc::ffi::CString;
+#[cfg(feature = "thread")]
+use core::ffi::c_void;
+use core::ffi::{c_char, CStr};
+#[cfg(not(feature = "sim"))]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::HapiHost;
+// Only the codes of the enabled subsystems are used
+#[allow(unused_imports)]
+use crate::abi::*;
+use crate::prelude::*;
+
+/// The abi version that added the `_v2` imports, which take strings as a pointer and a length
+const STR_V2: u32 = 3;
+
+/// The default host. Calls the `hapi` wasm imports.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WasmHost;
+
+/// Take ownership of a null-terminated string allocated by the host and free it.
+/// # Safety
+/// The pointer must be `NULL` or a null-terminated string allocated with `hapi_mem_alloc`.
+unsafe fn take_string(ptr: *const u8) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    // # Safety
+    // Since we know for certain the string is null terminated, there is no way to access unallocated memory
+    let string = CStr::from_ptr(ptr as *const c_char)
+        .to_string_lossy()
+        .to_string();
+
+    // # Safety
+    // Since we know the string was allocated by the host, we know it is safe to free
+    crate::mem::free(ptr as *mut u8);
+    Some(string)
+}
+
+/// Whether the host implements the `_v2` imports.
+/// Older hosts are given null-terminated copies of the strings instead.
+/// They still have to define the `_v2` imports as stubs for the module to load,
+/// so a real v1 kernel, which has neither them nor `hapi_abi_version`, never gets here.
+fn has_v2() -> bool {
+    // The version of the simulated kernel can be changed at any time, so it is not cached
+    #[cfg(feature = "sim")]
+    let version = unsafe { crate::ffi::hapi_abi_version() };
+    #[cfg(not(feature = "sim"))]
+    let version = {
+        static VERSION: AtomicU32 = AtomicU32::new(0);
+        match VERSION.load(Ordering::Relaxed) {
+            0 => {
+                let version = unsafe { crate::ffi::hapi_abi_version() };
+                VERSION.store(version, Ordering::Relaxed);
+                version
+            }
+            version => version,
+        }
+    };
+    version >= STR_V2
+}
+
+/// Copy a string for a v1 import, cutting it off at the first null byte
+fn cstring_lossy(string: &str) -> CString {
+    let end = string.find('\0').unwrap_or(string.len());
+    // There are no null bytes left in the string
+    CString::new(&string[..end]).unwrap()
+}
+
+/// Read an id written by an import into a 37 byte buffer
+#[cfg(any(feature = "process", feature = "fs"))]
+fn read_id(f: impl FnOnce(*mut u8) -> i32) -> Result<String, i32> {
+    crate::handle::Handle::<()>::read(f).map(|handle| handle.to_string())
+}
+
+/// Decode a value returned by an import, which is negative on failure
+#[cfg(any(feature = "display", feature = "network", feature = "fs"))]
+fn value<E: AbiCode>(result: i32) -> Result<i32, E> {
+    match result {
+        code if code < 0 => Err(E::from_code(code)),
+        value => Ok(value),
+    }
+}
+
+impl HapiHost for WasmHost {
+    fn abi_version(&self) -> u32 {
+        unsafe { crate::ffi::hapi_abi_version() }
+    }
+
+    fn abi_capabilities(&self) -> u32 {
+        unsafe { crate::ffi::hapi_abi_capabilities() }
+    }
+
+    #[cfg(feature = "js")]
+    fn js_console_log_info(&self, string: &str) {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_js_console_log_info_v2(string.as_ptr(), string.len() as u32)
+            };
+        }
+        let cstring = cstring_lossy(string);
+        unsafe { crate::ffi::hapi_js_console_log_info(cstring.as_ptr() as *const u8) }
+    }
+
+    #[cfg(feature = "js")]
+    fn js_console_log_warn(&self, string: &str) {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_js_console_log_warn_v2(string.as_ptr(), string.len() as u32)
+            };
+        }
+        let cstring = cstring_lossy(string);
+        unsafe { crate::ffi::hapi_js_console_log_warn(cstring.as_ptr() as *const u8) }
+    }
+
+    #[cfg(feature = "js")]
+    fn js_console_log_error(&self, string: &str) {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_js_console_log_error_v2(string.as_ptr(), string.len() as u32)
+            };
+        }
+        let cstring = cstring_lossy(string);
+        unsafe { crate::ffi::hapi_js_console_log_error(cstring.as_ptr() as *const u8) }
+    }
+
+    #[cfg(feature = "js")]
+    fn js_console_eval(&self, source: &str) -> Option<String> {
+        if has_v2() {
+            return unsafe {
+                take_string(crate::ffi::hapi_js_console_eval_v2(
+                    source.as_ptr(),
+                    source.len() as u32,
+                ))
+            };
+        }
+        let cstring = CString::new(source).ok()?;
+        unsafe {
+            take_string(crate::ffi::hapi_js_console_eval(
+                cstring.as_ptr() as *const u8
+            ))
+        }
+    }
+
+    fn stdout_clear(&self) {
+        // # Safety
+        // stdout_clear is garunteed to be safe by honeyos's implementation.
+        // If the honeyos kernel is non-standard, there are no safety garuntees and this might be unsound
+        unsafe { crate::ffi::hapi_stdout_clear() }
+    }
+
+    fn stdout_clear_line(&self) {
+        // # Safety
+        // stdout_clear is garunteed to be safe by honeyos's implementation.
+        // If the honeyos kernel is non-standard, there are no safety garuntees and this might be unsound
+        unsafe { crate::ffi::hapi_stdout_clear_line() }
+    }
+
+    fn stdout_clear_lines(&self, num: u32) {
+        // # Safety
+        // stdout_clear is garunteed to be safe by honeyos's implementation.
+        // If the honeyos kernel is non-standard, there are no safety garuntees and this might be unsound
+        unsafe { crate::ffi::hapi_stdout_clear_lines(num) }
+    }
+
+    fn stdout_write(&self, string: &str) {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_stdout_write_v2(string.as_ptr(), string.len() as u32)
+            };
+        }
+        let cstring = cstring_lossy(string);
+        // # Safety
+        // Since the string is garunteed to hae a null terminator, we are garunteed not to write unallocated memory
+        unsafe { crate::ffi::hapi_stdout_write(cstring.as_ptr() as *const u8) }
+    }
+
+    #[cfg(feature = "process")]
+    fn process_get_pid(&self) -> Option<String> {
+        read_id(|buffer| {
+            unsafe { crate::ffi::hapi_process_get_pid(buffer) };
+            0
+        })
+        .ok()
+    }
+
+    #[cfg(feature = "process")]
+    fn process_get_cwd(&self) -> Option<String> {
+        let cwd_length = unsafe { crate::ffi::hapi_process_get_cwd_length() } as usize;
+        let mut cwd_buf = vec![0u8; cwd_length];
+
+        unsafe { crate::ffi::hapi_process_get_cwd(cwd_buf.as_mut_ptr()) };
+
+        let string = CString::from_vec_with_nul(cwd_buf).ok()?;
+        Some(string.to_string_lossy().to_string())
+    }
+
+    #[cfg(feature = "process")]
+    fn process_set_cwd(&self, path: &str) -> ProcessSetCwdResult {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_process_set_cwd_v2(path.as_ptr(), path.len() as u32)
+            }
+            .into();
+        }
+        let Ok(cstring) = CString::new(path) else {
+            return ProcessSetCwdResult::InvalidPath;
+        };
+        unsafe { crate::ffi::hapi_process_set_cwd(cstring.as_ptr() as *const u8) }.into()
+    }
+
+    #[cfg(feature = "process")]
+    fn process_spawn_subprocess(
+        &self,
+        bin: &[u8],
+    ) -> Result<String, ProcessSpawnSubprocessV2Error> {
+        read_id(|buffer| unsafe {
+            crate::ffi::hapi_process_spawn_subprocess_v2(bin.as_ptr(), bin.len() as u32, buffer)
+        })
+        .map_err(Into::into)
+    }
+
+    #[cfg(feature = "process")]
+    fn process_alive(&self, id: &str) -> bool {
+        let Ok(id_cstr) = CString::new(id) else {
+            return false;
+        };
+        unsafe { crate::ffi::hapi_process_alive(id_cstr.as_ptr() as *const u8) > 0 }
+    }
+
+    #[cfg(feature = "process")]
+    fn process_stdout(&self, id: &str) -> Option<String> {
+        let id_cstr = CString::new(id).ok()?;
+
+        let length =
+            unsafe { crate::ffi::hapi_process_stdout_length(id_cstr.as_ptr() as *const u8) };
+        if length <= 0 {
+            return None;
+        }
+        let mut stdout_buf = vec![0u8; length as usize];
+
+        unsafe {
+            crate::ffi::hapi_process_stdout(id_cstr.as_ptr() as *const u8, stdout_buf.as_mut_ptr())
+        };
+
+        let string = CString::from_vec_with_nul(stdout_buf).ok()?;
+        Some(string.to_string_lossy().to_string())
+    }
+
+    #[cfg(feature = "display")]
+    fn display_assume_control(&self) -> DisplayAssumeControlResult {
+        unsafe { crate::ffi::hapi_display_assume_control() }.into()
+    }
+
+    #[cfg(feature = "display")]
+    fn display_override_control(&self) {
+        unsafe { crate::ffi::hapi_display_override_control() }
+    }
+
+    #[cfg(feature = "display")]
+    fn display_release_control(&self) -> DisplayReleaseControlResult {
+        unsafe { crate::ffi::hapi_display_release_control() }.into()
+    }
+
+    #[cfg(feature = "display")]
+    fn display_displace_control(&self) {
+        unsafe { crate::ffi::hapi_display_displace_control() }
+    }
+
+    #[cfg(feature = "display")]
+    fn display_push_stdout(&self) -> DisplayPushStdoutResult {
+        unsafe { crate::ffi::hapi_display_push_stdout() }.into()
+    }
+
+    #[cfg(feature = "display")]
+    fn display_set_text(&self, text: &str) -> DisplaySetTextResult {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_display_set_text_v2(text.as_ptr(), text.len() as u32)
+            }
+            .into();
+        }
+        let Ok(text_cstr) = CString::new(text) else {
+            return DisplaySetTextResult::InvalidString;
+        };
+        unsafe { crate::ffi::hapi_display_set_text(text_cstr.as_ptr() as *const u8) }.into()
+    }
+
+    #[cfg(feature = "display")]
+    fn display_get_key_buffer(&self) -> Result<i32, DisplayGetKeyBufferError> {
+        value(unsafe { crate::ffi::hapi_display_get_key_buffer() })
+    }
+
+    #[cfg(feature = "display")]
+    fn display_get_key_shift(&self) -> Result<bool, DisplayGetKeyShiftError> {
+        value(unsafe { crate::ffi::hapi_display_get_key_shift() }).map(|shift| shift > 0)
+    }
+
+    #[cfg(feature = "display")]
+    fn display_get_key_ctrl(&self) -> Result<bool, DisplayGetKeyCtrlError> {
+        value(unsafe { crate::ffi::hapi_display_get_key_ctrl() }).map(|ctrl| ctrl > 0)
+    }
+
+    #[cfg(feature = "display")]
+    fn display_clear_key(&self) {
+        unsafe { crate::ffi::hapi_display_clear_key() }
+    }
+
+    fn time_since_unix_epoch(&self) -> f64 {
+        unsafe { crate::ffi::hapi_time_since_unix_epoch() }
+    }
+
+    fn time_since_startup(&self) -> f64 {
+        unsafe { crate::ffi::hapi_time_since_startup() }
+    }
+
+    fn browser_user_agent(&self) -> Option<String> {
+        // # Safety
+        // Since we check for failed allocations, there is no way to access unallocated memory.
+        unsafe { take_string(crate::ffi::hapi_browser_user_agent()) }
+    }
+
+    fn browser_is_online(&self) -> bool {
+        unsafe { crate::ffi::hapi_browser_is_online() > 0 }
+    }
+
+    #[cfg(feature = "network")]
+    fn network_request(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        if has_v2() {
+            return unsafe {
+                take_string(crate::ffi::hapi_network_request_v2(
+                    url.as_ptr(),
+                    url.len() as u32,
+                    method,
+                    headers.as_ptr(),
+                    headers.len() as u32,
+                ))
+            };
+        }
+        let url_cstring = CString::new(url).ok()?;
+        let headers_cstring = CString::new(headers).ok()?;
+
+        // # Safety
+        // Since the id is garunteed to have a null terminator, there is no way of accessing unallocated memory.
+        unsafe {
+            take_string(crate::ffi::hapi_network_request(
+                url_cstring.as_ptr() as *const u8,
+                method,
+                headers_cstring.as_ptr() as *const u8,
+            ))
+        }
+    }
+
+    #[cfg(feature = "network")]
+    fn network_request_local(&self, url: &str, method: u32, headers: &str) -> Option<String> {
+        if has_v2() {
+            return unsafe {
+                take_string(crate::ffi::hapi_network_request_local_v2(
+                    url.as_ptr(),
+                    url.len() as u32,
+                    method,
+                    headers.as_ptr(),
+                    headers.len() as u32,
+                ))
+            };
+        }
+        let url_cstring = CString::new(url).ok()?;
+        let headers_cstring = CString::new(headers).ok()?;
+
+        // # Safety
+        // Since the id is garunteed to have a null terminator, there is no way of accessing unallocated memory.
+        unsafe {
+            take_string(crate::ffi::hapi_network_request_local(
+                url_cstring.as_ptr() as *const u8,
+                method,
+                headers_cstring.as_ptr() as *const u8,
+            ))
+        }
+    }
+
+    #[cfg(feature = "network")]
+    fn network_request_status(&self, id: &str) -> Result<u32, NetworkRequestStatusError> {
+        let id_cstr = CString::new(id).map_err(|_| NetworkRequestStatusError::NoSuchRequest)?;
+        value(unsafe { crate::ffi::hapi_network_request_status(id_cstr.as_ptr() as *const u8) })
+            .map(|status| status as u32)
+    }
+
+    #[cfg(feature = "network")]
+    fn network_request_data(&self, id: &str) -> Option<Vec<u8>> {
+        let id_cstr = CString::new(id).ok()?;
+
+        let ptr = unsafe { crate::ffi::hapi_network_request_data(id_cstr.as_ptr() as *const u8) };
+        if ptr.is_null() {
+            return None;
+        }
+
+        let len =
+            unsafe { crate::ffi::hapi_network_request_data_length(id_cstr.as_ptr() as *const u8) };
+        let data = unsafe { core::slice::from_raw_parts(ptr, len.max(0) as usize) }.to_vec();
+
+        // # Safety
+        // Since we know the data was allocated by the host, we know it is safe to free
+        unsafe { crate::mem::free(ptr as *mut u8) };
+        Some(data)
+    }
+
+    #[cfg(feature = "network")]
+    fn network_request_drop(&self, id: &str) {
+        let Ok(id_cstr) = CString::new(id) else {
+            return;
+        };
+        unsafe { crate::ffi::hapi_network_request_drop(id_cstr.as_ptr() as *const u8) }
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_init_ramfs(&self, label: u8) -> FsInitRamfsResult {
+        unsafe { crate::ffi::hapi_fs_init_ramfs(label) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_create(&self, path: &str) -> FsFileCreateResult {
+        if has_v2() {
+            return unsafe { crate::ffi::hapi_fs_file_create_v2(path.as_ptr(), path.len() as u32) }
+                .into();
+        }
+        let Ok(path_cstr) = CString::new(path) else {
+            return FsFileCreateResult::InvalidPath;
+        };
+        unsafe { crate::ffi::hapi_fs_file_create(path_cstr.as_ptr() as *const u8) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_get(&self, path: &str) -> Result<String, FsFileGetError> {
+        if has_v2() {
+            return read_id(|buffer| unsafe {
+                crate::ffi::hapi_fs_file_get_v2(path.as_ptr(), path.len() as u32, buffer)
+            })
+            .map_err(Into::into);
+        }
+        let path_cstr = CString::new(path).map_err(|_| FsFileGetError::NotFound)?;
+        read_id(|buffer| unsafe {
+            crate::ffi::hapi_fs_file_get(path_cstr.as_ptr() as *const u8, buffer)
+        })
+        .map_err(Into::into)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_write(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        data: &[u8],
+    ) -> FsFileWriteResult {
+        let Ok(id_cstr) = CString::new(file_id) else {
+            return FsFileWriteResult::NoSuchFile;
+        };
+        unsafe {
+            crate::ffi::hapi_fs_file_write(
+                fs_label,
+                id_cstr.as_ptr() as *const u8,
+                offset,
+                data.len() as u32,
+                data.as_ptr(),
+            )
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_read(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &mut [u8],
+    ) -> FsFileReadResult {
+        let Ok(id_cstr) = CString::new(file_id) else {
+            return FsFileReadResult::NoSuchFile;
+        };
+        unsafe {
+            crate::ffi::hapi_fs_file_read(
+                fs_label,
+                id_cstr.as_ptr() as *const u8,
+                offset,
+                buffer.len() as u32,
+                buffer.as_mut_ptr(),
+            )
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_size(&self, fs_label: u8, file_id: &str) -> Result<u32, FsFileSizeError> {
+        let id_cstr = CString::new(file_id).map_err(|_| FsFileSizeError::NoSuchFile)?;
+        value(unsafe { crate::ffi::hapi_fs_file_size(fs_label, id_cstr.as_ptr() as *const u8) })
+            .map(|size| size as u32)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_directory_create(&self, path: &str) -> FsDirectoryCreateResult {
+        if has_v2() {
+            return unsafe {
+                crate::ffi::hapi_fs_directory_create_v2(path.as_ptr(), path.len() as u32)
+            }
+            .into();
+        }
+        let Ok(path_cstr) = CString::new(path) else {
+            return FsDirectoryCreateResult::InvalidPath;
+        };
+        unsafe { crate::ffi::hapi_fs_directory_create(path_cstr.as_ptr() as *const u8) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_directory_get(&self, path: &str) -> Result<String, FsDirectoryGetError> {
+        if has_v2() {
+            return read_id(|buffer| unsafe {
+                crate::ffi::hapi_fs_directory_get_v2(path.as_ptr(), path.len() as u32, buffer)
+            })
+            .map_err(Into::into);
+        }
+        let path_cstr = CString::new(path).map_err(|_| FsDirectoryGetError::NotFound)?;
+        read_id(|buffer| unsafe {
+            crate::ffi::hapi_fs_directory_get(path_cstr.as_ptr() as *const u8, buffer)
+        })
+        .map_err(Into::into)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_directory_list(
+        &self,
+        fs_label: u8,
+        dir_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsDirectoryListError> {
+        value(unsafe {
+            crate::ffi::hapi_fs_directory_list(
+                fs_label,
+                dir_id.as_ptr(),
+                dir_id.len() as u32,
+                buffer.len() as u32,
+                buffer.as_mut_ptr(),
+            )
+        })
+        .map(|len| len as u32)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_remove(&self, path: &str) -> FsFileRemoveResult {
+        unsafe { crate::ffi::hapi_fs_file_remove(path.as_ptr(), path.len() as u32) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_directory_remove(&self, path: &str, recursive: u8) -> FsDirectoryRemoveResult {
+        unsafe { crate::ffi::hapi_fs_directory_remove(path.as_ptr(), path.len() as u32, recursive) }
+            .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_rename(&self, from: &str, to: &str) -> FsRenameResult {
+        unsafe {
+            crate::ffi::hapi_fs_rename(
+                from.as_ptr(),
+                from.len() as u32,
+                to.as_ptr(),
+                to.len() as u32,
+            )
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_copy(&self, from: &str, to: &str) -> Result<u32, FsFileCopyError> {
+        value(unsafe {
+            crate::ffi::hapi_fs_file_copy(
+                from.as_ptr(),
+                from.len() as u32,
+                to.as_ptr(),
+                to.len() as u32,
+            )
+        })
+        .map(|len| len as u32)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_metadata(&self, path: &str, buffer: &mut [u8]) -> Result<u32, FsMetadataError> {
+        value(unsafe {
+            crate::ffi::hapi_fs_metadata(
+                path.as_ptr(),
+                path.len() as u32,
+                buffer.len() as u32,
+                buffer.as_mut_ptr(),
+            )
+        })
+        .map(|len| len as u32)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_set_modified(&self, path: &str, modified: f64) -> FsSetModifiedResult {
+        unsafe { crate::ffi::hapi_fs_set_modified(path.as_ptr(), path.len() as u32, modified) }
+            .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_open(&self, path: &str, flags: u32) -> Result<String, FsFileOpenError> {
+        read_id(|buffer| unsafe {
+            crate::ffi::hapi_fs_file_open(path.as_ptr(), path.len() as u32, flags, buffer)
+        })
+        .map_err(Into::into)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_set_len(&self, fs_label: u8, file_id: &str, len: u32) -> FsFileSetLenResult {
+        unsafe {
+            crate::ffi::hapi_fs_file_set_len(fs_label, file_id.as_ptr(), file_id.len() as u32, len)
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_append(&self, fs_label: u8, file_id: &str, data: &[u8]) -> FsFileAppendResult {
+        unsafe {
+            crate::ffi::hapi_fs_file_append(
+                fs_label,
+                file_id.as_ptr(),
+                file_id.len() as u32,
+                data.len() as u32,
+                data.as_ptr(),
+            )
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_init_ramfs_with_capacity(
+        &self,
+        label: u8,
+        capacity: u32,
+    ) -> FsInitRamfsWithCapacityResult {
+        unsafe { crate::ffi::hapi_fs_init_ramfs_with_capacity(label, capacity) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_unmount(&self, label: u8) -> FsUnmountResult {
+        unsafe { crate::ffi::hapi_fs_unmount(label) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_mounts(&self, buffer: &mut [u8]) -> u32 {
+        unsafe { crate::ffi::hapi_fs_mounts(buffer.len() as u32, buffer.as_mut_ptr()) as u32 }
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_init_persistent(&self, label: u8) -> FsInitPersistentResult {
+        unsafe { crate::ffi::hapi_fs_init_persistent(label) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_init_overlay(&self, label: u8, lower: u8) -> FsInitOverlayResult {
+        unsafe { crate::ffi::hapi_fs_init_overlay(label, lower) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_watch(&self, path: &str, recursive: u8) -> Result<String, FsWatchError> {
+        read_id(|buffer| unsafe {
+            crate::ffi::hapi_fs_watch(path.as_ptr(), path.len() as u32, recursive, buffer)
+        })
+        .map_err(Into::into)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_watch_poll(&self, watcher_id: &str, buffer: &mut [u8]) -> Result<u32, FsWatchPollError> {
+        value(unsafe {
+            crate::ffi::hapi_fs_watch_poll(
+                watcher_id.as_ptr(),
+                watcher_id.len() as u32,
+                buffer.len() as u32,
+                buffer.as_mut_ptr(),
+            )
+        })
+        .map(|len| len as u32)
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_watch_wait(&self, watcher_id: &str, timeout_ms: u32) -> FsWatchWaitResult {
+        unsafe {
+            crate::ffi::hapi_fs_watch_wait(watcher_id.as_ptr(), watcher_id.len() as u32, timeout_ms)
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_unwatch(&self, watcher_id: &str) -> FsUnwatchResult {
+        unsafe { crate::ffi::hapi_fs_unwatch(watcher_id.as_ptr(), watcher_id.len() as u32) }.into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_lock(&self, fs_label: u8, file_id: &str, exclusive: u8) -> FsFileLockResult {
+        unsafe {
+            crate::ffi::hapi_fs_file_lock(
+                fs_label,
+                file_id.as_ptr(),
+                file_id.len() as u32,
+                exclusive,
+            )
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_lock_wait(
+        &self,
+        fs_label: u8,
+        file_id: &str,
+        exclusive: u8,
+        timeout_ms: u32,
+    ) -> FsFileLockWaitResult {
+        unsafe {
+            crate::ffi::hapi_fs_file_lock_wait(
+                fs_label,
+                file_id.as_ptr(),
+                file_id.len() as u32,
+                exclusive,
+                timeout_ms,
+            )
+        }
+        .into()
+    }
+
+    #[cfg(feature = "fs")]
+    fn fs_file_unlock(&self, fs_label: u8, file_id: &str) -> FsFileUnlockResult {
+        unsafe { crate::ffi::hapi_fs_file_unlock(fs_label, file_id.as_ptr(), file_id.len() as u32) }
+            .into()
+    }
+
+    #[cfg(feature = "thread")]
+    fn thread_spawn(&self, f: Box<dyn FnOnce()>) {
+        let ptr = Box::into_raw(Box::new(f));
+        unsafe { crate::ffi::hapi_thread_spawn(ptr as *const c_void) }
+    }
+}
@@ -1,4 +1,5 @@
-use std::ffi::{CStr, CString};
+#[cfg(feature = "logger")]
+use crate::prelude::*;
 
 /// Logs to the js console
 #[cfg(feature = "logger")]
@@ -37,27 +38,24 @@ impl ::log::Log for JsConsoleLogger {
 }
 
 pub mod log {
-    use std::ffi::CString;
+    use crate::prelude::*;
 
     /// Log a string to the js console as info
     pub fn info(string: impl Into<String>) {
         let string: String = string.into();
-        let cstring = CString::new(string.clone()).unwrap();
-        unsafe { crate::ffi::hapi_js_console_log_info(cstring.as_ptr() as *const u8) }
+        crate::host::current().js_console_log_info(&string)
     }
 
     /// Log a string to the js console as a warning
     pub fn warn(string: impl Into<String>) {
         let string: String = string.into();
-        let cstring = CString::new(string.clone()).unwrap();
-        unsafe { crate::ffi::hapi_js_console_log_warn(cstring.as_ptr() as *const u8) }
+        crate::host::current().js_console_log_warn(&string)
     }
 
     /// Log a string to the js console as an error
     pub fn error(string: impl Into<String>) {
         let string: String = string.into();
-        let cstring = CString::new(string.clone()).unwrap();
-        unsafe { crate::ffi::hapi_js_console_log_error(cstring.as_ptr() as *const u8) }
+        crate::host::current().js_console_log_error(&string)
     }
 }
 
@@ -65,22 +63,6 @@ pub mod log {
 /// Returns the result as a string
 /// Returns None if the code could not be evaluated
 pub fn eval(source: &str) -> Option<serde_json::Value> {
-    let cstring = CString::new(source).unwrap();
-    let ptr = unsafe { crate::ffi::hapi_js_console_eval(cstring.as_ptr() as *const u8) };
-
-    if ptr == std::ptr::null() {
-        return None;
-    }
-
-    // # Safety
-    // Since we know for certain the string is null terminated, there is no way to access unallocated memory
-    let cstring = unsafe { CStr::from_ptr(ptr as *mut i8) };
-    let string = cstring.to_string_lossy().to_string();
-
-    // Free the string from memory
-    // # Safety
-    // Since we know the string was allocated by the hapi_js_console_eval function, we know it is safe to free
-    unsafe { crate::mem::free(ptr as *mut u8) };
-
+    let string = crate::host::current().js_console_eval(source)?;
     serde_json::from_str(&string).ok()
 }
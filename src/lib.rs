@@ -1,15 +1,54 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// The items of the std prelude that come from `alloc`, for the `no_std` build.
+/// With `std` they are already in scope.
+#[allow(unused_imports)]
+mod prelude {
+    pub use alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+}
+
+pub mod abi;
 pub mod browser;
+#[cfg(feature = "display")]
 pub mod display;
+pub mod error;
 pub mod ffi;
+#[cfg(feature = "fs")]
 pub mod fs;
+#[cfg(feature = "async")]
 pub mod futures;
+pub mod handle;
+pub mod host;
+#[cfg(feature = "js")]
 pub mod js;
 pub mod mem;
+#[cfg(feature = "network")]
 pub mod network;
+#[cfg(feature = "process")]
 pub mod process;
+#[doc(hidden)]
+pub mod rt;
+#[cfg(feature = "sim")]
+pub mod sim;
 pub mod stdout;
+#[cfg(feature = "thread")]
 pub mod thread;
 pub mod time;
+#[cfg(feature = "display")]
 pub mod util;
 
+pub use error::{Error, ErrorKind, Result};
 pub use hapi_proc::main;
+
+/// Runs the examples in the readme as doctests
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
@@ -1,15 +1,16 @@
-use std::ffi::{CStr, CString};
+use crate::{handle::Handle, prelude::*};
 
 /// A network error
 #[derive(Debug)]
 pub enum NetworkError {
-    InvalidRequestId(CString),
+    InvalidRequestId(Handle<Request>),
     InvalidHeaders,
+    InvalidId(String),
     InvalidRequestStatus(u32),
-    RequestFailure(CString),
-    StillProcessing(CString),
-    StillPending(CString),
-    AllocFailure(CString),
+    RequestFailure(Handle<Request>),
+    StillProcessing(Handle<Request>),
+    StillPending(Handle<Request>),
+    AllocFailure(Handle<Request>),
 }
 
 /// The status of a request
@@ -36,8 +37,8 @@ pub enum RequestMethod {
 }
 
 /// Represents a request
-#[derive(Debug)]
-pub struct Request(CString);
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Request(Handle<Request>);
 
 impl Request {
     /// Create a new network request
@@ -50,28 +51,13 @@ impl Request {
         let headers: String = headers.into();
         let method: u32 = method.into();
 
-        let url_cstring = CString::new(url).unwrap();
-        let headers_cstring = CString::new(headers).unwrap();
-
-        let id = unsafe {
-            crate::ffi::hapi_network_request(
-                url_cstring.as_ptr() as *const u8,
-                method,
-                headers_cstring.as_ptr() as *const u8,
-            )
-        };
-
-        if id == std::ptr::null() {
-            return Err(NetworkError::InvalidHeaders);
-        }
-
-        // # Safety
-        // Since the pid is garunteed to have a null terminator, there is no way of accessing unallocated memory.
-        let id_str = unsafe { CStr::from_ptr(id as *const i8) };
-        let id_str = id_str.to_owned();
+        let id = crate::host::current()
+            .network_request(&url, method, &headers)
+            .ok_or(NetworkError::InvalidHeaders)?;
 
-        unsafe { crate::mem::free(id as *mut u8) };
-        Ok(Request(id_str))
+        Handle::parse(&id)
+            .map(Request)
+            .map_err(|_| NetworkError::InvalidId(id))
     }
 
     // /// Create a new network request to the local server
@@ -94,7 +80,7 @@ impl Request {
     //         )
     //     };
 
-    //     if pid == std::ptr::null() {
+    //     if pid.is_null() {
     //         return Err(NetworkError::InvalidHeaders);
     //     }
 
@@ -106,37 +92,22 @@ impl Request {
 
     /// Check the status of the request
     pub fn status(&self) -> Result<RequestStatus, NetworkError> {
-        let status =
-            unsafe { crate::ffi::hapi_network_request_status(self.0.as_ptr() as *const u8) };
-        if status <= -1 {
-            return Err(NetworkError::InvalidRequestId(self.0.clone()));
-        }
-        let status = status as u32;
-        status.try_into()
+        crate::host::current()
+            .network_request_status(self.id())
+            .map_err(|_| NetworkError::InvalidRequestId(self.0))?
+            .try_into()
     }
 
     /// Get the data of the request
     pub fn data(&self) -> Result<Vec<u8>, NetworkError> {
         let status = self.status()?;
         match status {
-            RequestStatus::Processing => Err(NetworkError::StillProcessing(self.0.clone())),
-            RequestStatus::Fail => Err(NetworkError::RequestFailure(self.0.clone())),
-            RequestStatus::Pending => Err(NetworkError::StillPending(self.0.clone())),
-            RequestStatus::Success => {
-                let ptr =
-                    unsafe { crate::ffi::hapi_network_request_data(self.0.as_ptr() as *const u8) };
-
-                if ptr == std::ptr::null() {
-                    return Err(NetworkError::AllocFailure(self.0.clone()));
-                }
-
-                let len = unsafe {
-                    crate::ffi::hapi_network_request_data_length(self.0.as_ptr() as *const u8)
-                };
-                let slice = unsafe { std::slice::from_raw_parts(ptr, len as usize) };
-
-                Ok(slice.to_vec())
-            }
+            RequestStatus::Processing => Err(NetworkError::StillProcessing(self.0)),
+            RequestStatus::Fail => Err(NetworkError::RequestFailure(self.0)),
+            RequestStatus::Pending => Err(NetworkError::StillPending(self.0)),
+            RequestStatus::Success => crate::host::current()
+                .network_request_data(self.id())
+                .ok_or(NetworkError::AllocFailure(self.0)),
         }
     }
 
@@ -154,39 +125,43 @@ impl Request {
 
     /// Get the id
     pub fn id(&self) -> &str {
-        self.0.as_c_str().to_str().unwrap()
+        self.0.as_str()
     }
 }
 
 impl Drop for Request {
     fn drop(&mut self) {
-        unsafe { crate::ffi::hapi_network_request_drop(self.0.as_ptr() as *const u8) }
+        crate::host::current().network_request_drop(self.id())
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for NetworkError {}
 
-impl std::fmt::Display for NetworkError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             NetworkError::InvalidRequestId(id) => {
-                writeln!(f, "The request {:?} does not or no longer exists", id)
+                write!(f, "The request \"{}\" does not or no longer exists", id)
             }
-            NetworkError::InvalidHeaders => writeln!(f, "Unable to parse request headers"),
+            NetworkError::InvalidHeaders => write!(f, "Unable to parse request headers"),
+            NetworkError::InvalidId(id) => write!(f, "The host returned an invalid id: {}", id),
             NetworkError::InvalidRequestStatus(v) => {
-                writeln!(f, "Request status of {} is invalid", v)
+                write!(f, "Request status of {} is invalid", v)
+            }
+            NetworkError::RequestFailure(id) => {
+                write!(f, "Request \"{}\" failed to complete", id)
             }
-            NetworkError::RequestFailure(id) => writeln!(f, "Request {:?} failed to complete", id),
             NetworkError::StillProcessing(id) => {
-                writeln!(f, "Request {:?} is still being processed", id)
+                write!(f, "Request \"{}\" is still being processed", id)
             }
             NetworkError::StillPending(id) => {
-                writeln!(f, "Request {:?} is still pending", id)
+                write!(f, "Request \"{}\" is still pending", id)
             }
             NetworkError::AllocFailure(id) => {
-                writeln!(
+                write!(
                     f,
-                    "Failed to allocate memory to hold data of request {:?}",
+                    "Failed to allocate memory to hold data of request \"{}\"",
                     id
                 )
             }
@@ -194,9 +169,9 @@ impl std::fmt::Display for NetworkError {
     }
 }
 
-impl Into<u32> for RequestMethod {
-    fn into(self) -> u32 {
-        match self {
+impl From<RequestMethod> for u32 {
+    fn from(method: RequestMethod) -> u32 {
+        match method {
             RequestMethod::Get => 0,
             RequestMethod::Head => 1,
             RequestMethod::Post => 2,
@@ -210,9 +185,9 @@ impl Into<u32> for RequestMethod {
     }
 }
 
-impl Into<u32> for RequestStatus {
-    fn into(self) -> u32 {
-        match self {
+impl From<RequestStatus> for u32 {
+    fn from(status: RequestStatus) -> u32 {
+        match status {
             RequestStatus::Processing => 0,
             RequestStatus::Success => 1,
             RequestStatus::Fail => 2,
@@ -1,92 +1,76 @@
-use std::ffi::CString;
+use crate::{
+    abi::ProcessSetCwdResult,
+    error::{Error, ErrorKind, Result},
+    handle::Handle,
+    prelude::*,
+};
 
 /// Get the process id
-pub fn pid() -> Option<String> {
-    const PID_LENGTH: usize = std::mem::size_of::<u8>() * 37;
-    let mut pid_buf = vec![0u8; PID_LENGTH];
-
-    unsafe { crate::ffi::hapi_process_get_pid(&mut pid_buf[0] as *mut u8) };
-
-    let string = CString::from_vec_with_nul(pid_buf).ok()?;
-    let string = string.to_string_lossy().to_string();
-    Some(string)
+pub fn pid() -> Result<String> {
+    crate::host::current()
+        .process_get_pid()
+        .ok_or(Error::new(ErrorKind::InvalidData, "hapi_process_get_pid"))
 }
 
 /// Get the current working directory
-pub fn cwd() -> Option<String> {
-    let cwd_length = unsafe { crate::ffi::hapi_process_get_cwd_length() } as usize;
-    let mut cwd_buf = vec![0u8; cwd_length];
-
-    unsafe { crate::ffi::hapi_process_get_cwd(&mut cwd_buf[0] as *mut u8) };
-
-    let string = CString::from_vec_with_nul(cwd_buf).ok()?;
-    let string = string.to_string_lossy().to_string();
-
-    Some(string)
+pub fn cwd() -> Result<String> {
+    crate::host::current()
+        .process_get_cwd()
+        .ok_or(Error::new(ErrorKind::InvalidData, "hapi_process_get_cwd"))
 }
 
-/// Set the current working directory
-pub fn set_cwd(wd: &str) {
-    let cstring = CString::new(wd).unwrap();
-    unsafe { crate::ffi::hapi_process_set_cwd(cstring.as_ptr() as *const u8) };
+/// Set the current working directory.
+/// With the `fs` feature relative paths are joined onto the current one, and `.` and `..` are resolved.
+/// ### Errors
+/// - `ErrorKind::InvalidInput` When the path is invalid
+pub fn set_cwd(wd: impl AsRef<str>) -> Result<()> {
+    let wd = wd.as_ref();
+    #[cfg(feature = "fs")]
+    let wd = crate::fs::HoneyPath::new(wd).resolve()?;
+
+    match crate::host::current().process_set_cwd(wd.as_ref()) {
+        ProcessSetCwdResult::Ok => Ok(()),
+        _ => Err(Error::new(ErrorKind::InvalidInput, "hapi_process_set_cwd")),
+    }
 }
 
 /// Represents a process
-pub struct Process(String);
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Process(Handle<Process>);
 
 impl Process {
     /// Spawn a wasm binary as a subprocess and return it's pid
-    pub fn spawn_sub(bin: &[u8]) -> Option<Self> {
-        let bin = bin.to_vec();
-
-        const PID_LENGTH: usize = std::mem::size_of::<u8>() * 37;
-        let mut pid_buf = vec![0u8; PID_LENGTH];
-
-        unsafe {
-            crate::ffi::hapi_process_spawn_subprocess(
-                bin.as_ptr(),
-                bin.len() as u32,
-                &mut pid_buf[0] as *mut u8,
-            )
-        };
-
-        let string = CString::from_vec_with_nul(pid_buf).ok()?;
-        let string = string.to_string_lossy().to_string();
-
-        Some(Self(string))
+    pub fn spawn_sub(bin: &[u8]) -> Result<Self> {
+        const CALL: &str = "hapi_process_spawn_subprocess";
+        let pid = crate::host::current()
+            .process_spawn_subprocess(bin)
+            .map_err(|_| Error::new(ErrorKind::SpawnFailed, CALL))?;
+        Handle::parse(&pid)
+            .map(Self)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, CALL))
     }
 
     /// Return the pid
     pub fn pid(&self) -> String {
-        self.0.clone()
+        self.0.to_string()
+    }
+
+    /// Get the handle
+    pub fn handle(&self) -> Handle<Process> {
+        self.0
     }
 
     /// Check if the process is alive
     pub fn alive(&self) -> bool {
-        let id = &self.0;
-        let id_cstr = CString::new(id.clone()).unwrap();
-        let alive = unsafe { crate::ffi::hapi_process_alive(id_cstr.as_ptr() as *const u8) };
-        alive > 0
+        crate::host::current().process_alive(self.0.as_str())
     }
 
     /// Fetch the stdout of the process
-    pub fn stdout(&self) -> Option<String> {
-        let id = &self.0;
-        let id_cstr = CString::new(id.clone()).unwrap();
-
-        const PID_LENGTH: usize = std::mem::size_of::<u8>() * 37;
-        let mut pid_buf = vec![0u8; PID_LENGTH];
-
-        unsafe {
-            crate::ffi::hapi_process_stdout(
-                id_cstr.as_ptr() as *const u8,
-                &mut pid_buf[0] as *mut u8,
-            )
-        };
-
-        let string = CString::from_vec_with_nul(pid_buf).ok()?;
-        let string = string.to_string_lossy().to_string();
-
-        Some(string)
+    /// ### Errors
+    /// - `ErrorKind::NotFound` When the process does not exist
+    pub fn stdout(&self) -> Result<String> {
+        crate::host::current()
+            .process_stdout(self.0.as_str())
+            .ok_or(Error::new(ErrorKind::NotFound, "hapi_process_stdout"))
     }
 }
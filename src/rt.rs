@@ -0,0 +1,26 @@
+//! Support for the entrypoints generated by [`hapi::main`](crate::main).
+//! Not part of the public api.
+
+use crate::prelude::*;
+
+/// Print panics to stdout, and to the js console with the `logger` feature.
+/// Without `std` panics are handled by the app's `#[panic_handler]`.
+pub fn set_panic_hook() {
+    #[cfg(feature = "std")]
+    std::panic::set_hook(Box::new(|info| {
+        let message = info.to_string();
+
+        #[cfg(feature = "logger")]
+        crate::js::log::error(message.as_str());
+
+        crate::println!("\x1b[91m{}\x1b[97m", message);
+    }));
+}
+
+/// Run a function passed to `hapi_thread_spawn`
+/// # Safety
+/// The pointer must be a `Box<dyn FnOnce()>` leaked by the thread host.
+pub unsafe fn thread_entrypoint(f_ptr: u32) {
+    let f = unsafe { Box::from_raw(f_ptr as *mut Box<dyn FnOnce()>) };
+    (*f)();
+}
@@ -0,0 +1,35 @@
+use std::time::{Duration, SystemTime};
+
+/// The simulated clock.
+/// Time only moves forward when advanced, so tests are deterministic.
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    /// The seconds since the unix epoch at startup
+    pub epoch: f64,
+    /// The seconds since startup
+    pub elapsed: f64,
+}
+
+impl SimClock {
+    /// Advance the clock
+    pub fn advance(&mut self, duration: Duration) {
+        self.elapsed += duration.as_secs_f64();
+    }
+
+    /// The seconds since the unix epoch
+    pub fn since_unix_epoch(&self) -> f64 {
+        self.epoch + self.elapsed
+    }
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        let epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            epoch: epoch.as_secs_f64(),
+            elapsed: 0.0,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use crate::util::keys::KeyCode;
+
+/// A key press waiting in the simulated key buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimKey {
+    pub key: i32,
+    pub shift: bool,
+    pub ctrl: bool,
+}
+
+/// The simulated display.
+/// Unlike honeyos's single key buffer, key presses are queued so tests can type several keys at once.
+/// The front of the queue acts as the key buffer and is popped by `hapi_display_clear_key`.
+#[derive(Debug, Clone, Default)]
+pub struct SimDisplay {
+    /// The pid of the process in control of the display
+    pub owner: Option<String>,
+    /// The contents of the text-mode buffer
+    pub text: String,
+    pub keys: VecDeque<SimKey>,
+}
+
+impl SimDisplay {
+    /// Queue a key press
+    pub fn press(&mut self, key: KeyCode, shift: bool, ctrl: bool) {
+        self.keys.push_back(SimKey {
+            key: key.into(),
+            shift,
+            ctrl,
+        });
+    }
+
+    /// Queue a key press for every character in the string
+    pub fn type_str(&mut self, string: &str) {
+        for c in string.chars() {
+            if let Some((key, shift)) = key_for_char(c) {
+                self.press(key, shift, false);
+            }
+        }
+    }
+
+    /// Whether the process is in control of the display
+    pub fn controlled_by(&self, pid: &str) -> bool {
+        self.owner.as_deref() == Some(pid)
+    }
+}
+
+/// Find the key and shift state that produce the character
+fn key_for_char(c: char) -> Option<(KeyCode, bool)> {
+    if c == '\n' {
+        return Some((KeyCode::Enter, false));
+    }
+    (0..256).map(KeyCode::from).find_map(|key| {
+        if key.to_char(false) == Some(c) {
+            Some((key, false))
+        } else if key.to_char(true) == Some(c) {
+            Some((key, true))
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,740 @@
+//! Native implementations of the `hapi` imports.
+//! The signatures match the extern block in `crate::ffi`, which re-exports these when the `sim` feature is enabled.
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    ffi::{c_char, CStr},
+    os::raw::c_void,
+};
+
+use crate::abi::*;
+
+use super::{mem, wait_for, with_imports, with_kernel};
+
+/// Read a null-terminated string. Returns None if the string is null or not utf-8.
+unsafe fn read_str<'a>(ptr: *const u8) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr as *const c_char).to_str().ok()
+}
+
+/// Read a string from a pointer and a length. Returns None if the string is not utf-8.
+unsafe fn read_str_len<'a>(ptr: *const u8, len: u32) -> Option<&'a str> {
+    std::str::from_utf8(slice(ptr, len)).ok()
+}
+
+/// Write a string and it's null terminator to the buffer
+unsafe fn write_str(buffer: *mut u8, string: &str) {
+    if buffer.is_null() {
+        return;
+    }
+    std::ptr::copy_nonoverlapping(string.as_ptr(), buffer, string.len());
+    buffer.add(string.len()).write(0);
+}
+
+/// Write the id to the buffer and return `0`, or return the code
+unsafe fn write_id<E: AbiCode>(buffer: *mut u8, result: Result<String, E>) -> i32 {
+    match result {
+        Ok(id) => {
+            write_str(buffer, &id);
+            0
+        }
+        Err(code) => code.code(),
+    }
+}
+
+/// Get a buffer from a pointer and a length
+unsafe fn slice<'a>(ptr: *const u8, len: u32) -> &'a [u8] {
+    if len == 0 {
+        return &[];
+    }
+    std::slice::from_raw_parts(ptr, len as usize)
+}
+
+/// Encode a value, or the code it failed with
+fn value<T: Into<i64>, E: AbiCode>(result: Result<T, E>) -> i32 {
+    match result {
+        Ok(value) => value.into() as i32,
+        Err(code) => code.code(),
+    }
+}
+
+pub unsafe fn hapi_abi_version() -> u32 {
+    with_imports(|imports| imports.abi_version())
+}
+
+pub unsafe fn hapi_abi_capabilities() -> u32 {
+    with_imports(|imports| imports.abi_capabilities())
+}
+
+pub unsafe fn hapi_js_console_log_info(string: *const u8) {
+    let string = read_str(string).unwrap_or_default();
+    with_imports(|imports| imports.js_console_log_info(string))
+}
+
+pub unsafe fn hapi_js_console_log_info_v2(string: *const u8, string_len: u32) {
+    let string = read_str_len(string, string_len).unwrap_or_default();
+    with_imports(|imports| imports.js_console_log_info(string))
+}
+
+pub unsafe fn hapi_js_console_log_warn(string: *const u8) {
+    let string = read_str(string).unwrap_or_default();
+    with_imports(|imports| imports.js_console_log_warn(string))
+}
+
+pub unsafe fn hapi_js_console_log_warn_v2(string: *const u8, string_len: u32) {
+    let string = read_str_len(string, string_len).unwrap_or_default();
+    with_imports(|imports| imports.js_console_log_warn(string))
+}
+
+pub unsafe fn hapi_js_console_log_error(string: *const u8) {
+    let string = read_str(string).unwrap_or_default();
+    with_imports(|imports| imports.js_console_log_error(string))
+}
+
+pub unsafe fn hapi_js_console_log_error_v2(string: *const u8, string_len: u32) {
+    let string = read_str_len(string, string_len).unwrap_or_default();
+    with_imports(|imports| imports.js_console_log_error(string))
+}
+
+pub unsafe fn hapi_js_console_eval(source: *const u8) -> *const u8 {
+    let Some(source) = read_str(source) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.js_console_eval(source)) {
+        Some(result) => mem::alloc_string(&result),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_js_console_eval_v2(source: *const u8, source_len: u32) -> *const u8 {
+    let Some(source) = read_str_len(source, source_len) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.js_console_eval(source)) {
+        Some(result) => mem::alloc_string(&result),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_stdout_clear() {
+    with_imports(|imports| imports.stdout_clear())
+}
+
+pub unsafe fn hapi_stdout_clear_line() {
+    with_imports(|imports| imports.stdout_clear_line())
+}
+
+pub unsafe fn hapi_stdout_clear_lines(num: u32) {
+    with_imports(|imports| imports.stdout_clear_lines(num))
+}
+
+pub unsafe fn hapi_stdout_write(string: *const u8) {
+    let string = read_str(string).unwrap_or_default();
+    with_imports(|imports| imports.stdout_write(string))
+}
+
+pub unsafe fn hapi_stdout_write_v2(string: *const u8, string_len: u32) {
+    let string = read_str_len(string, string_len).unwrap_or_default();
+    with_imports(|imports| imports.stdout_write(string))
+}
+
+pub unsafe fn hapi_process_get_pid(buffer: *mut u8) {
+    let pid = with_imports(|imports| imports.process_get_pid());
+    write_str(buffer, &pid);
+}
+
+pub unsafe fn hapi_process_get_cwd(buffer: *mut u8) {
+    let cwd = with_imports(|imports| imports.process_get_cwd());
+    write_str(buffer, &cwd);
+}
+
+pub unsafe fn hapi_process_get_cwd_length() -> u32 {
+    with_imports(|imports| imports.process_get_cwd_length())
+}
+
+pub unsafe fn hapi_process_set_cwd(path: *const u8) -> i32 {
+    let Some(path) = read_str(path) else {
+        return ProcessSetCwdResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.process_set_cwd(path)).code()
+}
+
+pub unsafe fn hapi_process_set_cwd_v2(path: *const u8, path_len: u32) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return ProcessSetCwdResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.process_set_cwd(path)).code()
+}
+
+pub unsafe fn hapi_process_spawn_subprocess(bin: *const u8, bin_len: u32, pid_out: *mut u8) {
+    let bin = slice(bin, bin_len);
+    let pid = with_imports(|imports| imports.process_spawn_subprocess(bin));
+    write_str(pid_out, &pid);
+}
+
+pub unsafe fn hapi_process_spawn_subprocess_v2(
+    bin: *const u8,
+    bin_len: u32,
+    pid_out: *mut u8,
+) -> i32 {
+    let bin = slice(bin, bin_len);
+    write_id(
+        pid_out,
+        with_imports(|imports| imports.process_spawn_subprocess_v2(bin)),
+    )
+}
+
+pub unsafe fn hapi_process_alive(id: *const u8) -> i32 {
+    let Some(id) = read_str(id) else {
+        return 0;
+    };
+    with_imports(|imports| imports.process_alive(id)) as i32
+}
+
+pub unsafe fn hapi_process_stdout(id: *const u8, out_buffer: *mut u8) {
+    let Some(id) = read_str(id) else {
+        return;
+    };
+    let stdout = with_imports(|imports| {
+        imports
+            .process_stdout_length(id)
+            .map(|_| imports.process_stdout(id))
+    });
+    if let Ok(stdout) = stdout {
+        write_str(out_buffer, &stdout);
+    }
+}
+
+pub unsafe fn hapi_process_stdout_length(id: *const u8) -> i32 {
+    let Some(id) = read_str(id) else {
+        return ProcessStdoutLengthError::NoSuchProcess.code();
+    };
+    value(with_imports(|imports| imports.process_stdout_length(id)))
+}
+
+pub unsafe fn hapi_mem_alloc(size: u32) -> *mut c_void {
+    mem::alloc(size as usize)
+}
+
+pub unsafe fn hapi_mem_realloc(ptr: *mut c_void, size: u32) -> *mut c_void {
+    mem::realloc(ptr, size as usize)
+}
+
+pub unsafe fn hapi_mem_free(ptr: *mut c_void) {
+    mem::free(ptr)
+}
+
+pub unsafe fn hapi_display_assume_control() -> i32 {
+    with_imports(|imports| imports.display_assume_control()).code()
+}
+
+pub unsafe fn hapi_display_override_control() {
+    with_imports(|imports| imports.display_override_control())
+}
+
+pub unsafe fn hapi_display_release_control() -> i32 {
+    with_imports(|imports| imports.display_release_control()).code()
+}
+
+pub unsafe fn hapi_display_displace_control() {
+    with_imports(|imports| imports.display_displace_control())
+}
+
+pub unsafe fn hapi_display_push_stdout() -> i32 {
+    with_imports(|imports| imports.display_push_stdout()).code()
+}
+
+pub unsafe fn hapi_display_set_text(text: *const u8) -> i32 {
+    let Some(text) = read_str(text) else {
+        return DisplaySetTextResult::InvalidString.code();
+    };
+    with_imports(|imports| imports.display_set_text(text)).code()
+}
+
+pub unsafe fn hapi_display_set_text_v2(text: *const u8, text_len: u32) -> i32 {
+    let Some(text) = read_str_len(text, text_len) else {
+        return DisplaySetTextResult::InvalidString.code();
+    };
+    with_imports(|imports| imports.display_set_text(text)).code()
+}
+
+pub unsafe fn hapi_display_get_key_buffer() -> i32 {
+    value(with_imports(|imports| imports.display_get_key_buffer()))
+}
+
+pub unsafe fn hapi_display_get_key_shift() -> i32 {
+    value(with_imports(|imports| imports.display_get_key_shift()))
+}
+
+pub unsafe fn hapi_display_get_key_ctrl() -> i32 {
+    value(with_imports(|imports| imports.display_get_key_ctrl()))
+}
+
+pub unsafe fn hapi_display_clear_key() {
+    with_imports(|imports| imports.display_clear_key())
+}
+
+pub unsafe fn hapi_time_since_unix_epoch() -> f64 {
+    with_imports(|imports| imports.time_since_unix_epoch())
+}
+
+pub unsafe fn hapi_time_since_startup() -> f64 {
+    with_imports(|imports| imports.time_since_startup())
+}
+
+pub unsafe fn hapi_browser_user_agent() -> *const u8 {
+    match with_imports(|imports| imports.browser_user_agent()) {
+        Some(user_agent) => mem::alloc_string(&user_agent),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_browser_user_agent_length() -> u32 {
+    with_imports(|imports| imports.browser_user_agent_length())
+}
+
+pub unsafe fn hapi_browser_is_online() -> u32 {
+    with_imports(|imports| imports.browser_is_online()) as u32
+}
+
+pub unsafe fn hapi_network_request(url: *const u8, method: u32, headers: *const u8) -> *const u8 {
+    let (Some(url), Some(headers)) = (read_str(url), read_str(headers)) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.network_request(url, method, headers)) {
+        Some(id) => mem::alloc_string(&id),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_network_request_v2(
+    url: *const u8,
+    url_len: u32,
+    method: u32,
+    headers: *const u8,
+    headers_len: u32,
+) -> *const u8 {
+    let (Some(url), Some(headers)) = (
+        read_str_len(url, url_len),
+        read_str_len(headers, headers_len),
+    ) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.network_request(url, method, headers)) {
+        Some(id) => mem::alloc_string(&id),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_network_request_local(
+    url: *const u8,
+    method: u32,
+    headers: *const u8,
+) -> *const u8 {
+    let (Some(url), Some(headers)) = (read_str(url), read_str(headers)) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.network_request_local(url, method, headers)) {
+        Some(id) => mem::alloc_string(&id),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_network_request_local_v2(
+    url: *const u8,
+    url_len: u32,
+    method: u32,
+    headers: *const u8,
+    headers_len: u32,
+) -> *const u8 {
+    let (Some(url), Some(headers)) = (
+        read_str_len(url, url_len),
+        read_str_len(headers, headers_len),
+    ) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.network_request_local(url, method, headers)) {
+        Some(id) => mem::alloc_string(&id),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_network_request_status(id: *const u8) -> i32 {
+    let Some(id) = read_str(id) else {
+        return NetworkRequestStatusError::NoSuchRequest.code();
+    };
+    value(with_imports(|imports| imports.network_request_status(id)))
+}
+
+pub unsafe fn hapi_network_request_data_length(id: *const u8) -> i32 {
+    let Some(id) = read_str(id) else {
+        return NetworkRequestDataLengthError::NoSuchRequest.code();
+    };
+    value(with_imports(|imports| {
+        imports.network_request_data_length(id)
+    }))
+}
+
+pub unsafe fn hapi_network_request_data(id: *const u8) -> *const u8 {
+    let Some(id) = read_str(id) else {
+        return std::ptr::null();
+    };
+    match with_imports(|imports| imports.network_request_data(id)) {
+        Some(data) => mem::alloc_bytes(&data),
+        None => std::ptr::null(),
+    }
+}
+
+pub unsafe fn hapi_network_request_drop(id: *const u8) {
+    if let Some(id) = read_str(id) {
+        with_imports(|imports| imports.network_request_drop(id))
+    }
+}
+
+pub unsafe fn hapi_fs_init_ramfs(label: u8) -> i32 {
+    with_imports(|imports| imports.fs_init_ramfs(label)).code()
+}
+
+pub unsafe fn hapi_fs_file_create(path: *const u8) -> i32 {
+    let Some(path) = read_str(path) else {
+        return FsFileCreateResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_file_create(path)).code()
+}
+
+pub unsafe fn hapi_fs_file_create_v2(path: *const u8, path_len: u32) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsFileCreateResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_file_create(path)).code()
+}
+
+pub unsafe fn hapi_fs_file_get(path: *const u8, id_buf: *mut u8) -> i32 {
+    let Some(path) = read_str(path) else {
+        return FsFileGetError::NotFound.code();
+    };
+    write_id(id_buf, with_imports(|imports| imports.fs_file_get(path)))
+}
+
+pub unsafe fn hapi_fs_file_get_v2(path: *const u8, path_len: u32, id_buf: *mut u8) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsFileGetError::NotFound.code();
+    };
+    write_id(id_buf, with_imports(|imports| imports.fs_file_get(path)))
+}
+
+pub unsafe fn hapi_fs_file_write(
+    fs_label: u8,
+    file_id: *const u8,
+    offset: u32,
+    size: u32,
+    buffer: *const u8,
+) -> i32 {
+    let Some(file_id) = read_str(file_id) else {
+        return FsFileWriteResult::NoSuchFile.code();
+    };
+    let buffer = slice(buffer, size);
+    with_imports(|imports| imports.fs_file_write(fs_label, file_id, offset, buffer)).code()
+}
+
+pub unsafe fn hapi_fs_file_read(
+    fs_label: u8,
+    file_id: *const u8,
+    offset: u32,
+    size: u32,
+    buffer: *mut u8,
+) -> i32 {
+    let Some(file_id) = read_str(file_id) else {
+        return FsFileReadResult::NoSuchFile.code();
+    };
+    let buffer = if size == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(buffer, size as usize)
+    };
+    with_imports(|imports| imports.fs_file_read(fs_label, file_id, offset, buffer)).code()
+}
+
+pub unsafe fn hapi_fs_file_size(fs_label: u8, file_id: *const u8) -> i32 {
+    let Some(file_id) = read_str(file_id) else {
+        return FsFileSizeError::NoSuchFile.code();
+    };
+    value(with_imports(|imports| {
+        imports.fs_file_size(fs_label, file_id)
+    }))
+}
+
+pub unsafe fn hapi_fs_directory_create(path: *const u8) -> i32 {
+    let Some(path) = read_str(path) else {
+        return FsDirectoryCreateResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_directory_create(path)).code()
+}
+
+pub unsafe fn hapi_fs_directory_create_v2(path: *const u8, path_len: u32) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsDirectoryCreateResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_directory_create(path)).code()
+}
+
+pub unsafe fn hapi_fs_directory_get(path: *const u8, id_buf: *mut u8) -> i32 {
+    let Some(path) = read_str(path) else {
+        return FsDirectoryGetError::NotFound.code();
+    };
+    write_id(
+        id_buf,
+        with_imports(|imports| imports.fs_directory_get(path)),
+    )
+}
+
+pub unsafe fn hapi_fs_directory_get_v2(path: *const u8, path_len: u32, id_buf: *mut u8) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsDirectoryGetError::NotFound.code();
+    };
+    write_id(
+        id_buf,
+        with_imports(|imports| imports.fs_directory_get(path)),
+    )
+}
+
+pub unsafe fn hapi_fs_directory_list(
+    fs_label: u8,
+    dir_id: *const u8,
+    dir_id_len: u32,
+    size: u32,
+    buffer: *mut u8,
+) -> i32 {
+    let Some(dir_id) = read_str_len(dir_id, dir_id_len) else {
+        return FsDirectoryListError::NoSuchDirectory.code();
+    };
+    let buffer = if size == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(buffer, size as usize)
+    };
+    value(with_imports(|imports| {
+        imports.fs_directory_list(fs_label, dir_id, buffer)
+    }))
+}
+
+pub unsafe fn hapi_fs_file_remove(path: *const u8, path_len: u32) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsFileRemoveResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_file_remove(path)).code()
+}
+
+pub unsafe fn hapi_fs_directory_remove(path: *const u8, path_len: u32, recursive: u8) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsDirectoryRemoveResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_directory_remove(path, recursive)).code()
+}
+
+pub unsafe fn hapi_fs_rename(from: *const u8, from_len: u32, to: *const u8, to_len: u32) -> i32 {
+    let (Some(from), Some(to)) = (read_str_len(from, from_len), read_str_len(to, to_len)) else {
+        return FsRenameResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_rename(from, to)).code()
+}
+
+pub unsafe fn hapi_fs_file_copy(from: *const u8, from_len: u32, to: *const u8, to_len: u32) -> i32 {
+    let (Some(from), Some(to)) = (read_str_len(from, from_len), read_str_len(to, to_len)) else {
+        return FsFileCopyError::InvalidPath.code();
+    };
+    value(with_imports(|imports| imports.fs_file_copy(from, to)))
+}
+
+pub unsafe fn hapi_fs_metadata(path: *const u8, path_len: u32, size: u32, buffer: *mut u8) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsMetadataError::InvalidPath.code();
+    };
+    let buffer = if size == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(buffer, size as usize)
+    };
+    value(with_imports(|imports| imports.fs_metadata(path, buffer)))
+}
+
+pub unsafe fn hapi_fs_set_modified(path: *const u8, path_len: u32, modified: f64) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsSetModifiedResult::InvalidPath.code();
+    };
+    with_imports(|imports| imports.fs_set_modified(path, modified)).code()
+}
+
+pub unsafe fn hapi_fs_file_open(
+    path: *const u8,
+    path_len: u32,
+    flags: u32,
+    id_buf: *mut u8,
+) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsFileOpenError::InvalidPath.code();
+    };
+    write_id(
+        id_buf,
+        with_imports(|imports| imports.fs_file_open(path, flags)),
+    )
+}
+
+pub unsafe fn hapi_fs_file_set_len(
+    fs_label: u8,
+    file_id: *const u8,
+    file_id_len: u32,
+    len: u32,
+) -> i32 {
+    let Some(file_id) = read_str_len(file_id, file_id_len) else {
+        return FsFileSetLenResult::NoSuchFile.code();
+    };
+    with_imports(|imports| imports.fs_file_set_len(fs_label, file_id, len)).code()
+}
+
+pub unsafe fn hapi_fs_file_append(
+    fs_label: u8,
+    file_id: *const u8,
+    file_id_len: u32,
+    size: u32,
+    buffer: *const u8,
+) -> i32 {
+    let Some(file_id) = read_str_len(file_id, file_id_len) else {
+        return FsFileAppendResult::NoSuchFile.code();
+    };
+    let buffer = slice(buffer, size);
+    with_imports(|imports| imports.fs_file_append(fs_label, file_id, buffer)).code()
+}
+
+pub unsafe fn hapi_fs_init_ramfs_with_capacity(label: u8, capacity: u32) -> i32 {
+    with_imports(|imports| imports.fs_init_ramfs_with_capacity(label, capacity)).code()
+}
+
+pub unsafe fn hapi_fs_unmount(label: u8) -> i32 {
+    with_imports(|imports| imports.fs_unmount(label)).code()
+}
+
+pub unsafe fn hapi_fs_mounts(size: u32, buffer: *mut u8) -> i32 {
+    let buffer = if size == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(buffer, size as usize)
+    };
+    with_imports(|imports| imports.fs_mounts(buffer)) as i32
+}
+
+pub unsafe fn hapi_fs_init_persistent(label: u8) -> i32 {
+    with_imports(|imports| imports.fs_init_persistent(label)).code()
+}
+
+pub unsafe fn hapi_fs_init_overlay(label: u8, lower: u8) -> i32 {
+    with_imports(|imports| imports.fs_init_overlay(label, lower)).code()
+}
+
+pub unsafe fn hapi_fs_watch(path: *const u8, path_len: u32, recursive: u8, id_buf: *mut u8) -> i32 {
+    let Some(path) = read_str_len(path, path_len) else {
+        return FsWatchError::InvalidPath.code();
+    };
+    write_id(
+        id_buf,
+        with_imports(|imports| imports.fs_watch(path, recursive)),
+    )
+}
+
+pub unsafe fn hapi_fs_watch_poll(
+    watcher_id: *const u8,
+    watcher_id_len: u32,
+    size: u32,
+    buffer: *mut u8,
+) -> i32 {
+    let Some(watcher_id) = read_str_len(watcher_id, watcher_id_len) else {
+        return FsWatchPollError::NoSuchWatcher.code();
+    };
+    let buffer = if size == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(buffer, size as usize)
+    };
+    value(with_imports(|imports| {
+        imports.fs_watch_poll(watcher_id, buffer)
+    }))
+}
+
+pub unsafe fn hapi_fs_watch_wait(
+    watcher_id: *const u8,
+    watcher_id_len: u32,
+    timeout_ms: u32,
+) -> i32 {
+    let Some(watcher_id) = read_str_len(watcher_id, watcher_id_len) else {
+        return FsWatchWaitResult::NoSuchWatcher.code();
+    };
+    wait_for(
+        timeout_ms,
+        || with_imports(|imports| imports.fs_watch_wait(watcher_id, timeout_ms)),
+        |result| *result != FsWatchWaitResult::Timeout,
+    )
+    .code()
+}
+
+pub unsafe fn hapi_fs_unwatch(watcher_id: *const u8, watcher_id_len: u32) -> i32 {
+    let Some(watcher_id) = read_str_len(watcher_id, watcher_id_len) else {
+        return FsUnwatchResult::NoSuchWatcher.code();
+    };
+    with_imports(|imports| imports.fs_unwatch(watcher_id)).code()
+}
+
+pub unsafe fn hapi_fs_file_lock(
+    fs_label: u8,
+    file_id: *const u8,
+    file_id_len: u32,
+    exclusive: u8,
+) -> i32 {
+    let Some(file_id) = read_str_len(file_id, file_id_len) else {
+        return FsFileLockResult::NoSuchFile.code();
+    };
+    with_imports(|imports| imports.fs_file_lock(fs_label, file_id, exclusive)).code()
+}
+
+pub unsafe fn hapi_fs_file_lock_wait(
+    fs_label: u8,
+    file_id: *const u8,
+    file_id_len: u32,
+    exclusive: u8,
+    timeout_ms: u32,
+) -> i32 {
+    let Some(file_id) = read_str_len(file_id, file_id_len) else {
+        return FsFileLockWaitResult::NoSuchFile.code();
+    };
+    wait_for(
+        timeout_ms,
+        || {
+            with_imports(|imports| {
+                imports.fs_file_lock_wait(fs_label, file_id, exclusive, timeout_ms)
+            })
+        },
+        |result| *result != FsFileLockWaitResult::Locked,
+    )
+    .code()
+}
+
+pub unsafe fn hapi_fs_file_unlock(fs_label: u8, file_id: *const u8, file_id_len: u32) -> i32 {
+    let Some(file_id) = read_str_len(file_id, file_id_len) else {
+        return FsFileUnlockResult::NoSuchFile.code();
+    };
+    with_imports(|imports| imports.fs_file_unlock(fs_label, file_id)).code()
+}
+
+pub unsafe fn hapi_thread_spawn(f_ptr: *const c_void) {
+    let context = super::context();
+    let f_ptr = f_ptr as usize;
+    let thread = std::thread::spawn(move || {
+        super::enter(context);
+        // # Safety
+        // The pointer was created by `hapi::thread::spawn` from a boxed closure
+        let func = unsafe { Box::from_raw(f_ptr as *mut Box<dyn FnOnce()>) };
+        (*func)();
+    });
+    with_kernel(|kernel, _| kernel.threads.push(thread));
+}
@@ -0,0 +1,1194 @@
+use crate::{abi::*, fs::fslabel::FsLabel, network::RequestStatus};
+
+use super::{
+    js::LogLevel,
+    kernel::Kernel,
+    lock::LockMode,
+    network::method_from_u32,
+    ramfs::{child_key, CreateError, Node, NodeKind, Overlay, RamFs, RenameError, ResolvedPath},
+    watch::SimChange,
+};
+
+/// The result of looking up a node
+enum Lookup {
+    Found(String),
+    NoSuchFs,
+    NotFound,
+}
+
+/// The imports of a single process in the simulated kernel
+#[derive(Debug)]
+pub struct SimImports<'a> {
+    kernel: &'a mut Kernel,
+    pid: &'a str,
+}
+
+impl<'a> SimImports<'a> {
+    pub fn new(kernel: &'a mut Kernel, pid: &'a str) -> Self {
+        Self { kernel, pid }
+    }
+
+    fn log(&mut self, level: LogLevel, string: &str) {
+        self.kernel.console.logs.push((level, string.to_owned()));
+    }
+
+    /// Create a node. Returns None if the path is invalid.
+    fn create(&mut self, path: &str, kind: NodeKind) -> Option<Result<(), CreateError>> {
+        let path = self.kernel.resolve(self.pid, path)?;
+        if self.lower_node(path.label, &path.key()).is_some() {
+            return Some(Err(CreateError::Exists));
+        }
+        self.copy_up_parent(&path);
+        let node = Node::new(self.kernel.next_id(), kind, self.now());
+        let Some(fs) = self.kernel.filesystems.get_mut(&path.label) else {
+            return Some(Err(CreateError::NoParent));
+        };
+        let result = fs.create(&path, node);
+        if result.is_ok() {
+            self.notify(path.label, SimChange::Create(path.key()));
+        }
+        Some(result)
+    }
+
+    /// Queue a change to the file system at the label for the watchers that see it
+    fn notify(&mut self, label: FsLabel, change: SimChange) {
+        self.kernel.watchers.notify(label, change);
+    }
+
+    /// Queue a modify of the file with the id for the watchers that see it
+    fn notify_modified(&mut self, fs_label: u8, file_id: &str) {
+        let Ok(label) = FsLabel::try_from(fs_label) else {
+            return;
+        };
+        if let Some(key) = self.kernel.fs(label).and_then(|fs| fs.key_of(file_id)) {
+            let change = SimChange::Modify(key.to_owned());
+            self.notify(label, change);
+        }
+    }
+
+    /// Mount a ram file system
+    fn init_ramfs(&mut self, label: u8, capacity: Option<usize>) -> FsInitRamfsResult {
+        let Ok(label) = FsLabel::try_from(label) else {
+            return FsInitRamfsResult::InvalidLabel;
+        };
+        if self.kernel.filesystems.contains_key(&label) {
+            return FsInitRamfsResult::LabelOccupied;
+        }
+        let root_id = self.kernel.next_id();
+        let mut fs = RamFs::new(root_id, self.now());
+        fs.capacity = capacity;
+        self.kernel.filesystems.insert(label, fs);
+        FsInitRamfsResult::Ok
+    }
+
+    /// The seconds since the unix epoch, used to time stamp nodes
+    fn now(&self) -> f64 {
+        self.kernel.clock.since_unix_epoch()
+    }
+
+    /// Find a node of a kind
+    fn get(&self, path: &str, matches: impl Fn(&NodeKind) -> bool) -> Lookup {
+        let Some(path) = self.kernel.resolve(self.pid, path) else {
+            return Lookup::NotFound;
+        };
+        if self.kernel.fs(path.label).is_none() {
+            return Lookup::NoSuchFs;
+        }
+        match self.lookup(path.label, &path.key()) {
+            Some(node) if matches(&node.kind) => Lookup::Found(node.id.clone()),
+            _ => Lookup::NotFound,
+        }
+    }
+
+    /// Get the overlay mounted at the label and the file system under it
+    fn lower(&self, label: FsLabel) -> Option<(&Overlay, &RamFs)> {
+        let overlay = self.kernel.fs(label)?.overlay.as_ref()?;
+        Some((overlay, self.kernel.fs(overlay.lower)?))
+    }
+
+    /// Find the node at a key, looking through to the lower layer of an overlay
+    fn lookup(&self, label: FsLabel, key: &str) -> Option<&Node> {
+        self.kernel
+            .fs(label)?
+            .get(key)
+            .or_else(|| self.lower_node(label, key))
+    }
+
+    /// Find the node at a key in the lower layer of an overlay, unless it was deleted
+    fn lower_node(&self, label: FsLabel, key: &str) -> Option<&Node> {
+        let (overlay, lower) = self.lower(label)?;
+        match overlay.hides(key) {
+            true => None,
+            false => lower.get(key),
+        }
+    }
+
+    /// Find the key and node with an id, looking through to the lower layer of an overlay
+    fn lookup_id(&self, label: FsLabel, id: &str) -> Option<(&str, &Node)> {
+        let fs = self.kernel.fs(label)?;
+        if let Some(key) = fs.key_of(id) {
+            return Some((key, fs.get(key)?));
+        }
+        let (overlay, lower) = self.lower(label)?;
+        let key = lower.key_of(id)?;
+        // A node created in the upper layer at the same key shadows it
+        if overlay.hides(key) || fs.get(key).is_some() {
+            return None;
+        }
+        Some((key, lower.get(key)?))
+    }
+
+    /// Get the names and nodes inside the directory at a key, merging the layers of an overlay
+    fn children(&self, label: FsLabel, key: &str) -> Option<Vec<(String, &Node)>> {
+        let fs = self.kernel.fs(label)?;
+        let upper = fs.children_at(key);
+        // A file in the upper layer shadows a directory under it
+        let shadowed = fs
+            .get(key)
+            .is_some_and(|node| node.kind != NodeKind::Directory);
+        let lower = match self.lower(label) {
+            Some((overlay, lower)) if !shadowed && !overlay.hides(key) => {
+                lower.children_at(key).map(|children| {
+                    children
+                        .into_iter()
+                        .filter(|(name, _)| !overlay.hides(&child_key(key, name)))
+                        .collect::<Vec<_>>()
+                })
+            }
+            _ => None,
+        };
+        if upper.is_none() && lower.is_none() {
+            return None;
+        }
+
+        let mut children = std::collections::BTreeMap::new();
+        for (name, node) in upper.into_iter().chain(lower).flatten() {
+            children.entry(name.to_owned()).or_insert(node);
+        }
+        Some(children.into_iter().collect())
+    }
+
+    /// Copy a node and its parents from the lower layer of an overlay to the upper layer, keeping their ids,
+    /// so it can be changed without writing to the lower layer
+    fn copy_up(&mut self, label: FsLabel, key: &str) {
+        if self
+            .kernel
+            .fs(label)
+            .map_or(true, |fs| fs.get(key).is_some())
+        {
+            return;
+        }
+        let Some(node) = self.lower_node(label, key).cloned() else {
+            return;
+        };
+        self.copy_up(label, key.rsplit_once('/').map_or("", |(parent, _)| parent));
+        if let Some(fs) = self.kernel.filesystems.get_mut(&label) {
+            fs.create(&ResolvedPath::from_key(label, key), node).ok();
+        }
+    }
+
+    /// Copy the parent of a path up from the lower layer of an overlay, so something can be created in it
+    fn copy_up_parent(&mut self, path: &ResolvedPath) {
+        if let Some(parent) = path.parent_key() {
+            self.copy_up(path.label, &parent);
+        }
+    }
+
+    /// Copy the node with an id up from the lower layer of an overlay
+    fn copy_up_id(&mut self, label: FsLabel, id: &str) {
+        if let Some((key, _)) = self.lookup_id(label, id) {
+            let key = key.to_owned();
+            self.copy_up(label, &key);
+        }
+    }
+
+    /// Copy a node and everything under it up from the lower layer of an overlay
+    fn copy_up_tree(&mut self, label: FsLabel, key: &str) {
+        if self.lower(label).is_none() {
+            return;
+        }
+        self.copy_up(label, key);
+        let names: Vec<_> = self
+            .children(label, key)
+            .into_iter()
+            .flatten()
+            .map(|(name, _)| name)
+            .collect();
+        for name in names {
+            self.copy_up_tree(label, &child_key(key, &name));
+        }
+    }
+
+    /// Remove the node at a key and everything under it, hiding it in the lower layer of an overlay
+    fn remove_node(&mut self, label: FsLabel, key: &str) {
+        let in_lower = self.lower_node(label, key).is_some();
+        let Some(fs) = self.kernel.filesystems.get_mut(&label) else {
+            return;
+        };
+        fs.remove(key, true).ok();
+        if let (true, Some(overlay)) = (in_lower, &mut fs.overlay) {
+            overlay.whiteout(key.to_owned());
+        }
+        self.release_removed_locks(label);
+    }
+
+    /// Release the locks on files that no longer exist on the file system at the label
+    fn release_removed_locks(&mut self, label: FsLabel) {
+        for id in self.kernel.locks.locked_files(label) {
+            if self.lookup_id(label, &id).is_none() {
+                self.kernel.locks.release(label, &id);
+            }
+        }
+    }
+
+    fn request(&mut self, url: &str, method: u32, headers: &str, local: bool) -> Option<String> {
+        let method = method_from_u32(method)?;
+        serde_json::from_str::<serde_json::Value>(headers).ok()?;
+
+        let id = self.kernel.next_id();
+        self.kernel
+            .network
+            .request(id.clone(), url, method, headers, local);
+        Some(id)
+    }
+
+    fn ramfs(&self, label: u8) -> Option<&RamFs> {
+        self.kernel.filesystems.get(&FsLabel::try_from(label).ok()?)
+    }
+
+    fn ramfs_mut(&mut self, label: u8) -> Option<&mut RamFs> {
+        self.kernel
+            .filesystems
+            .get_mut(&FsLabel::try_from(label).ok()?)
+    }
+
+    fn in_control(&self) -> bool {
+        self.kernel.display.controlled_by(self.pid)
+    }
+}
+
+impl Imports for SimImports<'_> {
+    fn abi_version(&mut self) -> u32 {
+        self.kernel.abi_version
+    }
+
+    fn abi_capabilities(&mut self) -> u32 {
+        self.kernel.capabilities
+    }
+
+    fn js_console_log_info(&mut self, string: &str) {
+        self.log(LogLevel::Info, string)
+    }
+
+    fn js_console_log_warn(&mut self, string: &str) {
+        self.log(LogLevel::Warn, string)
+    }
+
+    fn js_console_log_error(&mut self, string: &str) {
+        self.log(LogLevel::Error, string)
+    }
+
+    fn js_console_eval(&mut self, source: &str) -> Option<String> {
+        self.kernel.console.eval(source)
+    }
+
+    fn stdout_clear(&mut self) {
+        self.kernel.process(self.pid).stdout.clear();
+    }
+
+    fn stdout_clear_line(&mut self) {
+        self.kernel.process(self.pid).clear_lines(1);
+    }
+
+    fn stdout_clear_lines(&mut self, num: u32) {
+        self.kernel.process(self.pid).clear_lines(num);
+    }
+
+    fn stdout_write(&mut self, string: &str) {
+        self.kernel.process(self.pid).stdout.push_str(string);
+    }
+
+    fn process_get_pid(&mut self) -> String {
+        self.pid.to_owned()
+    }
+
+    fn process_get_cwd(&mut self) -> String {
+        self.kernel.process(self.pid).cwd.clone()
+    }
+
+    fn process_get_cwd_length(&mut self) -> u32 {
+        self.process_get_cwd().len() as u32 + 1
+    }
+
+    fn process_set_cwd(&mut self, path: &str) -> ProcessSetCwdResult {
+        let Some(path) = self.kernel.resolve(self.pid, path) else {
+            return ProcessSetCwdResult::InvalidPath;
+        };
+        self.kernel.process(self.pid).cwd = path.to_string();
+        ProcessSetCwdResult::Ok
+    }
+
+    fn process_spawn_subprocess(&mut self, bin: &[u8]) -> String {
+        self.process_spawn_subprocess_v2(bin).unwrap_or_default()
+    }
+
+    /// Spawn a subprocess. The simulator can not run wasm, so the process only records it's binary.
+    fn process_spawn_subprocess_v2(
+        &mut self,
+        bin: &[u8],
+    ) -> Result<String, ProcessSpawnSubprocessV2Error> {
+        let cwd = self.kernel.process(self.pid).cwd.clone();
+        Ok(self.kernel.spawn_process(&cwd, bin.to_vec()))
+    }
+
+    fn process_alive(&mut self, id: &str) -> bool {
+        self.kernel
+            .processes
+            .get(id)
+            .is_some_and(|process| process.alive)
+    }
+
+    fn process_stdout(&mut self, id: &str) -> String {
+        self.kernel
+            .processes
+            .get(id)
+            .map(|process| process.stdout.clone())
+            .unwrap_or_default()
+    }
+
+    fn process_stdout_length(&mut self, id: &str) -> Result<u32, ProcessStdoutLengthError> {
+        match self.kernel.processes.get(id) {
+            Some(process) => Ok(process.stdout.len() as u32 + 1),
+            None => Err(ProcessStdoutLengthError::NoSuchProcess),
+        }
+    }
+
+    fn display_assume_control(&mut self) -> DisplayAssumeControlResult {
+        if self.kernel.display.owner.is_some() && !self.in_control() {
+            return DisplayAssumeControlResult::Occupied;
+        }
+        self.kernel.display.owner = Some(self.pid.to_owned());
+        DisplayAssumeControlResult::Ok
+    }
+
+    fn display_override_control(&mut self) {
+        self.kernel.display.owner = Some(self.pid.to_owned());
+    }
+
+    fn display_release_control(&mut self) -> DisplayReleaseControlResult {
+        if !self.in_control() {
+            return DisplayReleaseControlResult::NotInControl;
+        }
+        self.kernel.display.owner = None;
+        DisplayReleaseControlResult::Ok
+    }
+
+    fn display_displace_control(&mut self) {
+        self.kernel.display.owner = None;
+    }
+
+    fn display_push_stdout(&mut self) -> DisplayPushStdoutResult {
+        if !self.in_control() {
+            return DisplayPushStdoutResult::NotInControl;
+        }
+        let stdout = self.kernel.process(self.pid).stdout.clone();
+        self.kernel.display.text = stdout;
+        DisplayPushStdoutResult::Ok
+    }
+
+    fn display_set_text(&mut self, text: &str) -> DisplaySetTextResult {
+        if !self.in_control() {
+            return DisplaySetTextResult::NotInControl;
+        }
+        text.clone_into(&mut self.kernel.display.text);
+        DisplaySetTextResult::Ok
+    }
+
+    fn display_get_key_buffer(&mut self) -> Result<i32, DisplayGetKeyBufferError> {
+        if !self.in_control() {
+            return Err(DisplayGetKeyBufferError::NotInControl);
+        }
+        self.kernel
+            .display
+            .keys
+            .front()
+            .map(|key| key.key)
+            .ok_or(DisplayGetKeyBufferError::Empty)
+    }
+
+    fn display_get_key_shift(&mut self) -> Result<bool, DisplayGetKeyShiftError> {
+        if !self.in_control() {
+            return Err(DisplayGetKeyShiftError::NotInControl);
+        }
+        Ok(self
+            .kernel
+            .display
+            .keys
+            .front()
+            .is_some_and(|key| key.shift))
+    }
+
+    fn display_get_key_ctrl(&mut self) -> Result<bool, DisplayGetKeyCtrlError> {
+        if !self.in_control() {
+            return Err(DisplayGetKeyCtrlError::NotInControl);
+        }
+        Ok(self.kernel.display.keys.front().is_some_and(|key| key.ctrl))
+    }
+
+    fn display_clear_key(&mut self) {
+        if self.in_control() {
+            self.kernel.display.keys.pop_front();
+        }
+    }
+
+    fn time_since_unix_epoch(&mut self) -> f64 {
+        self.kernel.clock.since_unix_epoch()
+    }
+
+    fn time_since_startup(&mut self) -> f64 {
+        self.kernel.clock.elapsed
+    }
+
+    fn browser_user_agent(&mut self) -> Option<String> {
+        Some(self.kernel.user_agent.clone())
+    }
+
+    fn browser_user_agent_length(&mut self) -> u32 {
+        self.kernel.user_agent.len() as u32
+    }
+
+    fn browser_is_online(&mut self) -> bool {
+        self.kernel.online
+    }
+
+    fn network_request(&mut self, url: &str, method: u32, headers: &str) -> Option<String> {
+        self.request(url, method, headers, false)
+    }
+
+    fn network_request_local(&mut self, url: &str, method: u32, headers: &str) -> Option<String> {
+        self.request(url, method, headers, true)
+    }
+
+    fn network_request_status(&mut self, id: &str) -> Result<u32, NetworkRequestStatusError> {
+        match self.kernel.network.get(id) {
+            Some(request) => Ok(request.response.status.into()),
+            None => Err(NetworkRequestStatusError::NoSuchRequest),
+        }
+    }
+
+    fn network_request_data_length(
+        &mut self,
+        id: &str,
+    ) -> Result<u32, NetworkRequestDataLengthError> {
+        match self.kernel.network.get(id) {
+            Some(request) => Ok(request.response.data.len() as u32),
+            None => Err(NetworkRequestDataLengthError::NoSuchRequest),
+        }
+    }
+
+    fn network_request_data(&mut self, id: &str) -> Option<Vec<u8>> {
+        let request = self.kernel.network.get(id)?;
+        match request.response.status {
+            RequestStatus::Success => Some(request.response.data.clone()),
+            _ => None,
+        }
+    }
+
+    fn network_request_drop(&mut self, id: &str) {
+        self.kernel.network.drop_request(id);
+    }
+
+    fn fs_init_ramfs(&mut self, label: u8) -> FsInitRamfsResult {
+        self.init_ramfs(label, None)
+    }
+
+    fn fs_file_create(&mut self, path: &str) -> FsFileCreateResult {
+        match self.create(path, NodeKind::File(Vec::new())) {
+            Some(Ok(())) => FsFileCreateResult::Ok,
+            Some(Err(CreateError::NoParent)) => FsFileCreateResult::NoSuchDirectory,
+            Some(Err(CreateError::Exists)) => FsFileCreateResult::Exists,
+            None => FsFileCreateResult::InvalidPath,
+        }
+    }
+
+    fn fs_file_get(&mut self, path: &str) -> Result<String, FsFileGetError> {
+        match self.get(path, |kind| matches!(kind, NodeKind::File(_))) {
+            Lookup::Found(id) => Ok(id),
+            Lookup::NoSuchFs | Lookup::NotFound => Err(FsFileGetError::NotFound),
+        }
+    }
+
+    fn fs_file_write(
+        &mut self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &[u8],
+    ) -> FsFileWriteResult {
+        let now = self.now();
+        if let Ok(label) = FsLabel::try_from(fs_label) {
+            self.copy_up_id(label, file_id);
+        }
+        let Some(fs) = self.ramfs_mut(fs_label) else {
+            return FsFileWriteResult::NoSuchFs;
+        };
+        let Some(NodeKind::File(contents)) = fs.get_by_id(file_id).map(|node| &node.kind) else {
+            return FsFileWriteResult::NoSuchFile;
+        };
+        let end = offset as usize + buffer.len();
+        if !fs.fits(contents.len(), end.max(contents.len())) {
+            return FsFileWriteResult::NotEnoughSpace;
+        }
+        let Some(Node {
+            kind: NodeKind::File(contents),
+            modified,
+            ..
+        }) = fs.get_by_id_mut(file_id)
+        else {
+            return FsFileWriteResult::NoSuchFile;
+        };
+
+        *modified = now;
+        let offset = offset as usize;
+        if contents.len() < end {
+            contents.resize(end, 0);
+        }
+        contents[offset..end].copy_from_slice(buffer);
+        self.notify_modified(fs_label, file_id);
+        FsFileWriteResult::Ok
+    }
+
+    fn fs_file_read(
+        &mut self,
+        fs_label: u8,
+        file_id: &str,
+        offset: u32,
+        buffer: &mut [u8],
+    ) -> FsFileReadResult {
+        let now = self.now();
+        let Some(fs) = self.ramfs_mut(fs_label) else {
+            return FsFileReadResult::NoSuchFs;
+        };
+        // Files read through to the lower layer of an overlay are left untouched
+        if let Some(node) = fs.get_by_id_mut(file_id) {
+            node.accessed = now;
+        }
+        let label = FsLabel::try_from(fs_label).ok();
+        let Some((
+            _,
+            Node {
+                kind: NodeKind::File(contents),
+                ..
+            },
+        )) = label.and_then(|label| self.lookup_id(label, file_id))
+        else {
+            return FsFileReadResult::NoSuchFile;
+        };
+
+        let start = (offset as usize).min(contents.len());
+        let end = (start + buffer.len()).min(contents.len());
+        buffer[..end - start].copy_from_slice(&contents[start..end]);
+        FsFileReadResult::Ok
+    }
+
+    fn fs_file_size(&mut self, fs_label: u8, file_id: &str) -> Result<u32, FsFileSizeError> {
+        let label = FsLabel::try_from(fs_label).map_err(|_| FsFileSizeError::NoSuchFs)?;
+        self.ramfs(fs_label).ok_or(FsFileSizeError::NoSuchFs)?;
+        match self.lookup_id(label, file_id).map(|(_, node)| &node.kind) {
+            Some(NodeKind::File(contents)) => Ok(contents.len() as u32),
+            _ => Err(FsFileSizeError::NoSuchFile),
+        }
+    }
+
+    fn fs_directory_create(&mut self, path: &str) -> FsDirectoryCreateResult {
+        match self.create(path, NodeKind::Directory) {
+            Some(Ok(())) => FsDirectoryCreateResult::Ok,
+            Some(Err(CreateError::NoParent)) => FsDirectoryCreateResult::NoSuchDirectory,
+            Some(Err(CreateError::Exists)) => FsDirectoryCreateResult::Exists,
+            None => FsDirectoryCreateResult::InvalidPath,
+        }
+    }
+
+    fn fs_directory_get(&mut self, path: &str) -> Result<String, FsDirectoryGetError> {
+        match self.get(path, |kind| *kind == NodeKind::Directory) {
+            Lookup::Found(id) => Ok(id),
+            Lookup::NoSuchFs => Err(FsDirectoryGetError::NoSuchFs),
+            Lookup::NotFound => Err(FsDirectoryGetError::NotFound),
+        }
+    }
+
+    fn fs_directory_list(
+        &mut self,
+        fs_label: u8,
+        dir_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsDirectoryListError> {
+        let label = FsLabel::try_from(fs_label).map_err(|_| FsDirectoryListError::NoSuchFs)?;
+        self.ramfs(fs_label).ok_or(FsDirectoryListError::NoSuchFs)?;
+        let (key, _) = self
+            .lookup_id(label, dir_id)
+            .ok_or(FsDirectoryListError::NoSuchDirectory)?;
+        let children = self
+            .children(label, key)
+            .ok_or(FsDirectoryListError::NoSuchDirectory)?;
+
+        let mut listing = Vec::new();
+        for (name, node) in children {
+            let (kind, size) = match &node.kind {
+                NodeKind::File(contents) => (0u8, contents.len() as u32),
+                NodeKind::Directory => (1u8, 0),
+            };
+            listing.push(kind);
+            listing.extend_from_slice(&size.to_le_bytes());
+            listing.extend_from_slice(node.id.as_bytes());
+            listing.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            listing.extend_from_slice(name.as_bytes());
+        }
+
+        if let Some(buffer) = buffer.get_mut(..listing.len()) {
+            buffer.copy_from_slice(&listing);
+        }
+        Ok(listing.len() as u32)
+    }
+
+    fn fs_file_remove(&mut self, path: &str) -> FsFileRemoveResult {
+        let Some(path) = self.kernel.resolve(self.pid, path) else {
+            return FsFileRemoveResult::InvalidPath;
+        };
+        if self.kernel.fs(path.label).is_none() {
+            return FsFileRemoveResult::NoSuchFs;
+        }
+        let key = path.key();
+        match self.lookup(path.label, &key).map(|node| &node.kind) {
+            Some(NodeKind::File(_)) => {
+                self.remove_node(path.label, &key);
+                self.notify(path.label, SimChange::Remove(key));
+                FsFileRemoveResult::Ok
+            }
+            _ => FsFileRemoveResult::NotFound,
+        }
+    }
+
+    fn fs_directory_remove(&mut self, path: &str, recursive: u8) -> FsDirectoryRemoveResult {
+        let Some(path) = self.kernel.resolve(self.pid, path) else {
+            return FsDirectoryRemoveResult::InvalidPath;
+        };
+        if self.kernel.fs(path.label).is_none() {
+            return FsDirectoryRemoveResult::NoSuchFs;
+        }
+        let key = path.key();
+        if !matches!(self.lookup(path.label, &key), Some(node) if node.kind == NodeKind::Directory)
+        {
+            return FsDirectoryRemoveResult::NotFound;
+        }
+        if key.is_empty() {
+            return FsDirectoryRemoveResult::InvalidPath;
+        }
+        let empty = self
+            .children(path.label, &key)
+            .map_or(true, |children| children.is_empty());
+        if !empty && recursive != 1 {
+            return FsDirectoryRemoveResult::NotEmpty;
+        }
+        self.remove_node(path.label, &key);
+        self.notify(path.label, SimChange::Remove(key));
+        FsDirectoryRemoveResult::Ok
+    }
+
+    fn fs_rename(&mut self, from: &str, to: &str) -> FsRenameResult {
+        let (Some(from), Some(to)) = (
+            self.kernel.resolve(self.pid, from),
+            self.kernel.resolve(self.pid, to),
+        ) else {
+            return FsRenameResult::InvalidPath;
+        };
+        if self.kernel.fs(to.label).is_none() || self.kernel.fs(from.label).is_none() {
+            return FsRenameResult::NoSuchFs;
+        }
+        let (label, from_key, to_key) = (from.label, from.key(), to.key());
+        let Some(node) = self.lookup(label, &from_key) else {
+            return FsRenameResult::NotFound;
+        };
+        if label != to.label {
+            return FsRenameResult::CrossDevice;
+        }
+        if from_key.is_empty() || to_key.is_empty() {
+            return FsRenameResult::InvalidPath;
+        }
+
+        // Only a file can replace a file, and the upper layer can't see one in the lower layer
+        let is_dir = node.kind == NodeKind::Directory;
+        let in_upper = self
+            .kernel
+            .fs(label)
+            .is_some_and(|fs| fs.get(&to_key).is_some());
+        match self.lower_node(label, &to_key) {
+            Some(dest) if !in_upper && (is_dir || dest.kind == NodeKind::Directory) => {
+                return FsRenameResult::Exists
+            }
+            _ => {}
+        }
+        self.copy_up_tree(label, &from_key);
+        self.copy_up_parent(&to);
+        let in_lower = self.lower_node(label, &from_key).is_some();
+
+        let Some(fs) = self.kernel.filesystems.get_mut(&label) else {
+            return FsRenameResult::NoSuchFs;
+        };
+        match fs.rename(&from, &to) {
+            Ok(()) => {
+                if let (true, Some(overlay)) = (in_lower && from_key != to_key, &mut fs.overlay) {
+                    overlay.whiteout(from_key.clone());
+                }
+                // A file that was replaced takes its locks with it
+                self.release_removed_locks(label);
+                self.notify(label, SimChange::Rename(from_key, to_key));
+                FsRenameResult::Ok
+            }
+            Err(RenameError::NotFound) => FsRenameResult::NotFound,
+            Err(RenameError::NoParent) => FsRenameResult::NoSuchDirectory,
+            Err(RenameError::Exists) => FsRenameResult::Exists,
+            Err(RenameError::Invalid) => FsRenameResult::InvalidPath,
+        }
+    }
+
+    fn fs_file_copy(&mut self, from: &str, to: &str) -> Result<u32, FsFileCopyError> {
+        let (Some(from), Some(to)) = (
+            self.kernel.resolve(self.pid, from),
+            self.kernel.resolve(self.pid, to),
+        ) else {
+            return Err(FsFileCopyError::InvalidPath);
+        };
+        self.kernel
+            .fs(from.label)
+            .ok_or(FsFileCopyError::NoSuchFs)?;
+        let data = match self.lookup(from.label, &from.key()) {
+            Some(Node {
+                kind: NodeKind::File(data),
+                ..
+            }) => data.clone(),
+            _ => return Err(FsFileCopyError::NotFound),
+        };
+        let len = data.len() as u32;
+
+        self.copy_up(to.label, &to.key());
+        self.copy_up_parent(&to);
+        let now = self.now();
+        let id = self.kernel.next_id();
+        let fs = self
+            .kernel
+            .filesystems
+            .get_mut(&to.label)
+            .ok_or(FsFileCopyError::NoSuchFs)?;
+        let old_len = fs.read(&to.key()).map_or(0, <[u8]>::len);
+        if !fs.fits(old_len, data.len()) {
+            return Err(FsFileCopyError::NotEnoughSpace);
+        }
+        let change = match fs.get_mut(&to.key()) {
+            Some(Node {
+                kind: NodeKind::File(contents),
+                modified,
+                ..
+            }) => {
+                *contents = data;
+                *modified = now;
+                SimChange::Modify(to.key())
+            }
+            Some(_) => return Err(FsFileCopyError::Exists),
+            None => {
+                fs.create(&to, Node::new(id, NodeKind::File(data), now))
+                    .map_err(|_| FsFileCopyError::NoSuchDirectory)?;
+                SimChange::Create(to.key())
+            }
+        };
+        self.notify(to.label, change);
+        Ok(len)
+    }
+
+    fn fs_metadata(&mut self, path: &str, buffer: &mut [u8]) -> Result<u32, FsMetadataError> {
+        let path = self
+            .kernel
+            .resolve(self.pid, path)
+            .ok_or(FsMetadataError::InvalidPath)?;
+        self.kernel
+            .fs(path.label)
+            .ok_or(FsMetadataError::NoSuchFs)?;
+        let node = self
+            .lookup(path.label, &path.key())
+            .ok_or(FsMetadataError::NotFound)?;
+
+        let (kind, len) = match &node.kind {
+            NodeKind::File(contents) => (0u8, contents.len() as u32),
+            NodeKind::Directory => (1u8, 0),
+        };
+        let mut metadata = vec![kind, node.read_only as u8];
+        metadata.extend_from_slice(&len.to_le_bytes());
+        for time in [node.created, node.modified, node.accessed] {
+            metadata.extend_from_slice(&time.to_le_bytes());
+        }
+
+        if let Some(buffer) = buffer.get_mut(..metadata.len()) {
+            buffer.copy_from_slice(&metadata);
+        }
+        Ok(metadata.len() as u32)
+    }
+
+    fn fs_set_modified(&mut self, path: &str, modified: f64) -> FsSetModifiedResult {
+        let Some(path) = self.kernel.resolve(self.pid, path) else {
+            return FsSetModifiedResult::InvalidPath;
+        };
+        self.copy_up(path.label, &path.key());
+        let Some(fs) = self.kernel.filesystems.get_mut(&path.label) else {
+            return FsSetModifiedResult::NoSuchFs;
+        };
+        match fs.get_mut(&path.key()) {
+            Some(node) => {
+                node.modified = modified;
+                self.notify(path.label, SimChange::Modify(path.key()));
+                FsSetModifiedResult::Ok
+            }
+            None => FsSetModifiedResult::NotFound,
+        }
+    }
+
+    fn fs_file_open(&mut self, path: &str, flags: u32) -> Result<String, FsFileOpenError> {
+        const CREATE: u32 = 1;
+        const CREATE_NEW: u32 = 2;
+        const TRUNCATE: u32 = 4;
+
+        let path = self
+            .kernel
+            .resolve(self.pid, path)
+            .ok_or(FsFileOpenError::InvalidPath)?;
+        self.kernel
+            .fs(path.label)
+            .ok_or(FsFileOpenError::NoSuchFs)?;
+
+        // Everything is checked against the merged layers of an overlay first,
+        // so nothing is copied up unless the open changes the file system
+        let key = path.key();
+        let exists = match self.lookup(path.label, &key) {
+            Some(node) if !matches!(node.kind, NodeKind::File(_)) => {
+                return Err(FsFileOpenError::Exists)
+            }
+            Some(_) if flags & CREATE_NEW != 0 => return Err(FsFileOpenError::Exists),
+            Some(node) if flags & TRUNCATE == 0 => return Ok(node.id.clone()),
+            Some(_) => true,
+            None if flags & (CREATE | CREATE_NEW) == 0 => return Err(FsFileOpenError::NotFound),
+            None => false,
+        };
+
+        let now = self.now();
+        if exists {
+            self.copy_up(path.label, &key);
+            let fs = self
+                .kernel
+                .filesystems
+                .get_mut(&path.label)
+                .ok_or(FsFileOpenError::NoSuchFs)?;
+            let Some(Node {
+                id,
+                kind: NodeKind::File(contents),
+                modified,
+                ..
+            }) = fs.get_mut(&key)
+            else {
+                return Err(FsFileOpenError::NotFound);
+            };
+            let id = id.clone();
+            contents.clear();
+            *modified = now;
+            self.notify(path.label, SimChange::Modify(key));
+            return Ok(id);
+        }
+
+        match path
+            .parent_key()
+            .and_then(|parent| self.lookup(path.label, &parent))
+        {
+            Some(Node {
+                kind: NodeKind::Directory,
+                ..
+            }) => {}
+            _ => return Err(FsFileOpenError::NoSuchDirectory),
+        }
+        self.copy_up_parent(&path);
+        let id = self.kernel.next_id();
+        let fs = self
+            .kernel
+            .filesystems
+            .get_mut(&path.label)
+            .ok_or(FsFileOpenError::NoSuchFs)?;
+        fs.create(
+            &path,
+            Node::new(id.clone(), NodeKind::File(Vec::new()), now),
+        )
+        .map_err(|_| FsFileOpenError::NoSuchDirectory)?;
+        self.notify(path.label, SimChange::Create(key));
+        Ok(id)
+    }
+
+    fn fs_file_set_len(&mut self, fs_label: u8, file_id: &str, len: u32) -> FsFileSetLenResult {
+        let now = self.now();
+        if let Ok(label) = FsLabel::try_from(fs_label) {
+            self.copy_up_id(label, file_id);
+        }
+        let Some(fs) = self.ramfs_mut(fs_label) else {
+            return FsFileSetLenResult::NoSuchFs;
+        };
+        let Some(NodeKind::File(contents)) = fs.get_by_id(file_id).map(|node| &node.kind) else {
+            return FsFileSetLenResult::NoSuchFile;
+        };
+        if !fs.fits(contents.len(), len as usize) {
+            return FsFileSetLenResult::NotEnoughSpace;
+        }
+        let Some(Node {
+            kind: NodeKind::File(contents),
+            modified,
+            ..
+        }) = fs.get_by_id_mut(file_id)
+        else {
+            return FsFileSetLenResult::NoSuchFile;
+        };
+
+        contents.resize(len as usize, 0);
+        *modified = now;
+        self.notify_modified(fs_label, file_id);
+        FsFileSetLenResult::Ok
+    }
+
+    fn fs_file_append(&mut self, fs_label: u8, file_id: &str, buffer: &[u8]) -> FsFileAppendResult {
+        if let Ok(label) = FsLabel::try_from(fs_label) {
+            self.copy_up_id(label, file_id);
+        }
+        let Some(fs) = self.ramfs(fs_label) else {
+            return FsFileAppendResult::NoSuchFs;
+        };
+        let Some(NodeKind::File(contents)) = fs.get_by_id(file_id).map(|node| &node.kind) else {
+            return FsFileAppendResult::NoSuchFile;
+        };
+        // The kernel is locked for the whole call, so nothing is written between reading the length and writing
+        match self.fs_file_write(fs_label, file_id, contents.len() as u32, buffer) {
+            FsFileWriteResult::Ok => FsFileAppendResult::Ok,
+            FsFileWriteResult::NoSuchFs => FsFileAppendResult::NoSuchFs,
+            FsFileWriteResult::NotEnoughSpace => FsFileAppendResult::NotEnoughSpace,
+            _ => FsFileAppendResult::NoSuchFile,
+        }
+    }
+
+    fn fs_init_ramfs_with_capacity(
+        &mut self,
+        label: u8,
+        capacity: u32,
+    ) -> FsInitRamfsWithCapacityResult {
+        match self.init_ramfs(label, Some(capacity as usize)) {
+            FsInitRamfsResult::Ok => FsInitRamfsWithCapacityResult::Ok,
+            FsInitRamfsResult::LabelOccupied => FsInitRamfsWithCapacityResult::LabelOccupied,
+            _ => FsInitRamfsWithCapacityResult::InvalidLabel,
+        }
+    }
+
+    fn fs_unmount(&mut self, label: u8) -> FsUnmountResult {
+        let Ok(label) = FsLabel::try_from(label) else {
+            return FsUnmountResult::NoSuchFs;
+        };
+        match self.kernel.filesystems.remove(&label) {
+            Some(mut fs) => {
+                self.release_removed_locks(label);
+                self.kernel.watchers.remove_fs(label);
+                if let Err(error) = fs.save() {
+                    let message = format!("Failed to save the file system: {}", error);
+                    self.log(LogLevel::Error, &message);
+                }
+                FsUnmountResult::Ok
+            }
+            None => FsUnmountResult::NoSuchFs,
+        }
+    }
+
+    fn fs_mounts(&mut self, buffer: &mut [u8]) -> u32 {
+        let mut mounts: Vec<_> = self.kernel.filesystems.iter().collect();
+        mounts.sort_by_key(|(label, _)| u8::from(**label));
+
+        let mut list = Vec::new();
+        for (label, fs) in mounts {
+            list.push(u8::from(*label));
+            let fs_type = match (&fs.overlay, fs.is_persistent()) {
+                (Some(_), _) => 2,
+                (None, true) => 1,
+                (None, false) => 0,
+            };
+            list.push(fs_type);
+            list.extend_from_slice(&(fs.used() as u32).to_le_bytes());
+            let capacity = fs.capacity.map_or(u32::MAX, |capacity| capacity as u32);
+            list.extend_from_slice(&capacity.to_le_bytes());
+        }
+
+        if let Some(buffer) = buffer.get_mut(..list.len()) {
+            buffer.copy_from_slice(&list);
+        }
+        list.len() as u32
+    }
+
+    fn fs_init_persistent(&mut self, label: u8) -> FsInitPersistentResult {
+        let Ok(label) = FsLabel::try_from(label) else {
+            return FsInitPersistentResult::InvalidLabel;
+        };
+        if self.kernel.filesystems.contains_key(&label) {
+            return FsInitPersistentResult::LabelOccupied;
+        }
+        let Some(dir) = &self.kernel.storage_dir else {
+            return FsInitPersistentResult::Unavailable;
+        };
+        let dir = dir.join((u8::from(label) as char).to_string());
+        let now = self.now();
+        match RamFs::load(&dir, now, || self.kernel.next_id()) {
+            Ok(fs) => {
+                self.kernel.filesystems.insert(label, fs);
+                FsInitPersistentResult::Ok
+            }
+            Err(error) => {
+                let message = format!("Failed to load {}: {}", dir.display(), error);
+                self.log(LogLevel::Error, &message);
+                FsInitPersistentResult::Unavailable
+            }
+        }
+    }
+
+    fn fs_init_overlay(&mut self, label: u8, lower: u8) -> FsInitOverlayResult {
+        let (Ok(label), Ok(lower)) = (FsLabel::try_from(label), FsLabel::try_from(lower)) else {
+            return FsInitOverlayResult::InvalidLabel;
+        };
+        if self.kernel.filesystems.contains_key(&label) {
+            return FsInitOverlayResult::LabelOccupied;
+        }
+        match self.kernel.fs(lower) {
+            None => return FsInitOverlayResult::NoSuchFs,
+            Some(fs) if fs.overlay.is_some() => return FsInitOverlayResult::InvalidLower,
+            Some(_) => {}
+        }
+        let root_id = self.kernel.next_id();
+        let mut fs = RamFs::new(root_id, self.now());
+        fs.overlay = Some(Overlay::new(lower));
+        self.kernel.filesystems.insert(label, fs);
+        FsInitOverlayResult::Ok
+    }
+
+    fn fs_watch(&mut self, path: &str, recursive: u8) -> Result<String, FsWatchError> {
+        let path = self
+            .kernel
+            .resolve(self.pid, path)
+            .ok_or(FsWatchError::InvalidPath)?;
+        self.kernel.fs(path.label).ok_or(FsWatchError::NoSuchFs)?;
+        self.lookup(path.label, &path.key())
+            .ok_or(FsWatchError::NotFound)?;
+
+        let id = self.kernel.next_id();
+        self.kernel
+            .watchers
+            .watch(id.clone(), self.pid, path.label, path.key(), recursive == 1);
+        Ok(id)
+    }
+
+    fn fs_watch_poll(
+        &mut self,
+        watcher_id: &str,
+        buffer: &mut [u8],
+    ) -> Result<u32, FsWatchPollError> {
+        let watcher = self
+            .kernel
+            .watchers
+            .get_mut(watcher_id)
+            .ok_or(FsWatchPollError::NoSuchWatcher)?;
+
+        let mut changes = Vec::new();
+        for change in &watcher.changes {
+            change.encode(watcher.label, &mut changes);
+        }
+        if let Some(buffer) = buffer.get_mut(..changes.len()) {
+            buffer.copy_from_slice(&changes);
+            watcher.changes.clear();
+        }
+        Ok(changes.len() as u32)
+    }
+
+    /// Only checks the queue, as the kernel is locked.
+    /// The caller waits between checks with [`super::wait_for`].
+    fn fs_watch_wait(&mut self, watcher_id: &str, _timeout_ms: u32) -> FsWatchWaitResult {
+        match self.kernel.watchers.get(watcher_id) {
+            Some(watcher) if watcher.changes.is_empty() => FsWatchWaitResult::Timeout,
+            Some(_) => FsWatchWaitResult::Ok,
+            None => FsWatchWaitResult::NoSuchWatcher,
+        }
+    }
+
+    fn fs_unwatch(&mut self, watcher_id: &str) -> FsUnwatchResult {
+        match self.kernel.watchers.unwatch(watcher_id) {
+            Some(_) => FsUnwatchResult::Ok,
+            None => FsUnwatchResult::NoSuchWatcher,
+        }
+    }
+
+    fn fs_file_lock(&mut self, fs_label: u8, file_id: &str, exclusive: u8) -> FsFileLockResult {
+        let Ok(label) = FsLabel::try_from(fs_label) else {
+            return FsFileLockResult::NoSuchFs;
+        };
+        if self.kernel.fs(label).is_none() {
+            return FsFileLockResult::NoSuchFs;
+        }
+        if !matches!(self.lookup_id(label, file_id), Some((_, node)) if node.kind != NodeKind::Directory)
+        {
+            return FsFileLockResult::NoSuchFile;
+        }
+        let mode = match exclusive {
+            1 => LockMode::Exclusive,
+            _ => LockMode::Shared,
+        };
+
+        // The process is registered, so it's locks aren't released as belonging to a dead process
+        self.kernel.process(self.pid);
+        let processes = &self.kernel.processes;
+        let alive = |pid: &str| processes.get(pid).is_some_and(|process| process.alive);
+        match self
+            .kernel
+            .locks
+            .lock(label, file_id, self.pid, mode, alive)
+        {
+            true => FsFileLockResult::Ok,
+            false => FsFileLockResult::Locked,
+        }
+    }
+
+    /// Only tries to take the lock once, as the kernel is locked.
+    /// The caller waits between tries with [`super::wait_for`].
+    fn fs_file_lock_wait(
+        &mut self,
+        fs_label: u8,
+        file_id: &str,
+        exclusive: u8,
+        _timeout_ms: u32,
+    ) -> FsFileLockWaitResult {
+        match self.fs_file_lock(fs_label, file_id, exclusive) {
+            FsFileLockResult::Ok => FsFileLockWaitResult::Ok,
+            FsFileLockResult::NoSuchFs => FsFileLockWaitResult::NoSuchFs,
+            FsFileLockResult::Locked => FsFileLockWaitResult::Locked,
+            _ => FsFileLockWaitResult::NoSuchFile,
+        }
+    }
+
+    fn fs_file_unlock(&mut self, fs_label: u8, file_id: &str) -> FsFileUnlockResult {
+        let Ok(label) = FsLabel::try_from(fs_label) else {
+            return FsFileUnlockResult::NoSuchFs;
+        };
+        if self.kernel.fs(label).is_none() {
+            return FsFileUnlockResult::NoSuchFs;
+        }
+        if self.lookup_id(label, file_id).is_none() {
+            return FsFileUnlockResult::NoSuchFile;
+        }
+        match self.kernel.locks.unlock(label, file_id, self.pid) {
+            true => FsFileUnlockResult::Ok,
+            false => FsFileUnlockResult::NotLocked,
+        }
+    }
+}
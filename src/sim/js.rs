@@ -0,0 +1,39 @@
+/// The level of a message logged to the js console
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// Evaluates js source, returning the result as json
+pub type EvalHandler = Box<dyn FnMut(&str) -> Option<String> + Send>;
+
+/// The simulated js console.
+/// There is no js engine in the simulator, so evaluation is delegated to a handler.
+#[derive(Default)]
+pub struct SimConsole {
+    pub logs: Vec<(LogLevel, String)>,
+    pub eval_handler: Option<EvalHandler>,
+}
+
+impl SimConsole {
+    /// Set the handler used to evaluate js source
+    pub fn on_eval(&mut self, handler: impl FnMut(&str) -> Option<String> + Send + 'static) {
+        self.eval_handler = Some(Box::new(handler));
+    }
+
+    /// Evaluate js source. Returns None if there is no handler.
+    pub fn eval(&mut self, source: &str) -> Option<String> {
+        self.eval_handler.as_mut()?(source)
+    }
+}
+
+impl std::fmt::Debug for SimConsole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimConsole")
+            .field("logs", &self.logs)
+            .field("eval_handler", &self.eval_handler.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,140 @@
+use std::{collections::HashMap, path::PathBuf, thread::JoinHandle};
+
+use crate::{
+    abi::{Capability, ABI_VERSION},
+    fs::fslabel::FsLabel,
+};
+
+use super::{
+    clock::SimClock,
+    display::SimDisplay,
+    imports::SimImports,
+    js::{LogLevel, SimConsole},
+    lock::SimLocks,
+    network::SimNetwork,
+    process::{ProcessTable, SimProcess},
+    ramfs::{RamFs, ResolvedPath},
+    watch::SimWatchers,
+};
+
+/// The working directory of processes created by the simulator
+pub const DEFAULT_CWD: &str = "a:/";
+
+/// The simulated honeyos kernel.
+/// The `hapi_*` imports of a process are served by [`Kernel::imports`].
+#[derive(Debug)]
+pub struct Kernel {
+    pub processes: ProcessTable,
+    pub filesystems: HashMap<FsLabel, RamFs>,
+    /// The host directory persistent file systems are kept in, with a directory per label.
+    /// Mounting a persistent file system fails while it is None.
+    pub storage_dir: Option<PathBuf>,
+    pub display: SimDisplay,
+    pub network: SimNetwork,
+    /// The file system watchers of every process
+    pub watchers: SimWatchers,
+    /// The advisory file locks of every process
+    pub locks: SimLocks,
+    pub clock: SimClock,
+    pub console: SimConsole,
+    pub user_agent: String,
+    pub online: bool,
+    /// The abi version reported to processes
+    pub abi_version: u32,
+    /// The capabilities reported to processes, as a bit set of [`Capability::bit`]
+    pub capabilities: u32,
+    pub(crate) threads: Vec<JoinHandle<()>>,
+    next_id: u64,
+}
+
+impl Kernel {
+    /// Create a kernel with no processes and no mounted file systems
+    pub fn new() -> Self {
+        Self {
+            processes: ProcessTable::default(),
+            filesystems: HashMap::new(),
+            storage_dir: None,
+            display: SimDisplay::default(),
+            network: SimNetwork::default(),
+            watchers: SimWatchers::default(),
+            locks: SimLocks::default(),
+            clock: SimClock::default(),
+            console: SimConsole::default(),
+            user_agent: "hapi-sim".to_owned(),
+            online: true,
+            abi_version: ABI_VERSION,
+            capabilities: Capability::ALL
+                .iter()
+                .fold(0, |bits, capability| bits | capability.bit()),
+            threads: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Generate a new uuid formatted id
+    pub fn next_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("00000000-0000-4000-8000-{:012x}", self.next_id)
+    }
+
+    /// Create a process and return it's pid
+    pub fn spawn_process(&mut self, cwd: &str, binary: Vec<u8>) -> String {
+        let pid = self.next_id();
+        self.processes
+            .insert(pid.clone(), SimProcess::new(cwd, binary));
+        pid
+    }
+
+    /// Mark a process as dead and stop its watchers. The locks it held are released the next time they are checked.
+    pub fn kill(&mut self, pid: &str) {
+        self.processes.kill(pid);
+        self.watchers.remove_process(pid);
+    }
+
+    /// Get the imports of a process
+    pub fn imports<'a>(&'a mut self, pid: &'a str) -> SimImports<'a> {
+        SimImports::new(self, pid)
+    }
+
+    /// Get the file system mounted at the label
+    pub fn fs(&self, label: FsLabel) -> Option<&RamFs> {
+        self.filesystems.get(&label)
+    }
+
+    /// Resolve a path against the working directory of the process
+    pub fn resolve(&self, pid: &str, path: &str) -> Option<ResolvedPath> {
+        let cwd = self
+            .processes
+            .get(pid)
+            .map(|process| process.cwd.as_str())
+            .unwrap_or(DEFAULT_CWD);
+        ResolvedPath::resolve(cwd, path)
+    }
+
+    /// Write the changes made to persistent file systems to their host directories.
+    /// They are also saved when the file system is unmounted, but not when the kernel is dropped,
+    /// so a host has to call this before it exits.
+    /// Failures are logged to the console.
+    pub fn save(&mut self) {
+        for (label, fs) in &mut self.filesystems {
+            if let Err(error) = fs.save() {
+                let message = format!("Failed to save the file system at {}: {}", label, error);
+                self.console.logs.push((LogLevel::Error, message));
+            }
+        }
+    }
+
+    pub(super) fn process(&mut self, pid: &str) -> &mut SimProcess {
+        if self.processes.get(pid).is_none() {
+            self.processes
+                .insert(pid.to_owned(), SimProcess::new(DEFAULT_CWD, Vec::new()));
+        }
+        self.processes.get_mut(pid).unwrap()
+    }
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::fs::fslabel::FsLabel;
+
+/// How a process holds a lock on a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// The advisory locks processes hold on files, keyed by the label and id of the file
+#[derive(Debug, Clone, Default)]
+pub struct SimLocks {
+    locks: HashMap<(FsLabel, String), Vec<(String, LockMode)>>,
+}
+
+impl SimLocks {
+    /// Lock a file for a process, replacing the lock it already holds on it.
+    /// Locks held by processes that are not alive are released first.
+    /// Returns false if another process holds a lock that conflicts with it.
+    pub fn lock(
+        &mut self,
+        label: FsLabel,
+        id: &str,
+        pid: &str,
+        mode: LockMode,
+        alive: impl Fn(&str) -> bool,
+    ) -> bool {
+        let holders = self.locks.entry((label, id.to_owned())).or_default();
+        holders.retain(|(holder, _)| alive(holder));
+
+        let conflicts = holders.iter().any(|(holder, held)| {
+            holder != pid && (mode == LockMode::Exclusive || *held == LockMode::Exclusive)
+        });
+        if conflicts {
+            return false;
+        }
+        holders.retain(|(holder, _)| holder != pid);
+        holders.push((pid.to_owned(), mode));
+        true
+    }
+
+    /// Release the lock a process holds on a file. Returns false if it holds none.
+    pub fn unlock(&mut self, label: FsLabel, id: &str, pid: &str) -> bool {
+        let key = (label, id.to_owned());
+        let Some(holders) = self.locks.get_mut(&key) else {
+            return false;
+        };
+        let len = holders.len();
+        holders.retain(|(holder, _)| holder != pid);
+        let released = holders.len() != len;
+        if holders.is_empty() {
+            self.locks.remove(&key);
+        }
+        released
+    }
+
+    /// Get the processes holding a lock on a file, and how they hold it.
+    /// Locks held by processes that are not alive are released first.
+    pub fn holders(
+        &mut self,
+        label: FsLabel,
+        id: &str,
+        alive: impl Fn(&str) -> bool,
+    ) -> &[(String, LockMode)] {
+        let key = (label, id.to_owned());
+        if let Some(holders) = self.locks.get_mut(&key) {
+            holders.retain(|(holder, _)| alive(holder));
+            if holders.is_empty() {
+                self.locks.remove(&key);
+            }
+        }
+        self.locks.get(&key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the ids of the files on the file system at the label that are locked
+    pub fn locked_files(&self, label: FsLabel) -> Vec<String> {
+        self.locks
+            .keys()
+            .filter(|(locked, _)| *locked == label)
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+
+    /// Release every lock held on a file, when it is removed
+    pub fn release(&mut self, label: FsLabel, id: &str) {
+        self.locks.remove(&(label, id.to_owned()));
+    }
+}
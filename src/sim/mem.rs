@@ -0,0 +1,84 @@
+use std::{
+    alloc::{self, Layout},
+    os::raw::c_void,
+};
+
+/// The size of the header that stores the size of an allocation
+const HEADER: usize = 16;
+
+/// Get the layout of an allocation of the provided size, including the header
+fn layout(size: usize) -> Option<Layout> {
+    Layout::from_size_align(size.checked_add(HEADER)?, HEADER).ok()
+}
+
+/// Allocate a block of memory that can be freed with `hapi_mem_free`.
+/// Returns `NULL` if the allocation failed.
+pub fn alloc(size: usize) -> *mut c_void {
+    let Some(layout) = layout(size) else {
+        return std::ptr::null_mut();
+    };
+
+    // # Safety
+    // The layout is never zero sized since it always contains the header.
+    unsafe {
+        let ptr = alloc::alloc(layout);
+        if ptr.is_null() {
+            return std::ptr::null_mut();
+        }
+        (ptr as *mut usize).write(size);
+        ptr.add(HEADER) as *mut c_void
+    }
+}
+
+/// Allocate a block of memory and copy the bytes into it
+pub fn alloc_bytes(bytes: &[u8]) -> *mut u8 {
+    let ptr = alloc(bytes.len()) as *mut u8;
+    if !ptr.is_null() {
+        // # Safety
+        // The block was just allocated with the length of the bytes
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+    }
+    ptr
+}
+
+/// Allocate a null-terminated string
+pub fn alloc_string(string: &str) -> *mut u8 {
+    let mut bytes = string.as_bytes().to_vec();
+    bytes.push(0);
+    alloc_bytes(&bytes)
+}
+
+/// Reallocate a block of memory.
+/// # Safety
+/// The pointer must be `NULL` or have been returned by `alloc`.
+pub unsafe fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+    if ptr.is_null() {
+        return alloc(size);
+    }
+    let Some(new_layout) = layout(size) else {
+        return std::ptr::null_mut();
+    };
+
+    let base = (ptr as *mut u8).sub(HEADER);
+    let old_size = (base as *mut usize).read();
+    let old_layout = layout(old_size).unwrap();
+
+    let base = alloc::realloc(base, old_layout, new_layout.size());
+    if base.is_null() {
+        return std::ptr::null_mut();
+    }
+    (base as *mut usize).write(size);
+    base.add(HEADER) as *mut c_void
+}
+
+/// Free a block of memory.
+/// # Safety
+/// The pointer must be `NULL` or have been returned by `alloc`.
+pub unsafe fn free(ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+    let base = (ptr as *mut u8).sub(HEADER);
+    let size = (base as *mut usize).read();
+    alloc::dealloc(base, layout(size).unwrap());
+}
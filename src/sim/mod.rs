@@ -0,0 +1,180 @@
+//! A simulated honeyos host.
+//! Enabled with the `sim` feature, it replaces the `hapi` wasm imports with native implementations,
+//! so apps can be tested with `cargo test`.
+//!
+//! Every thread gets it's own kernel and process the first time it calls into hapi.
+//! Threads spawned with `hapi::thread::spawn` share the kernel and process of their parent.
+//! ```
+//! # use hapi::fs::fslabel::FsLabel;
+//! # fn main() -> Result<(), hapi::fs::error::Error> {
+//! hapi::fs::RamFileSystem::init(FsLabel::A)?;
+//! hapi::fs::File::create("a:/hello.txt")?.write(0, b"Hello")?;
+//!
+//! let contents = hapi::sim::with_kernel(|kernel, _| {
+//!     kernel.fs(FsLabel::A).unwrap().read("hello.txt").unwrap().to_vec()
+//! });
+//! assert_eq!(contents, b"Hello");
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod clock;
+pub mod display;
+pub(crate) mod ffi;
+pub mod imports;
+pub mod js;
+pub mod kernel;
+pub mod lock;
+pub(crate) mod mem;
+pub mod network;
+pub mod process;
+pub mod ramfs;
+pub mod watch;
+
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+pub use imports::SimImports;
+pub use kernel::Kernel;
+
+use crate::{abi::Imports, util::keys::KeyCode};
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// Counts the contexts created, so each gets a new id
+static CONTEXT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// The kernel and process a thread runs in
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub kernel: Arc<Mutex<Kernel>>,
+    pub pid: String,
+    /// Tells contexts apart, as every context is a process but they share the statics of hapi
+    pub id: u64,
+}
+
+impl Context {
+    /// Create a fresh kernel with a single process
+    pub fn new() -> Self {
+        let mut kernel = Kernel::new();
+        let pid = kernel.spawn_process(kernel::DEFAULT_CWD, Vec::new());
+        Self {
+            kernel: Arc::new(Mutex::new(kernel)),
+            pid,
+            id: CONTEXT_COUNT.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the context of the current thread, creating one if it has none
+pub fn context() -> Context {
+    CONTEXT.with(|context| {
+        context
+            .borrow_mut()
+            .get_or_insert_with(Context::new)
+            .clone()
+    })
+}
+
+/// Run the current thread in the context
+pub fn enter(context: Context) {
+    CONTEXT.with(|current| *current.borrow_mut() = Some(context));
+}
+
+/// Replace the context of the current thread with a fresh kernel
+pub fn reset() {
+    enter(Context::new());
+}
+
+/// Run a closure with the kernel and the pid of the current thread
+pub fn with_kernel<R>(f: impl FnOnce(&mut Kernel, &str) -> R) -> R {
+    let context = context();
+    let mut kernel = context
+        .kernel
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut kernel, &context.pid)
+}
+
+/// Run a closure with the imports of the current process
+pub fn with_imports<R>(f: impl FnOnce(&mut SimImports) -> R) -> R {
+    with_kernel(|kernel, pid| f(&mut kernel.imports(pid)))
+}
+
+/// Repeat a call until its result is `done` or `timeout_ms` milliseconds have passed, returning the last result.
+/// The imports that block check once with the kernel locked, so this sleeps between checks with it unlocked
+/// to let other threads make the changes being waited for.
+pub fn wait_for<R>(timeout_ms: u32, mut call: impl FnMut() -> R, done: impl Fn(&R) -> bool) -> R {
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms.into());
+    loop {
+        let result = call();
+        if done(&result) || Instant::now() >= deadline {
+            return result;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Get the stdout of the current process
+pub fn stdout() -> String {
+    with_kernel(|kernel, pid| kernel.imports(pid).process_stdout(pid))
+}
+
+/// Get the text in the display's text-mode buffer
+pub fn display_text() -> String {
+    with_kernel(|kernel, _| kernel.display.text.clone())
+}
+
+/// Queue a key press on the display
+pub fn press_key(key: KeyCode, shift: bool, ctrl: bool) {
+    with_kernel(|kernel, _| kernel.display.press(key, shift, ctrl));
+}
+
+/// Queue a key press on the display for every character in the string
+pub fn type_str(string: &str) {
+    with_kernel(|kernel, _| kernel.display.type_str(string));
+}
+
+/// Advance the simulated clock
+pub fn advance_time(duration: Duration) {
+    with_kernel(|kernel, _| kernel.clock.advance(duration));
+}
+
+/// Keep the persistent file systems of the current kernel in a host directory, with a directory per label
+pub fn set_storage_dir(dir: impl Into<PathBuf>) {
+    let dir = dir.into();
+    with_kernel(|kernel, _| kernel.storage_dir = Some(dir));
+}
+
+/// Write the changes made to the persistent file systems of the current kernel to their host directories
+pub fn save() {
+    with_kernel(|kernel, _| kernel.save());
+}
+
+/// Wait for every thread spawned in the current kernel to finish
+pub fn join_threads() {
+    loop {
+        let threads = with_kernel(|kernel, _| std::mem::take(&mut kernel.threads));
+        if threads.is_empty() {
+            return;
+        }
+        for thread in threads {
+            let _ = thread.join();
+        }
+    }
+}
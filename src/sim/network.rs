@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::network::{RequestMethod, RequestStatus};
+
+/// A canned response served by the simulated network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimResponse {
+    pub status: RequestStatus,
+    pub data: Vec<u8>,
+}
+
+/// A request made through the simulated network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimRequest {
+    pub url: String,
+    pub method: RequestMethod,
+    pub headers: String,
+    pub local: bool,
+    pub response: SimResponse,
+}
+
+/// The simulated network.
+/// Requests to urls without a registered response fail.
+#[derive(Debug, Clone, Default)]
+pub struct SimNetwork {
+    routes: HashMap<(bool, String), SimResponse>,
+    requests: HashMap<String, SimRequest>,
+    /// Every request made, in order
+    pub log: Vec<SimRequest>,
+}
+
+impl SimNetwork {
+    /// Serve the data for requests to the url
+    pub fn respond(&mut self, url: impl Into<String>, data: impl Into<Vec<u8>>) {
+        self.route(false, url, RequestStatus::Success, data);
+    }
+
+    /// Serve the data for requests to the url on the local server
+    pub fn respond_local(&mut self, url: impl Into<String>, data: impl Into<Vec<u8>>) {
+        self.route(true, url, RequestStatus::Success, data);
+    }
+
+    /// Serve a response with the status for requests to the url
+    pub fn route(
+        &mut self,
+        local: bool,
+        url: impl Into<String>,
+        status: RequestStatus,
+        data: impl Into<Vec<u8>>,
+    ) {
+        let response = SimResponse {
+            status,
+            data: data.into(),
+        };
+        self.routes.insert((local, url.into()), response);
+    }
+
+    /// Create a request, answering it with the registered response
+    pub fn request(
+        &mut self,
+        id: String,
+        url: &str,
+        method: RequestMethod,
+        headers: &str,
+        local: bool,
+    ) {
+        let response = self
+            .routes
+            .get(&(local, url.to_owned()))
+            .cloned()
+            .unwrap_or(SimResponse {
+                status: RequestStatus::Fail,
+                data: Vec::new(),
+            });
+        let request = SimRequest {
+            url: url.to_owned(),
+            method,
+            headers: headers.to_owned(),
+            local,
+            response,
+        };
+        self.log.push(request.clone());
+        self.requests.insert(id, request);
+    }
+
+    /// Get a request that has not been dropped
+    pub fn get(&self, id: &str) -> Option<&SimRequest> {
+        self.requests.get(id)
+    }
+
+    /// Get a request that has not been dropped mutably
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut SimRequest> {
+        self.requests.get_mut(id)
+    }
+
+    /// Drop a request
+    pub fn drop_request(&mut self, id: &str) {
+        self.requests.remove(id);
+    }
+}
+
+/// Convert a method passed over ffi to a request method
+pub fn method_from_u32(value: u32) -> Option<RequestMethod> {
+    match value {
+        0 => Some(RequestMethod::Get),
+        1 => Some(RequestMethod::Head),
+        2 => Some(RequestMethod::Post),
+        3 => Some(RequestMethod::Put),
+        4 => Some(RequestMethod::Delete),
+        5 => Some(RequestMethod::Connect),
+        6 => Some(RequestMethod::Options),
+        7 => Some(RequestMethod::Trace),
+        8 => Some(RequestMethod::Patch),
+        _ => None,
+    }
+}
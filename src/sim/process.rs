@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// A simulated process
+#[derive(Debug, Clone)]
+pub struct SimProcess {
+    pub stdout: String,
+    pub cwd: String,
+    pub alive: bool,
+    /// The wasm binary the process was spawned from. Empty for processes created by the simulator.
+    pub binary: Vec<u8>,
+}
+
+impl SimProcess {
+    /// Create a new living process with an empty stdout
+    pub fn new(cwd: impl Into<String>, binary: Vec<u8>) -> Self {
+        Self {
+            stdout: String::new(),
+            cwd: cwd.into(),
+            alive: true,
+            binary,
+        }
+    }
+
+    /// Clear the last N lines in the stdout
+    pub fn clear_lines(&mut self, num: u32) {
+        for _ in 0..num {
+            match self.stdout.trim_end_matches('\n').rfind('\n') {
+                Some(index) => self.stdout.truncate(index + 1),
+                None => {
+                    self.stdout.clear();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The table of simulated processes, keyed by pid
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTable {
+    processes: HashMap<String, SimProcess>,
+}
+
+impl ProcessTable {
+    /// Insert a process
+    pub fn insert(&mut self, pid: String, process: SimProcess) {
+        self.processes.insert(pid, process);
+    }
+
+    /// Get a process
+    pub fn get(&self, pid: &str) -> Option<&SimProcess> {
+        self.processes.get(pid)
+    }
+
+    /// Get a process mutably
+    pub fn get_mut(&mut self, pid: &str) -> Option<&mut SimProcess> {
+        self.processes.get_mut(pid)
+    }
+
+    /// Mark a process as dead
+    pub fn kill(&mut self, pid: &str) {
+        if let Some(process) = self.processes.get_mut(pid) {
+            process.alive = false;
+        }
+    }
+
+    /// Iterate over all the processes and their pids
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SimProcess)> {
+        self.processes
+            .iter()
+            .map(|(pid, process)| (pid.as_str(), process))
+    }
+}
@@ -0,0 +1,539 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::fs::fslabel::FsLabel;
+
+/// A path resolved to a file system and the components within it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPath {
+    pub label: FsLabel,
+    pub components: Vec<String>,
+}
+
+impl ResolvedPath {
+    /// Resolve a path, joining it onto the working directory if it has no fs label.
+    /// Returns None if neither the path nor the working directory contain a fs label.
+    pub fn resolve(cwd: &str, path: &str) -> Option<Self> {
+        let (label, rest) = match split_label(path) {
+            Some((label, rest)) => (label, rest.to_owned()),
+            None => {
+                let (label, cwd_rest) = split_label(cwd)?;
+                (label, format!("{}/{}", cwd_rest, path))
+            }
+        };
+
+        let mut components = Vec::<String>::new();
+        for component in rest.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    components.pop();
+                }
+                _ => components.push(component.to_owned()),
+            }
+        }
+        Some(Self { label, components })
+    }
+
+    /// The path of a key inside a file system
+    pub fn from_key(label: FsLabel, key: &str) -> Self {
+        Self {
+            label,
+            components: key
+                .split('/')
+                .filter(|component| !component.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        }
+    }
+
+    /// The key of the path inside of it's file system
+    pub fn key(&self) -> String {
+        self.components.join("/")
+    }
+
+    /// The key of the parent directory, None for the root
+    pub fn parent_key(&self) -> Option<String> {
+        let (_, parent) = self.components.split_last()?;
+        Some(parent.join("/"))
+    }
+}
+
+impl std::fmt::Display for ResolvedPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label: u8 = self.label.into();
+        write!(f, "{}:/{}", label as char, self.key())
+    }
+}
+
+/// The key of a child in the directory at the key
+pub fn child_key(key: &str, name: &str) -> String {
+    match key.is_empty() {
+        true => name.to_owned(),
+        false => format!("{}/{}", key, name),
+    }
+}
+
+/// Split the fs label from the start of a path
+fn split_label(path: &str) -> Option<(FsLabel, &str)> {
+    let mut chars = path.char_indices();
+    let (_, label) = chars.next()?;
+    let (colon, ':') = chars.next()? else {
+        return None;
+    };
+    let label = label.to_string().parse().ok()?;
+    Some((label, &path[colon + 1..]))
+}
+
+/// The contents of a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    File(Vec<u8>),
+    Directory,
+}
+
+/// A file or directory in the ram file system.
+/// Times are in seconds since the unix epoch.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub id: String,
+    pub kind: NodeKind,
+    pub created: f64,
+    pub modified: f64,
+    pub accessed: f64,
+    pub read_only: bool,
+}
+
+impl Node {
+    /// Create a writable node at the time
+    pub fn new(id: String, kind: NodeKind, now: f64) -> Self {
+        Self {
+            id,
+            kind,
+            created: now,
+            modified: now,
+            accessed: now,
+            read_only: false,
+        }
+    }
+}
+
+/// The reasons creating a node can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateError {
+    NoParent,
+    Exists,
+}
+
+/// The reasons removing a node can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveError {
+    NotFound,
+    NotEmpty,
+    IsRoot,
+}
+
+/// The reasons moving a node can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameError {
+    NotFound,
+    NoParent,
+    Exists,
+    Invalid,
+}
+
+/// The lower layer of an overlay file system, and the keys deleted from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlay {
+    pub lower: FsLabel,
+    whiteouts: BTreeSet<String>,
+}
+
+impl Overlay {
+    /// Create an overlay of the file system at the label with nothing deleted
+    pub fn new(lower: FsLabel) -> Self {
+        Self {
+            lower,
+            whiteouts: BTreeSet::new(),
+        }
+    }
+
+    /// Check if the node at the key in the lower layer was deleted, by itself or with a parent
+    pub fn hides(&self, mut key: &str) -> bool {
+        loop {
+            if self.whiteouts.contains(key) {
+                return true;
+            }
+            match key.rfind('/') {
+                Some(slash) => key = &key[..slash],
+                None => return false,
+            }
+        }
+    }
+
+    /// Hide the node at the key in the lower layer and everything under it
+    pub fn whiteout(&mut self, key: String) {
+        self.whiteouts.insert(key);
+    }
+}
+
+/// The host directory a persistent file system is kept in
+#[derive(Debug, Clone)]
+struct Storage {
+    dir: PathBuf,
+    /// The kind and modified time of every node as it was last saved
+    saved: BTreeMap<String, (NodeKind, f64)>,
+    /// Set when a node may have changed since the last save
+    dirty: bool,
+}
+
+/// A simulated ram file system.
+/// Nodes are keyed by their path relative to the root of the file system, without leading slashes.
+/// A persistent file system is loaded from a host directory, and changes are written back with [`RamFs::save`].
+/// The upper layer of an overlay file system is a ram file system with an [`Overlay`].
+#[derive(Debug, Clone)]
+pub struct RamFs {
+    nodes: BTreeMap<String, Node>,
+    ids: HashMap<String, String>,
+    /// The most bytes of file contents the file system can hold
+    pub capacity: Option<usize>,
+    pub overlay: Option<Overlay>,
+    storage: Option<Storage>,
+}
+
+impl RamFs {
+    /// Create an empty file system whose root directory has the id
+    pub fn new(root_id: String, now: f64) -> Self {
+        let mut fs = Self {
+            nodes: BTreeMap::new(),
+            ids: HashMap::new(),
+            capacity: None,
+            overlay: None,
+            storage: None,
+        };
+        fs.insert(String::new(), Node::new(root_id, NodeKind::Directory, now));
+        fs
+    }
+
+    /// Load a persistent file system from a host directory, creating the directory if it is missing.
+    /// Nodes get ids from `next_id`, and their times from the modified time of the host files.
+    pub fn load(dir: &Path, now: f64, mut next_id: impl FnMut() -> String) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let mut ramfs = Self::new(next_id(), now);
+
+        let mut stack = vec![(dir.to_path_buf(), String::new())];
+        while let Some((dir, prefix)) = stack.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+                let key = match prefix.is_empty() {
+                    true => name,
+                    false => format!("{}/{}", prefix, name),
+                };
+                // Symlinks are skipped rather than followed, as they may leave the directory or form a cycle
+                let file_type = entry.file_type()?;
+                let kind = if file_type.is_dir() {
+                    stack.push((entry.path(), key.clone()));
+                    NodeKind::Directory
+                } else if file_type.is_file() {
+                    NodeKind::File(fs::read(entry.path())?)
+                } else {
+                    continue;
+                };
+                let metadata = entry.metadata()?;
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .map_or(now, |duration| duration.as_secs_f64());
+                ramfs.insert(key, Node::new(next_id(), kind, modified));
+            }
+        }
+
+        let saved = ramfs.snapshot();
+        ramfs.storage = Some(Storage {
+            dir: dir.to_path_buf(),
+            saved,
+            dirty: false,
+        });
+        Ok(ramfs)
+    }
+
+    /// Check if the file system is kept in a host directory
+    pub fn is_persistent(&self) -> bool {
+        self.storage.is_some()
+    }
+
+    /// Write the changes since the last save to the host directory of a persistent file system.
+    /// Does nothing for a ram file system.
+    pub fn save(&mut self) -> io::Result<()> {
+        let snapshot = match &self.storage {
+            Some(storage) if storage.dirty => self.snapshot(),
+            _ => return Ok(()),
+        };
+        let Some(storage) = &mut self.storage else {
+            return Ok(());
+        };
+
+        for (key, (kind, _)) in &storage.saved {
+            let kept = snapshot.get(key).is_some_and(|(new_kind, _)| {
+                matches!(
+                    (kind, new_kind),
+                    (NodeKind::File(_), NodeKind::File(_))
+                        | (NodeKind::Directory, NodeKind::Directory)
+                )
+            });
+            if kept {
+                continue;
+            }
+            let path = storage.dir.join(key);
+            let removed = match kind {
+                NodeKind::File(_) => fs::remove_file(path),
+                NodeKind::Directory => fs::remove_dir_all(path),
+            };
+            // A parent directory may have been removed first
+            match removed {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+
+        // Parents sort before their contents, so directories are created before what is in them
+        for (key, node) in &snapshot {
+            if storage.saved.get(key) == Some(node) {
+                continue;
+            }
+            let path = storage.dir.join(key);
+            match &node.0 {
+                NodeKind::Directory => fs::create_dir_all(path)?,
+                NodeKind::File(data) => {
+                    fs::write(&path, data)?;
+                    // Times the host file system can't hold are left at the time of the write
+                    let modified = Duration::try_from_secs_f64(node.1.max(0.0))
+                        .ok()
+                        .and_then(|duration| SystemTime::UNIX_EPOCH.checked_add(duration));
+                    if let Some(modified) = modified {
+                        fs::File::options()
+                            .write(true)
+                            .open(&path)?
+                            .set_modified(modified)?;
+                    }
+                }
+            }
+        }
+
+        storage.saved = snapshot;
+        storage.dirty = false;
+        Ok(())
+    }
+
+    /// Get the kind and modified time of every node except the root
+    fn snapshot(&self) -> BTreeMap<String, (NodeKind, f64)> {
+        self.nodes
+            .iter()
+            .filter(|(key, _)| !key.is_empty())
+            .map(|(key, node)| (key.clone(), (node.kind.clone(), node.modified)))
+            .collect()
+    }
+
+    /// Mark a persistent file system as changed
+    fn touch(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            storage.dirty = true;
+        }
+    }
+
+    /// Create a node at the path
+    pub fn create(&mut self, path: &ResolvedPath, node: Node) -> Result<(), CreateError> {
+        let key = path.key();
+        if self.nodes.contains_key(&key) {
+            return Err(CreateError::Exists);
+        }
+        let parent = path.parent_key().ok_or(CreateError::Exists)?;
+        match self.nodes.get(&parent) {
+            Some(Node {
+                kind: NodeKind::Directory,
+                ..
+            }) => {}
+            _ => return Err(CreateError::NoParent),
+        }
+        self.insert(key, node);
+        Ok(())
+    }
+
+    /// Get the node at the key
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.nodes.get(key)
+    }
+
+    /// Get the node at the key mutably
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Node> {
+        self.touch();
+        self.nodes.get_mut(key)
+    }
+
+    /// Get the node with the id
+    pub fn get_by_id(&self, id: &str) -> Option<&Node> {
+        self.nodes.get(self.ids.get(id)?)
+    }
+
+    /// Get the node with the id mutably
+    pub fn get_by_id_mut(&mut self, id: &str) -> Option<&mut Node> {
+        self.touch();
+        self.nodes.get_mut(self.ids.get(id)?)
+    }
+
+    /// Get the key of the node with the id
+    pub fn key_of(&self, id: &str) -> Option<&str> {
+        self.ids.get(id).map(String::as_str)
+    }
+
+    /// Get the names and nodes inside the directory with the id, sorted by name.
+    /// Returns None if there is no directory with the id.
+    pub fn children(&self, id: &str) -> Option<Vec<(&str, &Node)>> {
+        self.children_at(self.ids.get(id)?)
+    }
+
+    /// Get the names and nodes inside the directory at the key, sorted by name.
+    /// Returns None if there is no directory at the key.
+    pub fn children_at(&self, key: &str) -> Option<Vec<(&str, &Node)>> {
+        if self.nodes.get(key)?.kind != NodeKind::Directory {
+            return None;
+        }
+
+        let prefix = match key.is_empty() {
+            true => String::new(),
+            false => format!("{}/", key),
+        };
+        Some(
+            self.nodes
+                .range(prefix.clone()..)
+                .take_while(|(child, _)| child.starts_with(&prefix))
+                .filter_map(|(child, node)| {
+                    let name = &child[prefix.len()..];
+                    (!name.is_empty() && !name.contains('/')).then_some((name, node))
+                })
+                .collect(),
+        )
+    }
+
+    /// Remove the node at the key, and everything under it if it is a directory.
+    /// A directory with contents is only removed if recursive is set.
+    pub fn remove(&mut self, key: &str, recursive: bool) -> Result<(), RemoveError> {
+        if key.is_empty() {
+            return Err(RemoveError::IsRoot);
+        }
+        let node = self.nodes.get(key).ok_or(RemoveError::NotFound)?;
+        let descendants = match node.kind {
+            NodeKind::File(_) => Vec::new(),
+            NodeKind::Directory => self.descendants(key),
+        };
+        if !descendants.is_empty() && !recursive {
+            return Err(RemoveError::NotEmpty);
+        }
+
+        self.touch();
+        for key in descendants.iter().map(String::as_str).chain([key]) {
+            if let Some(node) = self.nodes.remove(key) {
+                self.ids.remove(&node.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the node at a path and everything under it to another path, keeping their ids.
+    /// A file at the new path is replaced.
+    pub fn rename(&mut self, from: &ResolvedPath, to: &ResolvedPath) -> Result<(), RenameError> {
+        let (from_key, to_key) = (from.key(), to.key());
+        let node = self.nodes.get(&from_key).ok_or(RenameError::NotFound)?;
+        let is_dir = node.kind == NodeKind::Directory;
+        if from_key.is_empty() || to_key.is_empty() {
+            return Err(RenameError::Invalid);
+        }
+        if from_key == to_key {
+            return Ok(());
+        }
+        if is_dir && to.components.starts_with(&from.components) {
+            return Err(RenameError::Invalid);
+        }
+        match self.nodes.get(&to.parent_key().unwrap_or_default()) {
+            Some(Node {
+                kind: NodeKind::Directory,
+                ..
+            }) => {}
+            _ => return Err(RenameError::NoParent),
+        }
+        match self.nodes.get(&to_key).map(|node| &node.kind) {
+            None => {}
+            Some(NodeKind::File(_)) if !is_dir => {
+                self.remove(&to_key, false).ok();
+            }
+            Some(_) => return Err(RenameError::Exists),
+        }
+
+        let moved = self
+            .descendants(&from_key)
+            .into_iter()
+            .chain([from_key.clone()]);
+        for key in moved.collect::<Vec<_>>() {
+            let node = self.nodes.remove(&key).unwrap();
+            let key = format!("{}{}", to_key, &key[from_key.len()..]);
+            self.insert(key, node);
+        }
+        Ok(())
+    }
+
+    /// Get the keys of every node under the directory at the key
+    fn descendants(&self, key: &str) -> Vec<String> {
+        let prefix = format!("{}/", key);
+        self.nodes
+            .range(prefix.clone()..)
+            .take_while(|(child, _)| child.starts_with(&prefix))
+            .map(|(child, _)| child.clone())
+            .collect()
+    }
+
+    /// Get the contents of the file at the key
+    pub fn read(&self, key: &str) -> Option<&[u8]> {
+        match &self.nodes.get(key)?.kind {
+            NodeKind::File(data) => Some(data),
+            NodeKind::Directory => None,
+        }
+    }
+
+    /// Iterate over all the nodes and their keys
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, &Node)> {
+        self.nodes.iter().map(|(key, node)| (key.as_str(), node))
+    }
+
+    /// The amount of bytes stored in files
+    pub fn used(&self) -> usize {
+        self.nodes
+            .values()
+            .map(|node| match &node.kind {
+                NodeKind::File(data) => data.len(),
+                NodeKind::Directory => 0,
+            })
+            .sum()
+    }
+
+    /// Check if a file can grow from one length to another without going over the capacity
+    pub fn fits(&self, old_len: usize, new_len: usize) -> bool {
+        match self.capacity {
+            Some(capacity) => self.used() - old_len + new_len <= capacity || new_len <= old_len,
+            None => true,
+        }
+    }
+
+    fn insert(&mut self, key: String, node: Node) {
+        self.touch();
+        self.ids.insert(node.id.clone(), key.clone());
+        self.nodes.insert(key, node);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use crate::fs::fslabel::FsLabel;
+
+use super::ramfs::ResolvedPath;
+
+/// The most changes queued for a watcher. One overflow change is queued after them once it is full.
+pub const MAX_CHANGES: usize = 1024;
+
+/// A change made to a file system, with the keys of the paths it changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimChange {
+    Create(String),
+    Modify(String),
+    Remove(String),
+    Rename(String, String),
+    /// Later changes were dropped because the queue was full, with the watched key
+    Overflow(String),
+}
+
+impl SimChange {
+    /// Encode the change as it is written by `hapi_fs_watch_poll`, with absolute paths
+    pub fn encode(&self, label: FsLabel, buffer: &mut Vec<u8>) {
+        let (kind, path, to) = match self {
+            Self::Create(key) => (0u8, key, None),
+            Self::Modify(key) => (1, key, None),
+            Self::Remove(key) => (2, key, None),
+            Self::Rename(from, to) => (3, from, Some(to)),
+            Self::Overflow(key) => (4, key, None),
+        };
+        let path = ResolvedPath::from_key(label, path).to_string();
+        let to = to.map_or_else(String::new, |to| {
+            ResolvedPath::from_key(label, to).to_string()
+        });
+        buffer.push(kind);
+        buffer.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(path.as_bytes());
+        buffer.extend_from_slice(&(to.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(to.as_bytes());
+    }
+}
+
+/// A watched file or directory, and the changes to it that have not been polled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimWatcher {
+    pub pid: String,
+    pub label: FsLabel,
+    pub key: String,
+    pub recursive: bool,
+    pub changes: Vec<SimChange>,
+}
+
+impl SimWatcher {
+    /// Queue a change, or an overflow change instead once the queue is full
+    fn queue(&mut self, change: SimChange) {
+        match self.changes.len() {
+            len if len < MAX_CHANGES => self.changes.push(change),
+            MAX_CHANGES => self.changes.push(SimChange::Overflow(self.key.clone())),
+            _ => {}
+        }
+    }
+
+    /// Check if a change at a key is seen by the watcher.
+    /// A removed or renamed parent takes the watched path with it, so it is seen too.
+    fn sees(&self, key: &str, moved: bool) -> bool {
+        let inside = |outer: &str, inner: &str| {
+            outer.is_empty() && !inner.is_empty()
+                || inner
+                    .strip_prefix(outer)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        };
+        let parent = key.rsplit_once('/').map_or("", |(parent, _)| parent);
+        key == self.key
+            || (!key.is_empty() && parent == self.key)
+            || (self.recursive && inside(&self.key, key))
+            || (moved && inside(key, &self.key))
+    }
+}
+
+/// The watchers of every process, shared so changes made by one process are seen by the others
+#[derive(Debug, Clone, Default)]
+pub struct SimWatchers {
+    watchers: HashMap<String, SimWatcher>,
+}
+
+impl SimWatchers {
+    /// Start watching a key for a process
+    pub fn watch(&mut self, id: String, pid: &str, label: FsLabel, key: String, recursive: bool) {
+        let watcher = SimWatcher {
+            pid: pid.to_owned(),
+            label,
+            key,
+            recursive,
+            changes: Vec::new(),
+        };
+        self.watchers.insert(id, watcher);
+    }
+
+    /// Get a watcher that has not been stopped
+    pub fn get(&self, id: &str) -> Option<&SimWatcher> {
+        self.watchers.get(id)
+    }
+
+    /// Get a watcher that has not been stopped mutably
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut SimWatcher> {
+        self.watchers.get_mut(id)
+    }
+
+    /// Stop a watcher, returning it if it existed
+    pub fn unwatch(&mut self, id: &str) -> Option<SimWatcher> {
+        self.watchers.remove(id)
+    }
+
+    /// Stop the watchers of a process, when it dies
+    pub fn remove_process(&mut self, pid: &str) {
+        self.watchers.retain(|_, watcher| watcher.pid != pid);
+    }
+
+    /// Stop the watchers of the file system at the label, when it is unmounted
+    pub fn remove_fs(&mut self, label: FsLabel) {
+        self.watchers.retain(|_, watcher| watcher.label != label);
+    }
+
+    /// Queue a change made to the file system at the label for every watcher that sees it
+    pub fn notify(&mut self, label: FsLabel, change: SimChange) {
+        for watcher in self.watchers.values_mut() {
+            if watcher.label != label {
+                continue;
+            }
+            let seen = match &change {
+                SimChange::Create(key) | SimChange::Modify(key) => watcher.sees(key, false),
+                SimChange::Remove(key) => watcher.sees(key, true),
+                SimChange::Rename(from, to) => watcher.sees(from, true) || watcher.sees(to, false),
+                SimChange::Overflow(_) => false,
+            };
+            if seen {
+                watcher.queue(change.clone());
+            }
+        }
+    }
+}
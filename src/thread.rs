@@ -1,8 +1,8 @@
-use std::{ffi::c_void, pin::Pin};
-
-use futures::Future;
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin};
 
 /// A wrapper around async closures
+#[cfg(feature = "async")]
 pub trait AsyncFnOnce {
     fn call_once_async(self) -> Pin<Box<dyn Future<Output = ()>>>;
 }
@@ -12,14 +12,14 @@ pub fn spawn<F>(f: F)
 where
     F: FnOnce() + Send + 'static,
 {
-    let ptr = Box::into_raw(Box::new(Box::new(f) as Box<dyn FnOnce()>));
-    unsafe { crate::ffi::hapi_thread_spawn(ptr as *const c_void) }
+    spawn_boxed(Box::new(f))
 }
 
 /// Spawn an async function on a new thread
+#[cfg(feature = "async")]
 pub fn spawn_async<F>(f: F)
 where
-    F: AsyncFnOnce,
+    F: AsyncFnOnce + 'static,
 {
     let f = Box::new(move || {
         futures::executor::block_on(async {
@@ -27,10 +27,20 @@ where
         })
     });
 
-    let ptr = Box::into_raw(Box::new(Box::new(f) as Box<dyn FnOnce()>));
-    unsafe { crate::ffi::hapi_thread_spawn(ptr as *const c_void) }
+    spawn_boxed(f)
+}
+
+/// Spawn a boxed function on a new thread.
+/// The thread inherits the host installed for the current thread.
+fn spawn_boxed(f: Box<dyn FnOnce()>) {
+    let host = crate::host::local();
+    crate::host::current().thread_spawn(Box::new(move || {
+        let _guard = host.map(crate::host::install_local_shared);
+        f()
+    }))
 }
 
+#[cfg(feature = "async")]
 impl<A, Fut> AsyncFnOnce for A
 where
     A: FnOnce() -> Fut,
@@ -1,12 +1,35 @@
+#[cfg(feature = "std")]
 use std::time::{Duration, SystemTime};
 
 /// Get the time in seconds since the start of the process or thread
 pub fn since_startup() -> f64 {
-    unsafe { crate::ffi::hapi_time_since_startup() }
+    crate::host::current().time_since_startup()
 }
 
-/// Get the sytem time
-pub fn system() -> SystemTime {
-    let since_epoch = unsafe { crate::ffi::hapi_time_since_unix_epoch() };
-    SystemTime::UNIX_EPOCH + Duration::from_secs_f64(since_epoch)
+/// Get the sytem time.
+/// Returns None when the host returns a time that a [`SystemTime`] can't hold,
+/// so a broken clock is not mistaken for the unix epoch.
+#[cfg(feature = "std")]
+pub fn system() -> Option<SystemTime> {
+    from_unix_epoch(crate::host::current().time_since_unix_epoch())
+}
+
+/// Convert seconds since the unix epoch, as the host passes times, to a system time.
+/// Returns None for times that are not a number or are too far from the epoch for a [`SystemTime`].
+#[cfg(feature = "std")]
+pub(crate) fn from_unix_epoch(secs: f64) -> Option<SystemTime> {
+    let duration = Duration::try_from_secs_f64(secs.abs()).ok()?;
+    match secs < 0.0 {
+        true => SystemTime::UNIX_EPOCH.checked_sub(duration),
+        false => SystemTime::UNIX_EPOCH.checked_add(duration),
+    }
+}
+
+/// Convert a system time to seconds since the unix epoch, to pass it to the host
+#[cfg(feature = "fs")]
+pub(crate) fn to_unix_epoch(time: SystemTime) -> f64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(error) => -error.duration().as_secs_f64(),
+    }
 }
@@ -212,9 +212,9 @@ impl KeyCode {
     }
 }
 
-impl Into<i32> for KeyCode {
-    fn into(self) -> i32 {
-        self as i32
+impl From<KeyCode> for i32 {
+    fn from(code: KeyCode) -> i32 {
+        code as i32
     }
 }
 
@@ -1,6 +1,6 @@
 //! Utility methods for terminal emulation
 
-use crate::display::KeyPress;
+use crate::{display::KeyPress, prelude::*};
 
 use super::keys::KeyCode;
 
@@ -36,17 +36,15 @@ pub fn keypressed_to_string(keys: &[KeyPress]) -> String {
                         cursor -= 1;
                         while result[cursor] != ' ' {
                             result.remove(cursor);
-                            if cursor <= 0 {
+                            if cursor == 0 {
                                 break;
                             }
                             cursor -= 1;
                         }
                     }
-                } else {
-                    if cursor > 0 {
-                        cursor -= 1;
-                        result.remove(cursor);
-                    }
+                } else if cursor > 0 {
+                    cursor -= 1;
+                    result.remove(cursor);
                 }
             }
             KeyCode::LeftArrow => {
@@ -54,16 +52,14 @@ pub fn keypressed_to_string(keys: &[KeyPress]) -> String {
                     if cursor > 0 {
                         cursor -= 1;
                         while result[cursor] != ' ' {
-                            if cursor <= 0 {
+                            if cursor == 0 {
                                 break;
                             }
                             cursor -= 1;
                         }
                     }
                 } else {
-                    if cursor > 0 {
-                        cursor -= 1;
-                    }
+                    cursor = cursor.saturating_sub(1);
                 }
             }
             KeyCode::RightArrow => {
@@ -74,10 +70,8 @@ pub fn keypressed_to_string(keys: &[KeyPress]) -> String {
                             cursor += 1;
                         }
                     }
-                } else {
-                    if cursor < result.len() {
-                        cursor += 1;
-                    }
+                } else if cursor < result.len() {
+                    cursor += 1;
                 }
             }
             _ => {
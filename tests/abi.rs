@@ -0,0 +1,41 @@
+use hapi::host::{self, Capability, ABI_VERSION};
+
+fn last_log() -> String {
+    hapi::sim::with_kernel(|kernel, _| kernel.console.logs.last().unwrap().1.clone())
+}
+
+#[test]
+fn version_and_capabilities() {
+    assert_eq!(host::abi_version(), ABI_VERSION);
+    assert!(Capability::ALL
+        .iter()
+        .all(|capability| host::supports(*capability)));
+
+    hapi::sim::with_kernel(|kernel, _| {
+        kernel.capabilities &= !Capability::NetworkLocal.bit();
+        kernel.abi_version = 1;
+    });
+    assert!(!host::supports(Capability::NetworkLocal));
+    assert!(host::supports(Capability::Network));
+    assert_eq!(host::abi_version(), 1);
+    assert!(Capability::Ramfs.calls().contains(&"hapi_fs_init_ramfs"));
+}
+
+#[test]
+fn v2_strings_keep_null_bytes() {
+    hapi::print!("a\0b");
+    assert_eq!(hapi::sim::stdout(), "a\0b");
+
+    hapi::js::log::info("x\0y");
+    assert_eq!(last_log(), "x\0y");
+}
+
+#[test]
+fn v1_fallback_cuts_strings_at_null() {
+    hapi::sim::with_kernel(|kernel, _| kernel.abi_version = 2);
+    hapi::print!("a\0b");
+    assert_eq!(hapi::sim::stdout(), "a");
+
+    hapi::js::log::info("x\0y");
+    assert_eq!(last_log(), "x");
+}
@@ -0,0 +1,159 @@
+use hapi::fs::{
+    archive::{self, Format},
+    dir::Directory,
+    error::Error,
+    fslabel::FsLabel,
+    File, RamFileSystem,
+};
+
+/// 100000 bytes of `a`, deflated with a dynamic huffman block
+fn deflated() -> Vec<u8> {
+    let mut data = vec![
+        237, 193, 49, 1, 0, 0, 0, 194, 160, 172, 235, 95, 194, 26, 30, 64, 1,
+    ];
+    data.extend_from_slice(&[0; 96]);
+    data.extend_from_slice(&[175, 6]);
+    data
+}
+
+/// A zip archive with a single deflated file, declaring `len` bytes once inflated
+fn zip(name: &str, data: &[u8], crc: u32, len: u32) -> Vec<u8> {
+    let mut common = Vec::new();
+    for field in [20u16, 0, 8, 0, 0x21] {
+        common.extend_from_slice(&field.to_le_bytes());
+    }
+    for field in [crc, data.len() as u32, len] {
+        common.extend_from_slice(&field.to_le_bytes());
+    }
+    common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    common.extend_from_slice(&0u16.to_le_bytes());
+
+    let mut archive = 0x04034b50u32.to_le_bytes().to_vec();
+    archive.extend_from_slice(&common);
+    archive.extend_from_slice(name.as_bytes());
+    archive.extend_from_slice(data);
+    let offset = archive.len() as u32;
+
+    let mut directory = 0x02014b50u32.to_le_bytes().to_vec();
+    directory.extend_from_slice(&20u16.to_le_bytes());
+    directory.extend_from_slice(&common);
+    directory.extend_from_slice(&[0; 14]);
+    directory.extend_from_slice(name.as_bytes());
+
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&offset.to_le_bytes());
+    archive.extend_from_slice(&[0, 0]);
+    archive
+}
+
+fn read(path: &str) -> Vec<u8> {
+    File::open(path).unwrap().read_all().unwrap()
+}
+
+#[test]
+fn pack_and_unpack() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/src").unwrap();
+    Directory::create("a:/src/empty").unwrap();
+    Directory::create("a:/src/sub").unwrap();
+    File::create("a:/src/top.txt")
+        .unwrap()
+        .write(0, b"top")
+        .unwrap();
+    File::create("a:/src/sub/inner.bin")
+        .unwrap()
+        .write(0, &[0, 1, 2, 255])
+        .unwrap();
+    let long = format!("a:/src/sub/{}", "x".repeat(120));
+    File::create(long).unwrap().write(0, b"long").unwrap();
+
+    for format in [Format::Tar, Format::Zip] {
+        let packed = archive::pack_as("a:/src", format).unwrap();
+        assert_eq!(Format::detect(&packed), Some(format));
+        let dest = format!("a:/out/{:?}", format);
+        archive::unpack(&packed, &dest).unwrap();
+        assert_eq!(read(&format!("{}/top.txt", dest)), b"top");
+        assert_eq!(read(&format!("{}/sub/inner.bin", dest)), [0, 1, 2, 255]);
+        assert_eq!(read(&format!("{}/sub/{}", dest, "x".repeat(120))), b"long");
+        Directory::open(format!("{}/empty", dest)).unwrap();
+    }
+
+    assert!(matches!(
+        archive::unpack(b"garbage", "a:/garbage"),
+        Err(Error::InvalidArchive(_))
+    ));
+}
+
+#[test]
+fn deflated_entries_are_limited_to_their_length() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let data = deflated();
+    archive::unpack(&zip("a", &data, 467860103, 100000), "a:/").unwrap();
+    assert_eq!(read("a:/a"), vec![b'a'; 100000]);
+
+    // Claiming a small length must not inflate the whole stream first
+    let bomb = zip("bomb", &data, 467860103, 1000);
+    assert!(matches!(
+        archive::unpack(&bomb, "a:/"),
+        Err(Error::InvalidArchive(message)) if message.contains("larger than its entry")
+    ));
+    assert!(File::open("a:/bomb").is_err());
+}
+
+#[test]
+fn zip_offsets_past_the_end_are_invalid() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let archive = zip("a", &deflated(), 467860103, 100000);
+    let central = archive.len() - 22 - 46 - 1;
+
+    let mut local = archive.clone();
+    local[central + 42..central + 46].copy_from_slice(&u32::MAX.to_le_bytes());
+    let mut compressed = archive.clone();
+    compressed[central + 20..central + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+    let mut directory = archive;
+    let end = directory.len() - 22;
+    directory[end + 16..end + 20].copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+
+    for archive in [local, compressed, directory] {
+        assert!(matches!(
+            archive::unpack(&archive, "a:/"),
+            Err(Error::InvalidArchive(_))
+        ));
+    }
+}
+
+#[test]
+fn unpack_overwrites_existing_files() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/src").unwrap();
+    File::create("a:/src/one")
+        .unwrap()
+        .write(0, b"new")
+        .unwrap();
+    let packed = archive::pack("a:/src").unwrap();
+
+    Directory::create("a:/dest").unwrap();
+    File::create("a:/dest/one")
+        .unwrap()
+        .write(0, b"old contents")
+        .unwrap();
+    archive::unpack(&packed, "a:/dest").unwrap();
+    assert_eq!(read("a:/dest/one"), b"new");
+}
+
+#[test]
+fn zip_rejects_long_names() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/long").unwrap();
+    let name = "x".repeat(u16::MAX as usize + 1);
+    File::create(format!("a:/long/{}", name)).unwrap();
+
+    assert!(matches!(
+        archive::pack_as("a:/long", Format::Zip),
+        Err(Error::InvalidPath(path)) if path == name
+    ));
+    archive::pack_as("a:/long", Format::Tar).unwrap();
+}
@@ -0,0 +1,81 @@
+use std::io;
+
+use hapi::{
+    display::Display,
+    fs::{self, fslabel::FsLabel, File, RamFileSystem},
+    Error, ErrorKind,
+};
+
+#[test]
+fn fs_errors_convert() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let error: Error = RamFileSystem::init(FsLabel::A).unwrap_err().into();
+    assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(error.code(), 2);
+
+    // Missing files are reported by several imports, so the call is not known
+    let error: Error = File::open("a:/missing").unwrap_err().into();
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert_eq!(error.call(), None);
+    let error: Error = fs::remove_file("a:/missing").unwrap_err().into();
+    assert_eq!(error.call(), None);
+
+    let error: Error = RamFileSystem::unmount(FsLabel::B).unwrap_err().into();
+    assert_eq!(error.kind(), ErrorKind::NotMounted);
+}
+
+#[test]
+fn display_errors_keep_the_call() {
+    let error: Error = Display::release_control().unwrap_err().into();
+    assert_eq!(error.kind(), ErrorKind::NotInControl);
+    assert_eq!(error.call(), Some("hapi_display_release_control"));
+}
+
+#[test]
+fn io_round_trip() {
+    let error = Error::new(ErrorKind::StorageFull, "hapi_fs_file_write");
+    let io_error: io::Error = error.into();
+    assert_eq!(io_error.kind(), io::ErrorKind::Other);
+    let error: Error = io_error.into();
+    assert_eq!(error.kind(), ErrorKind::StorageFull);
+    assert_eq!(error.call(), Some("hapi_fs_file_write"));
+
+    let error: Error = io::Error::from(io::ErrorKind::PermissionDenied).into();
+    assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    assert_eq!(error.io_kind(), io::ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn display_has_no_trailing_newline() {
+    let error = Error::new(ErrorKind::NotFound, "hapi_process_stdout");
+    assert_eq!(error.to_string(), "hapi_process_stdout: not found");
+    assert_eq!(Error::from(ErrorKind::Other).to_string(), "other error");
+
+    let fs_error = fs::error::Error::NoSuchFile("a:/missing".to_owned());
+    assert_eq!(
+        Error::from(fs_error).to_string(),
+        "No such file: a:/missing"
+    );
+    let network_error = hapi::network::NetworkError::InvalidHeaders;
+    assert_eq!(
+        Error::from(network_error).to_string(),
+        "hapi_network_request: Unable to parse request headers"
+    );
+    let display_error = hapi::display::DisplayError::Occupied;
+    assert_eq!(
+        Error::from(display_error).to_string(),
+        "hapi_display_assume_control: The display is currently occupied by another process"
+    );
+}
+
+#[test]
+fn sources_are_not_printed_twice() {
+    use std::error::Error as _;
+
+    let error = Error::from(fs::error::Error::NoSuchFile("a:/missing".to_owned()));
+    assert!(error.source().is_none());
+    assert_eq!(
+        error.get_ref().unwrap().to_string(),
+        "No such file: a:/missing"
+    );
+}
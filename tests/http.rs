@@ -0,0 +1,161 @@
+use std::time::SystemTime;
+
+use hapi::fs::{
+    self, dir::Directory, error::Error, fslabel::FsLabel, File, FsType, HttpFileSystem,
+    OverlayFileSystem, RamFileSystem,
+};
+
+/// Serve a library of files on the simulated network
+fn serve() {
+    hapi::sim::with_kernel(|kernel, _| {
+        let network = &mut kernel.network;
+        network.respond(
+            "http://assets/lib/manifest.json",
+            r#"{ "files": ["tex/grass.png", "./readme"], "directories": ["sounds/empty"] }"#,
+        );
+        network.respond("http://assets/lib/tex/grass.png", "GRASS");
+        network.respond("http://assets/lib/readme", "hi");
+        network.respond("http://assets/broken.json", r#"{ "files": ["missing"] }"#);
+        network.respond("http://assets/outside.json", r#"{ "files": ["../up"] }"#);
+        network.respond("http://assets/nothing.json", "[]");
+    });
+}
+
+/// The urls requested so far
+fn requested() -> Vec<String> {
+    hapi::sim::with_kernel(|kernel, _| {
+        let log = &kernel.network.log;
+        log.iter().map(|request| request.url.clone()).collect()
+    })
+}
+
+#[test]
+fn files_are_fetched_when_first_read() {
+    serve();
+    HttpFileSystem::init(FsLabel::B, "http://assets/lib/manifest.json").unwrap();
+    assert!(matches!(
+        HttpFileSystem::init(FsLabel::B, "http://assets/lib/manifest.json"),
+        Err(Error::FsAlreadyExists(_))
+    ));
+    Directory::open("b:/sounds/empty").unwrap();
+    let names: Vec<_> = Directory::open("b:/")
+        .unwrap()
+        .entries()
+        .unwrap()
+        .map(|entry| entry.name().to_owned())
+        .collect();
+    assert_eq!(names, ["readme", "sounds", "tex"]);
+    assert_eq!(requested().len(), 2);
+
+    let grass = File::open("b:/tex/grass.png").unwrap();
+    assert_eq!(grass.read_all().unwrap(), b"GRASS");
+    assert_eq!(grass.read_all().unwrap(), b"GRASS");
+    assert_eq!(
+        requested()[2..],
+        ["http://assets/lib/tex/grass.png".to_owned()]
+    );
+
+    RamFileSystem::init(FsLabel::A).unwrap();
+    assert_eq!(fs::copy("b:/readme", "a:/readme").unwrap(), 2);
+    assert_eq!(File::open("a:/readme").unwrap().read_all().unwrap(), b"hi");
+
+    let mounts = fs::mounts().unwrap();
+    assert_eq!(mounts[0].fs_type(), FsType::Ram);
+    assert_eq!(mounts[1].fs_type(), FsType::Http);
+    HttpFileSystem::unmount(FsLabel::B).unwrap();
+    RamFileSystem::init(FsLabel::B).unwrap();
+    File::create("b:/writable").unwrap();
+}
+
+#[test]
+fn bad_manifests_and_files() {
+    serve();
+    for (url, invalid) in [
+        ("http://assets/missing.json", false),
+        ("http://assets/outside.json", true),
+        ("http://assets/nothing.json", true),
+    ] {
+        match HttpFileSystem::init(FsLabel::B, url) {
+            Err(Error::FetchFailed(_)) => assert!(!invalid),
+            Err(Error::InvalidManifest(_)) => assert!(invalid),
+            result => panic!("{:?}", result),
+        }
+    }
+    assert!(fs::mounts().unwrap().is_empty());
+
+    HttpFileSystem::init(FsLabel::B, "http://assets/broken.json").unwrap();
+    let missing = File::open("b:/missing").unwrap();
+    assert!(matches!(missing.read_all(), Err(Error::FetchFailed(_))));
+    // A failed fetch is tried again
+    hapi::sim::with_kernel(|kernel, _| kernel.network.respond("http://assets/missing", "late"));
+    assert_eq!(missing.read_all().unwrap(), b"late");
+}
+
+#[test]
+fn changes_fail_with_read_only() {
+    serve();
+    HttpFileSystem::init(FsLabel::B, "http://assets/lib/manifest.json").unwrap();
+    RamFileSystem::init(FsLabel::A).unwrap();
+    File::create("a:/note").unwrap();
+    let read_only = |result: Result<(), Error>| {
+        assert!(
+            matches!(result, Err(Error::ReadOnly(FsLabel::B))),
+            "{:?}",
+            result
+        );
+    };
+
+    let mut readme = File::open("b:/readme").unwrap();
+    read_only(readme.write(0, b"x"));
+    read_only(readme.set_len(0));
+    read_only(File::create("b:/new").map(drop));
+    read_only(Directory::create("b:/new").map(drop));
+    read_only(fs::remove_file("b:/readme"));
+    read_only(fs::remove_dir_all("b:/tex"));
+    read_only(fs::rename("b:/readme", "b:/moved"));
+    read_only(fs::copy("a:/note", "b:/note").map(drop));
+    read_only(fs::set_modified("b:/readme", SystemTime::now()));
+    read_only(File::options().write(true).open("b:/readme").map(drop));
+    assert_eq!(readme.read_all().unwrap(), b"hi");
+
+    let error: hapi::Error = Error::ReadOnly(FsLabel::B).into();
+    assert_eq!(error.kind(), hapi::ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn overlays_fetch_everything_first() {
+    serve();
+    HttpFileSystem::init(FsLabel::B, "http://assets/lib/manifest.json").unwrap();
+    OverlayFileSystem::init(FsLabel::O, FsLabel::B).unwrap();
+    assert_eq!(requested().len(), 3);
+
+    File::open("o:/readme").unwrap().write(0, b"HI!").unwrap();
+    assert_eq!(File::open("o:/readme").unwrap().read_all().unwrap(), b"HI!");
+    assert_eq!(
+        File::open("o:/tex/grass.png").unwrap().read_all().unwrap(),
+        b"GRASS"
+    );
+    assert_eq!(File::open("b:/readme").unwrap().read_all().unwrap(), b"hi");
+}
+
+#[test]
+fn sizes_are_fetched_before_reads() {
+    serve();
+    HttpFileSystem::init(FsLabel::B, "http://assets/lib/manifest.json").unwrap();
+    assert_eq!(fs::metadata("b:/tex/grass.png").unwrap().len(), 5);
+    assert_eq!(
+        requested()[1..],
+        ["http://assets/lib/tex/grass.png".to_owned()]
+    );
+    assert!(fs::metadata("b:/tex").unwrap().is_dir());
+
+    let readme = Directory::open("b:/")
+        .unwrap()
+        .entries()
+        .unwrap()
+        .find(|entry| entry.name() == "readme")
+        .unwrap();
+    assert_eq!(requested().len(), 2);
+    assert_eq!(readme.size(), 2);
+    assert_eq!(requested()[2..], ["http://assets/lib/readme".to_owned()]);
+}
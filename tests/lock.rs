@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use hapi::{
+    fs::{self, error::Error, fslabel::FsLabel, File, RamFileSystem},
+    sim::kernel::DEFAULT_CWD,
+};
+
+/// Run a closure on a new thread as another process on the same kernel
+fn other_process<R: Send + 'static>(
+    f: impl FnOnce() -> R + Send + 'static,
+) -> (String, std::thread::JoinHandle<R>) {
+    let mut context = hapi::sim::context();
+    context.pid = hapi::sim::with_kernel(|kernel, _| kernel.spawn_process(DEFAULT_CWD, Vec::new()));
+    let pid = context.pid.clone();
+    let thread = std::thread::spawn(move || {
+        hapi::sim::enter(context);
+        f()
+    });
+    (pid, thread)
+}
+
+/// The processes holding a lock on a file, without the ones that died
+fn holders(file: &File) -> Vec<String> {
+    hapi::sim::with_kernel(|kernel, _| {
+        let processes = &kernel.processes;
+        let alive = |pid: &str| processes.get(pid).is_some_and(|process| process.alive);
+        let holders = kernel.locks.holders(FsLabel::A, file.id(), alive);
+        holders.iter().map(|(pid, _)| pid.clone()).collect()
+    })
+}
+
+#[test]
+fn waiting_blocks_until_the_lock_is_released() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let file = File::create("a:/shared").unwrap();
+    file.lock_exclusive().unwrap();
+
+    let (_, thread) = other_process(|| {
+        let file = File::open("a:/shared").unwrap();
+        assert!(matches!(file.try_lock_shared(), Err(Error::Locked(_))));
+        file.lock_shared().unwrap();
+    });
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(!thread.is_finished());
+
+    file.unlock().unwrap();
+    thread.join().unwrap();
+    assert!(matches!(file.try_lock(), Err(Error::Locked(_))));
+    file.lock_shared().unwrap();
+}
+
+#[test]
+fn dead_holders_are_pruned() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let file = File::create("a:/log").unwrap();
+    let (pid, thread) = other_process(|| File::open("a:/log").unwrap().try_lock().unwrap());
+    thread.join().unwrap();
+    assert_eq!(holders(&file), [pid.clone()]);
+
+    hapi::sim::with_kernel(|kernel, _| kernel.processes.kill(&pid));
+    assert!(holders(&file).is_empty());
+    file.try_lock().unwrap();
+}
+
+#[test]
+fn removing_a_file_releases_its_locks() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let removed = File::create("a:/removed").unwrap();
+    removed.try_lock().unwrap();
+    fs::remove_file("a:/removed").unwrap();
+    assert!(holders(&removed).is_empty());
+
+    // So does replacing it with a rename
+    let replaced = File::create("a:/replaced").unwrap();
+    replaced.try_lock_shared().unwrap();
+    File::create("a:/new").unwrap();
+    fs::rename("a:/new", "a:/replaced").unwrap();
+    assert!(holders(&replaced).is_empty());
+
+    let locked = hapi::sim::with_kernel(|kernel, _| kernel.locks.locked_files(FsLabel::A));
+    assert!(locked.is_empty());
+}
@@ -0,0 +1,89 @@
+use hapi::abi::Imports;
+use hapi::fs::{
+    self,
+    dir::{Directory, EntryKind},
+    error::Error,
+    fslabel::FsLabel,
+    File, RamFileSystem,
+};
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn metadata() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let t0 = hapi::time::system().unwrap();
+    let mut f = File::create("a:/f").unwrap();
+    hapi::sim::advance_time(Duration::from_secs(10));
+    f.write(0, b"hello").unwrap();
+    let m = fs::metadata("a:/f").unwrap();
+    assert!(m.is_file() && !m.read_only());
+    assert_eq!(m.len(), 5);
+    let close = |a: SystemTime, b: SystemTime| {
+        let d = a.duration_since(b).unwrap_or_else(|e| e.duration());
+        d < Duration::from_millis(1)
+    };
+    assert!(close(m.created(), t0));
+    assert!(close(m.modified(), t0 + Duration::from_secs(10)));
+    assert!(close(m.accessed(), t0));
+    hapi::sim::advance_time(Duration::from_secs(5));
+    f.read_all().unwrap();
+    assert!(close(
+        fs::metadata("a:/f").unwrap().accessed(),
+        t0 + Duration::from_secs(15)
+    ));
+
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    fs::set_modified("a:/f", old).unwrap();
+    assert!(close(fs::metadata("a:/f").unwrap().modified(), old));
+    let before = SystemTime::UNIX_EPOCH - Duration::from_secs(100);
+    fs::set_modified("a:/f", before).unwrap();
+    assert!(close(fs::metadata("a:/f").unwrap().modified(), before));
+
+    Directory::create("a:/d").unwrap();
+    let d = fs::metadata("a:/d").unwrap();
+    assert_eq!(d.kind(), EntryKind::Directory);
+    assert!(d.is_empty());
+    assert!(fs::metadata("a:/").unwrap().is_dir());
+    assert!(matches!(fs::metadata("a:/nope"), Err(Error::NoSuchFile(_))));
+    assert!(matches!(
+        fs::metadata("c:/nope"),
+        Err(Error::NoSuchFs(FsLabel::C))
+    ));
+    assert!(matches!(
+        fs::set_modified("a:/nope", old),
+        Err(Error::NoSuchFile(_))
+    ));
+    hapi::sim::with_kernel(|k, _| {
+        let fsys = k.filesystems.get_mut(&FsLabel::A).unwrap();
+        let id = fsys.get("f").unwrap().id.clone();
+        fsys.get_by_id_mut(&id).unwrap().read_only = true;
+    });
+    assert!(fs::metadata("a:/f").unwrap().read_only());
+}
+
+#[test]
+fn extreme_times_do_not_panic() {
+    hapi::sim::with_kernel(|kernel, _| kernel.clock.epoch = 1e300);
+    assert_eq!(hapi::time::system(), None);
+    hapi::sim::with_kernel(|kernel, _| kernel.clock.epoch = -1e300);
+    assert_eq!(hapi::time::system(), None);
+    hapi::sim::with_kernel(|kernel, _| kernel.clock.epoch = 0.0);
+
+    RamFileSystem::init(FsLabel::A).unwrap();
+    File::create("a:/f").unwrap();
+    hapi::sim::with_imports(|imports| imports.fs_set_modified("a:/f", f64::INFINITY));
+    assert!(matches!(fs::metadata("a:/f"), Err(Error::InvalidMetadata)));
+}
+
+#[test]
+fn extreme_times_are_not_saved() {
+    let dir = std::env::temp_dir().join(format!("hapi-extreme-times-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    hapi::sim::set_storage_dir(&dir);
+    fs::PersistentFileSystem::init(FsLabel::P).unwrap();
+    File::create("p:/f").unwrap().write(0, b"data").unwrap();
+    hapi::sim::with_imports(|imports| imports.fs_set_modified("p:/f", 1e300));
+    hapi::sim::save();
+    assert_eq!(std::fs::read(dir.join("p/f")).unwrap(), b"data");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
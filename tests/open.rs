@@ -0,0 +1,179 @@
+use hapi::fs::{
+    dir::Directory, error::Error, fslabel::FsLabel, File, OpenOptions, OverlayFileSystem,
+    RamFileSystem,
+};
+use std::io::Write;
+
+#[test]
+fn open_options() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    assert!(matches!(
+        OpenOptions::new().open("a:/f"),
+        Err(Error::InvalidOpenOptions(_))
+    ));
+    assert!(matches!(
+        OpenOptions::new().read(true).create(true).open("a:/f"),
+        Err(Error::InvalidOpenOptions(_))
+    ));
+    assert!(matches!(
+        OpenOptions::new().append(true).truncate(true).open("a:/f"),
+        Err(Error::InvalidOpenOptions(_))
+    ));
+    assert!(matches!(
+        OpenOptions::new().read(true).open("a:/f"),
+        Err(Error::NoSuchFile(_))
+    ));
+
+    let mut f = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open("a:/f")
+        .unwrap();
+    f.write(0, b"hello world").unwrap();
+    assert!(matches!(f.read(0, 1), Err(Error::NotReadable(_))));
+    assert!(matches!(
+        OpenOptions::new().write(true).create_new(true).open("a:/f"),
+        Err(Error::FileExists(_))
+    ));
+    let g = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open("a:/f")
+        .unwrap();
+    assert_eq!(g, f);
+    assert_eq!(g.read_all().unwrap(), b"hello world");
+    let mut r = File::options().read(true).open("a:/f").unwrap();
+    assert!(matches!(r.write(0, b"x"), Err(Error::NotWritable(_))));
+    assert!(matches!(r.set_len(0), Err(Error::NotWritable(_))));
+
+    let mut a = OpenOptions::new().append(true).open("a:/f").unwrap();
+    a.write(0, b"!").unwrap();
+    let mut c = a.cursor();
+    c.write_all(b"?").unwrap();
+    assert_eq!(c.position(), 13);
+    assert_eq!(g.read_all().unwrap(), b"hello world!?");
+
+    let mut t = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open("a:/f")
+        .unwrap();
+    assert_eq!(g.size().unwrap(), 0);
+    t.write(0, b"abc").unwrap();
+    t.set_len(5).unwrap();
+    assert_eq!(g.read_all().unwrap(), b"abc\0\0");
+    t.set_len(1).unwrap();
+    assert_eq!(g.read_all().unwrap(), b"a");
+
+    Directory::create("a:/d").unwrap();
+    assert!(matches!(
+        OpenOptions::new().write(true).create(true).open("a:/d"),
+        Err(Error::FileExists(_))
+    ));
+    assert!(matches!(
+        OpenOptions::new().write(true).create(true).open("a:/x/y"),
+        Err(Error::NoSuchDirectory(_))
+    ));
+    assert!(matches!(
+        OpenOptions::new().write(true).create(true).open("c:/x"),
+        Err(Error::NoSuchFs(FsLabel::C))
+    ));
+    assert_eq!(
+        hapi::Error::from(r.write(0, b"").unwrap_err()).io_kind(),
+        std::io::ErrorKind::PermissionDenied
+    );
+}
+
+fn in_upper_layer(key: &str) -> bool {
+    hapi::sim::with_kernel(|kernel, _| kernel.fs(FsLabel::O).unwrap().get(key).is_some())
+}
+
+#[test]
+fn failed_opens_on_an_overlay_copy_nothing_up() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/assets").unwrap();
+    File::create("a:/assets/config")
+        .unwrap()
+        .write(0, b"default")
+        .unwrap();
+    OverlayFileSystem::init(FsLabel::O, FsLabel::A).unwrap();
+
+    let missing = OpenOptions::new().write(true).open("o:/assets/missing");
+    assert!(matches!(missing, Err(Error::NoSuchFile(_))));
+    assert!(!in_upper_layer("assets"));
+
+    let options = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .truncate(true)
+        .open("o:/assets/config");
+    assert!(matches!(options, Err(Error::FileExists(_))));
+    assert!(!in_upper_layer("assets/config"));
+    assert_eq!(
+        File::open("o:/assets/config").unwrap().read_all().unwrap(),
+        b"default"
+    );
+
+    OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open("o:/assets/config")
+        .unwrap();
+    assert!(in_upper_layer("assets/config"));
+    assert_eq!(
+        File::open("o:/assets/config").unwrap().read_all().unwrap(),
+        b""
+    );
+    assert_eq!(
+        File::open("a:/assets/config").unwrap().read_all().unwrap(),
+        b"default"
+    );
+
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open("o:/assets/new")
+        .unwrap();
+    assert!(in_upper_layer("assets/new"));
+}
+
+#[test]
+fn appending_files_are_writable() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let mut log = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open("a:/log")
+        .unwrap();
+    log.write(0, b"first").unwrap();
+    log.set_len(0).unwrap();
+    log.write(100, b"second").unwrap();
+    assert_eq!(File::open("a:/log").unwrap().read_all().unwrap(), b"second");
+}
+
+#[test]
+fn appends_never_overwrite_each_other() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    File::create("a:/log").unwrap();
+    let threads: Vec<_> = [b"a", b"b"]
+        .into_iter()
+        .map(|line| {
+            let context = hapi::sim::context();
+            std::thread::spawn(move || {
+                hapi::sim::enter(context);
+                let mut log = OpenOptions::new().append(true).open("a:/log").unwrap();
+                for _ in 0..200 {
+                    log.write(0, line).unwrap();
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let log = File::open("a:/log").unwrap().read_all().unwrap();
+    assert_eq!(log.len(), 400);
+    assert_eq!(log.iter().filter(|byte| **byte == b'a').count(), 200);
+}
@@ -0,0 +1,100 @@
+use hapi::fs::{
+    dir::Directory, error::Error, fslabel::FsLabel, File, HoneyPath, HoneyPathBuf, RamFileSystem,
+};
+
+#[test]
+fn paths() {
+    let p = HoneyPath::new("a:/x/./y/../z");
+    assert_eq!(p.label(), Some(FsLabel::A));
+    assert_eq!(p.normalize().as_str(), "a:/x/z");
+    assert_eq!(HoneyPath::new("A:/../..").normalize().as_str(), "a:/");
+    assert_eq!(HoneyPath::new("../x/..//y").normalize().as_str(), "../y");
+    assert_eq!(HoneyPath::new("x").label(), None);
+    assert_eq!(HoneyPath::new("").label(), None);
+    assert_eq!(HoneyPath::new("ab:c").label(), None);
+    assert_eq!(HoneyPath::new("?:/").label(), None);
+    assert!(FsLabel::extract_from_path("b").is_err());
+    assert_eq!(HoneyPath::new("a:/x/y").parent().unwrap().as_str(), "a:/x");
+    assert_eq!(HoneyPath::new("a:/x").parent().unwrap().as_str(), "a:/");
+    assert_eq!(HoneyPath::new("a:/").parent(), None);
+    assert_eq!(HoneyPath::new("a:/x/y.txt/").file_name(), Some("y.txt"));
+    assert_eq!(HoneyPath::new("a:/x").join("y").as_str(), "a:/x/y");
+    assert_eq!(HoneyPath::new("a:/").join("y").as_str(), "a:/y");
+    assert_eq!(HoneyPath::new("a:/x").join("b:/q").as_str(), "b:/q");
+    let mut b = HoneyPathBuf::from("a:/x/y");
+    assert!(b.pop());
+    assert_eq!(b.as_str(), "a:/x");
+    assert!(b.pop());
+    assert_eq!(b.as_str(), "a:/");
+    assert!(!b.pop());
+    b.push("q");
+    assert_eq!(b.to_string(), "a:/q");
+    let comps: Vec<_> = HoneyPath::new("a:/x/./y/").components().collect();
+    assert_eq!(comps, ["x", "y"]);
+
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/home").unwrap();
+    Directory::create("a:/home/docs").unwrap();
+    hapi::process::set_cwd("a:/home").unwrap();
+    hapi::process::set_cwd("docs/../docs/.").unwrap();
+    assert_eq!(hapi::process::cwd().unwrap(), "a:/home/docs");
+    let f = File::create("notes").unwrap();
+    assert_eq!(File::open("a:/home/docs/notes").unwrap(), f);
+    assert_eq!(File::open("../docs/notes").unwrap(), f);
+    let d = Directory::open("..").unwrap();
+    assert_eq!(d, Directory::open("a:/home").unwrap());
+    Directory::create("sub").unwrap();
+    hapi::fs::rename("notes", "sub/notes").unwrap();
+    let std_path = std::path::Path::new("a:/home/docs/sub/notes");
+    let path = <&HoneyPath>::try_from(std_path).unwrap();
+    assert_eq!(File::open(path).unwrap(), f);
+    let path = HoneyPathBuf::try_from(std_path.to_path_buf()).unwrap();
+    assert_eq!(File::open(path).unwrap(), f);
+    assert_eq!(File::open(HoneyPathBuf::from("sub/notes")).unwrap(), f);
+}
+
+#[cfg(unix)]
+#[test]
+fn only_ascii_letters_are_labels() {
+    // The kelvin sign lowercases to `k`, but is three bytes long
+    let kelvin = HoneyPath::new("\u{212A}:/x");
+    assert_eq!(kelvin.label(), None);
+    assert_eq!(kelvin.components().collect::<Vec<_>>(), ["\u{212A}:", "x"]);
+    assert_eq!(kelvin.parent().map(HoneyPath::as_str), Some("\u{212A}:"));
+    assert!("\u{212A}".parse::<FsLabel>().is_err());
+    assert_eq!(HoneyPath::new("K:/x").label(), Some(FsLabel::K));
+}
+
+#[test]
+fn non_utf8_paths_fail_to_convert() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::Path};
+
+    let path = Path::new(OsStr::from_bytes(b"a:/\xff"));
+    assert!(matches!(
+        <&HoneyPath>::try_from(path),
+        Err(Error::InvalidPath(_))
+    ));
+    assert!(matches!(
+        HoneyPathBuf::try_from(path.to_path_buf()),
+        Err(Error::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn walk_entries_have_paths() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/d").unwrap();
+    Directory::create("a:/d/sub").unwrap();
+    File::create("a:/d/sub/x").unwrap();
+
+    let paths: Vec<_> = Directory::open("a:/d")
+        .unwrap()
+        .walk()
+        .map(|entry| entry.unwrap().path().to_owned())
+        .collect();
+    assert_eq!(
+        paths,
+        [HoneyPathBuf::from("sub"), HoneyPathBuf::from("sub/x")]
+    );
+    assert_eq!(paths[1].file_name(), Some("x"));
+}
@@ -0,0 +1,41 @@
+use hapi::fs::{dir::Directory, fslabel::FsLabel, File, PersistentFileSystem};
+
+fn names(path: &str) -> Vec<String> {
+    let entries = Directory::open(path).unwrap().entries().unwrap();
+    let mut names: Vec<_> = entries.map(|entry| entry.name().to_owned()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn saved_on_explicit_save() {
+    let dir = std::env::temp_dir().join(format!("hapi-persistent-save-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    hapi::sim::set_storage_dir(&dir);
+    PersistentFileSystem::init(FsLabel::P).unwrap();
+    File::create("p:/f").unwrap().write(0, b"data").unwrap();
+    assert!(!dir.join("p/f").exists());
+
+    hapi::sim::save();
+    assert_eq!(std::fs::read(dir.join("p/f")).unwrap(), b"data");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_skipped_on_load() {
+    let dir = std::env::temp_dir().join(format!("hapi-persistent-links-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("p/sub")).unwrap();
+    std::fs::write(dir.join("p/sub/f"), b"data").unwrap();
+    std::os::unix::fs::symlink(dir.join("p/sub"), dir.join("p/dir_link")).unwrap();
+    std::os::unix::fs::symlink(dir.join("p/sub/f"), dir.join("p/file_link")).unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("p/sub/cycle")).unwrap();
+
+    hapi::sim::set_storage_dir(&dir);
+    PersistentFileSystem::init(FsLabel::P).unwrap();
+    assert_eq!(names("p:/"), ["sub"]);
+    assert_eq!(names("p:/sub"), ["f"]);
+    assert_eq!(File::open("p:/sub/f").unwrap().read_all().unwrap(), b"data");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
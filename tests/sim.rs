@@ -0,0 +1,115 @@
+use hapi::{
+    fs::{dir::Directory, fslabel::FsLabel, File, RamFileSystem},
+    network::{NetworkError, Request, RequestMethod, RequestStatus},
+    process::Process,
+};
+
+#[test]
+fn fs_create_read_write_list() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/docs").unwrap();
+    let mut file = File::create("a:/docs/hello.txt").unwrap();
+    file.write(0, b"Hello, world").unwrap();
+    file.write(7, b"sim").unwrap();
+    File::create("a:/docs/empty").unwrap();
+
+    let file = File::open("a:/docs/hello.txt").unwrap();
+    assert_eq!(file.read_all().unwrap(), b"Hello, simld");
+    assert_eq!(file.read(7, 3).unwrap(), b"sim");
+
+    let names: Vec<_> = Directory::open("a:/docs")
+        .unwrap()
+        .entries()
+        .unwrap()
+        .map(|entry| entry.name().to_owned())
+        .collect();
+    assert_eq!(names, ["empty", "hello.txt"]);
+
+    let contents = hapi::sim::with_kernel(|kernel, _| {
+        kernel
+            .fs(FsLabel::A)
+            .unwrap()
+            .read("docs/hello.txt")
+            .unwrap()
+            .to_vec()
+    });
+    assert_eq!(contents, b"Hello, simld");
+}
+
+#[test]
+fn fs_errors() {
+    assert!(File::create("b:/missing").is_err());
+    RamFileSystem::init(FsLabel::B).unwrap();
+    assert!(RamFileSystem::init(FsLabel::B).is_err());
+    assert!(File::open("b:/missing").is_err());
+    assert!(File::create("b:/no/parent").is_err());
+}
+
+#[test]
+fn process_spawn() {
+    hapi::stdout::write("Hello");
+    assert_eq!(hapi::sim::stdout(), "Hello");
+
+    let pid = hapi::process::pid().unwrap();
+    let sub = Process::spawn_sub(b"\0asm").unwrap();
+    assert_ne!(sub.pid(), pid);
+    assert!(sub.alive());
+    assert_eq!(sub.stdout().unwrap(), "");
+
+    let binary = hapi::sim::with_kernel(|kernel, _| {
+        kernel.processes.get(&sub.pid()).unwrap().binary.clone()
+    });
+    assert_eq!(binary, b"\0asm");
+
+    hapi::sim::with_kernel(|kernel, _| kernel.processes.kill(&sub.pid()));
+    assert!(!sub.alive());
+}
+
+#[test]
+fn process_spawn_v1() {
+    let mut pid = [0u8; 37];
+    unsafe { hapi::ffi::hapi_process_spawn_subprocess(b"\0asm".as_ptr(), 4, pid.as_mut_ptr()) };
+    let pid = std::str::from_utf8(&pid[..36]).unwrap();
+    let spawned = hapi::sim::with_kernel(|kernel, _| kernel.processes.get(pid).is_some());
+    assert!(spawned);
+}
+
+#[test]
+fn network_stand_in() {
+    hapi::sim::with_kernel(|kernel, _| {
+        kernel
+            .network
+            .respond("https://example.com/data", "payload");
+    });
+
+    let request = Request::new("https://example.com/data", RequestMethod::Get, "{}").unwrap();
+    assert_eq!(request.wait().unwrap(), RequestStatus::Success);
+    assert_eq!(request.data().unwrap(), b"payload");
+
+    let missing = Request::new("https://example.com/missing", RequestMethod::Post, "{}").unwrap();
+    assert_eq!(missing.wait().unwrap(), RequestStatus::Fail);
+    assert!(matches!(
+        missing.data(),
+        Err(NetworkError::RequestFailure(_))
+    ));
+
+    let invalid = Request::new("https://example.com/data", RequestMethod::Get, "not json");
+    assert!(matches!(invalid, Err(NetworkError::InvalidHeaders)));
+
+    let urls: Vec<_> = hapi::sim::with_kernel(|kernel, _| {
+        let log = &kernel.network.log;
+        log.iter()
+            .map(|request| (request.url.clone(), request.method))
+            .collect()
+    });
+    assert_eq!(
+        urls,
+        [
+            ("https://example.com/data".to_owned(), RequestMethod::Get),
+            (
+                "https://example.com/missing".to_owned(),
+                RequestMethod::Post
+            )
+        ]
+    );
+}
@@ -0,0 +1,92 @@
+use hapi::fs::{
+    dir::Directory, error::Error, fslabel::FsLabel, metadata, File, RamFileSystem, Snapshot,
+};
+
+fn read(path: &str) -> Vec<u8> {
+    File::open(path).unwrap().read_all().unwrap()
+}
+
+fn names(path: &str) -> Vec<String> {
+    Directory::open(path)
+        .unwrap()
+        .entries()
+        .unwrap()
+        .map(|entry| entry.name().to_owned())
+        .collect()
+}
+
+#[test]
+fn take_rollback_and_restore() {
+    assert!(matches!(
+        Snapshot::take(FsLabel::A),
+        Err(Error::NoSuchFs(_))
+    ));
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let empty = Snapshot::take(FsLabel::A).unwrap();
+    Directory::create("a:/cfg").unwrap();
+    File::create("a:/cfg/x")
+        .unwrap()
+        .write(0, b"factory")
+        .unwrap();
+    let factory = Snapshot::take(FsLabel::A).unwrap();
+
+    File::open("a:/cfg/x")
+        .unwrap()
+        .write(0, b"CHANGED")
+        .unwrap();
+    File::create("a:/junk").unwrap();
+    Directory::create("a:/more").unwrap();
+    factory.rollback(FsLabel::A).unwrap();
+    assert_eq!(read("a:/cfg/x"), b"factory");
+    assert_eq!(names("a:/"), ["cfg"]);
+
+    let again = Snapshot::from_bytes(factory.clone().into_bytes()).unwrap();
+    assert!(matches!(
+        again.restore(FsLabel::A),
+        Err(Error::FsAlreadyExists(_))
+    ));
+    again.restore(FsLabel::B).unwrap();
+    assert_eq!(read("b:/cfg/x"), b"factory");
+
+    empty.rollback(FsLabel::A).unwrap();
+    assert!(names("a:/").is_empty());
+    assert!(matches!(
+        Snapshot::from_bytes(b"nope".to_vec()),
+        Err(Error::InvalidArchive(_))
+    ));
+}
+
+#[test]
+fn failed_rollback_keeps_the_old_contents() {
+    RamFileSystem::init(FsLabel::B).unwrap();
+    File::create("b:/big").unwrap().write(0, &[1; 150]).unwrap();
+    let big = Snapshot::take(FsLabel::B).unwrap();
+
+    RamFileSystem::with_capacity(FsLabel::A, 100).unwrap();
+    Directory::create("a:/cfg").unwrap();
+    File::create("a:/cfg/x").unwrap().write(0, b"mine").unwrap();
+    assert!(matches!(
+        big.rollback(FsLabel::A),
+        Err(Error::NotEnoughSpace(_))
+    ));
+    assert_eq!(names("a:/"), ["cfg"]);
+    assert_eq!(read("a:/cfg/x"), b"mine");
+}
+
+#[test]
+fn rollback_keeps_modified_times_but_not_read_only() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    File::create("a:/x").unwrap().write(0, b"x").unwrap();
+    let modified = metadata("a:/x").unwrap().modified();
+    let snapshot = Snapshot::take(FsLabel::A).unwrap();
+
+    hapi::sim::advance_time(std::time::Duration::from_secs(60));
+    snapshot.rollback(FsLabel::A).unwrap();
+    let restored = metadata("a:/x").unwrap();
+    let drift = modified
+        .duration_since(restored.modified())
+        .unwrap_or_else(|error| error.duration());
+    assert!(drift.as_secs() < 2);
+    assert!(!restored.read_only());
+    assert!(restored.created() > modified);
+}
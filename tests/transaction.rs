@@ -0,0 +1,108 @@
+use hapi::fs::{
+    self, dir::Directory, error::Error, fslabel::FsLabel, File, RamFileSystem, Transaction,
+};
+
+/// The names in a directory, including any temporary files left behind
+fn names(path: &str) -> Vec<String> {
+    let entries = Directory::open(path).unwrap().entries().unwrap();
+    entries.map(|entry| entry.name().to_owned()).collect()
+}
+
+fn read(path: &str) -> Vec<u8> {
+    File::open(path).unwrap().read_all().unwrap()
+}
+
+#[test]
+fn write_atomic_replaces_the_file() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/etc").unwrap();
+    fs::write_atomic("a:/etc/config", b"old config").unwrap();
+    let file = File::open("a:/etc/config").unwrap();
+
+    fs::write_atomic("a:/etc/config", b"new").unwrap();
+    assert_eq!(read("a:/etc/config"), b"new");
+    assert_ne!(File::open("a:/etc/config").unwrap().id(), file.id());
+    assert_eq!(names("a:/etc"), ["config"]);
+
+    assert!(fs::write_atomic("a:/missing/config", b"").is_err());
+    Directory::create("a:/etc/dir").unwrap();
+    assert!(fs::write_atomic("a:/etc/dir", b"").is_err());
+    assert_eq!(names("a:/etc"), ["config", "dir"]);
+}
+
+#[test]
+fn write_atomic_keeps_the_old_contents_when_full() {
+    RamFileSystem::with_capacity(FsLabel::A, 100).unwrap();
+    fs::write_atomic("a:/save", &[1; 60]).unwrap();
+
+    let result = fs::write_atomic("a:/save", &[2; 60]);
+    assert!(matches!(result, Err(Error::NotEnoughSpace(_))));
+    assert_eq!(read("a:/save"), [1; 60]);
+    assert_eq!(names("a:/"), ["save"]);
+}
+
+#[test]
+fn commit_makes_every_change() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/db").unwrap();
+    fs::write_atomic("a:/db/index", b"index 1").unwrap();
+    fs::write_atomic("a:/db/journal", b"journal").unwrap();
+
+    let mut transaction = Transaction::new();
+    transaction
+        .write("a:/db/index", b"index 2".to_vec())
+        .create("a:/db/page", b"page".to_vec())
+        .remove("a:/db/journal");
+    transaction.commit().unwrap();
+
+    assert_eq!(read("a:/db/index"), b"index 2");
+    assert_eq!(read("a:/db/page"), b"page");
+    assert_eq!(names("a:/db"), ["index", "page"]);
+}
+
+#[test]
+fn failed_commits_change_nothing() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    fs::write_atomic("a:/index", b"index").unwrap();
+    fs::write_atomic("a:/page", b"page").unwrap();
+
+    let mut transaction = Transaction::new();
+    transaction
+        .write("a:/index", b"changed".to_vec())
+        .remove("a:/page")
+        .remove("a:/missing");
+    assert!(matches!(transaction.commit(), Err(Error::NoSuchFile(_))));
+
+    let mut transaction = Transaction::new();
+    transaction
+        .write("a:/index", b"changed".to_vec())
+        .create("a:/new", b"new".to_vec())
+        .create("a:/page", b"page 2".to_vec());
+    assert!(matches!(transaction.commit(), Err(Error::FileExists(_))));
+
+    Directory::create("a:/dir").unwrap();
+    let mut transaction = Transaction::new();
+    transaction
+        .remove("a:/page")
+        .write("a:/dir", b"not a file".to_vec());
+    assert!(matches!(transaction.commit(), Err(Error::DirExists(_))));
+
+    assert_eq!(read("a:/index"), b"index");
+    assert_eq!(read("a:/page"), b"page");
+    assert_eq!(names("a:/"), ["dir", "index", "page"]);
+}
+
+#[test]
+fn commits_that_dont_fit_change_nothing() {
+    RamFileSystem::with_capacity(FsLabel::A, 100).unwrap();
+    fs::write_atomic("a:/a", &[1; 40]).unwrap();
+
+    let mut transaction = Transaction::new();
+    transaction.write("a:/a", [2; 40]).write("a:/b", [3; 40]);
+    assert!(matches!(
+        transaction.commit(),
+        Err(Error::NotEnoughSpace(_))
+    ));
+    assert_eq!(read("a:/a"), [1; 40]);
+    assert_eq!(names("a:/"), ["a"]);
+}
@@ -0,0 +1,200 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::{
+    task::{waker, ArcWake},
+    StreamExt,
+};
+use hapi::fs::{
+    self, dir::Directory, error::Error, fslabel::FsLabel, path::HoneyPathBuf, watch::Event, File,
+    RamFileSystem,
+};
+
+fn path(path: &str) -> HoneyPathBuf {
+    HoneyPathBuf::from(path)
+}
+
+#[test]
+fn changes_are_seen_in_order() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    Directory::create("a:/src").unwrap();
+    let mut watcher = fs::watch("a:/src", false).unwrap();
+    assert_eq!(watcher.poll().unwrap(), None);
+
+    File::create("a:/src/main.rs")
+        .unwrap()
+        .write(0, b"fn main() {}")
+        .unwrap();
+    fs::rename("a:/src/main.rs", "a:/src/lib.rs").unwrap();
+    fs::remove_file("a:/src/lib.rs").unwrap();
+
+    let events: Vec<_> = watcher.events().collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        events,
+        [
+            Event::Create(path("a:/src/main.rs")),
+            Event::Modify(path("a:/src/main.rs")),
+            Event::Rename {
+                from: path("a:/src/main.rs"),
+                to: path("a:/src/lib.rs"),
+            },
+            Event::Remove(path("a:/src/lib.rs")),
+        ]
+    );
+    assert_eq!(watcher.poll().unwrap(), None);
+}
+
+/// Create files in a new watched directory, and read the events the watcher saw
+fn create_and_read(label: FsLabel, files: usize) -> Vec<Event> {
+    RamFileSystem::init(label).unwrap();
+    let dir = format!("{}:/many", u8::from(label) as char);
+    Directory::create(&dir).unwrap();
+    let mut watcher = fs::watch(&dir, false).unwrap();
+    for i in 0..files {
+        File::create(format!("{}/{}", dir, i)).unwrap();
+    }
+    watcher.events().collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn full_queues_overflow() {
+    let events = create_and_read(FsLabel::A, 1024);
+    assert_eq!(events.len(), 1024);
+    assert_eq!(events[1023], Event::Create(path("a:/many/1023")));
+
+    let events = create_and_read(FsLabel::B, 1025);
+    assert_eq!(events.len(), 1025);
+    assert_eq!(events[1023], Event::Create(path("b:/many/1023")));
+    assert_eq!(events[1024], Event::Overflow(path("b:/many")));
+
+    let events = create_and_read(FsLabel::C, 2000);
+    assert_eq!(events.len(), 1025);
+    assert_eq!(events[1024], Event::Overflow(path("c:/many")));
+}
+
+#[test]
+fn changes_are_queued_again_after_an_overflow() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let mut watcher = fs::watch("a:/", false).unwrap();
+    for i in 0..1100 {
+        File::create(format!("a:/{}", i)).unwrap();
+    }
+    assert_eq!(watcher.events().count(), 1025);
+
+    fs::remove_file("a:/0").unwrap();
+    assert_eq!(watcher.poll().unwrap(), Some(Event::Remove(path("a:/0"))));
+}
+
+#[test]
+fn wait_blocks_until_a_change() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let mut watcher = fs::watch("a:/", false).unwrap();
+
+    let start = Instant::now();
+    assert_eq!(watcher.wait(Duration::from_millis(20)).unwrap(), None);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    hapi::thread::spawn(|| {
+        std::thread::sleep(Duration::from_millis(20));
+        File::create("a:/late").unwrap();
+    });
+    let start = Instant::now();
+    assert_eq!(
+        watcher.wait(Duration::from_secs(10)).unwrap(),
+        Some(Event::Create(path("a:/late")))
+    );
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // Queued changes are returned without waiting
+    File::create("a:/now").unwrap();
+    assert_eq!(
+        watcher.wait(Duration::from_secs(10)).unwrap(),
+        Some(Event::Create(path("a:/now")))
+    );
+}
+
+#[test]
+fn streams_wait_for_changes() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let mut watcher = fs::watch("a:/", false).unwrap();
+    hapi::thread::spawn(|| {
+        std::thread::sleep(Duration::from_millis(50));
+        File::create("a:/streamed").unwrap();
+    });
+
+    let event = futures::executor::block_on(watcher.next());
+    assert_eq!(event.unwrap().unwrap(), Event::Create(path("a:/streamed")));
+}
+
+#[test]
+fn stopped_watchers_fail() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let mut watcher = fs::watch("a:/", false).unwrap();
+    let id = watcher.id().to_owned();
+    hapi::sim::with_kernel(|kernel, _| kernel.watchers.unwatch(&id));
+
+    assert!(matches!(watcher.poll(), Err(Error::NoSuchWatcher(_))));
+    assert!(matches!(
+        watcher.wait(Duration::from_millis(1)),
+        Err(Error::NoSuchWatcher(_))
+    ));
+}
+
+/// Counts the times a task was woken
+#[derive(Default)]
+struct Wakes(AtomicUsize);
+
+impl ArcWake for Wakes {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn idle_streams_are_not_woken() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    let mut watcher = fs::watch("a:/", false).unwrap();
+    let wakes = Arc::new(Wakes::default());
+    let waker = waker(wakes.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let start = Instant::now();
+    assert!(watcher.poll_next_unpin(&mut cx).is_pending());
+    assert!(watcher.poll_next_unpin(&mut cx).is_pending());
+    assert!(start.elapsed() < Duration::from_millis(500));
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 0);
+
+    File::create("a:/woken").unwrap();
+    let start = Instant::now();
+    while wakes.0.load(Ordering::SeqCst) == 0 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let Poll::Ready(Some(event)) = watcher.poll_next_unpin(&mut cx) else {
+        panic!("the stream was woken without a change");
+    };
+    assert_eq!(event.unwrap(), Event::Create(path("a:/woken")));
+}
+
+#[test]
+fn watchers_stop_with_their_process_and_fs() {
+    RamFileSystem::init(FsLabel::A).unwrap();
+    RamFileSystem::init(FsLabel::B).unwrap();
+    let mut on_a = fs::watch("a:/", false).unwrap();
+    let mut on_b = fs::watch("b:/", false).unwrap();
+
+    RamFileSystem::unmount(FsLabel::A).unwrap();
+    assert!(matches!(on_a.poll(), Err(Error::NoSuchWatcher(_))));
+    assert_eq!(on_b.poll().unwrap(), None);
+
+    let pid = hapi::process::pid().unwrap();
+    hapi::sim::with_kernel(|kernel, _| kernel.kill(&pid));
+    assert!(matches!(on_b.poll(), Err(Error::NoSuchWatcher(_))));
+}